@@ -36,7 +36,9 @@ enum class MessageType(val value: String) {
     HEARTBEAT("HEARTBEAT"),
     ACK("ACK"),
     PAIR_REQ("PAIR_REQ"),
-    PAIR_ACK("PAIR_ACK");
+    PAIR_ACK("PAIR_ACK"),
+    FEEDBACK("FEEDBACK"),  // Delivery report from desktop
+    LANGUAGE("LANGUAGE");  // Speech recognizer language
 
     companion object {
         fun fromValue(value: String): MessageType {
@@ -75,6 +77,15 @@ data class WordPayload(
     val ts: Long = System.currentTimeMillis()
 )
 
+/**
+ * Payload for LANGUAGE messages - the speech recognizer language
+ */
+@Serializable
+data class LanguagePayload(
+    @SerialName("language")
+    val language: String
+)
+
 /**
  * Protocol message for BLE communication.
  * Matches Flutter's Message class exactly.
@@ -234,6 +245,16 @@ data class Message(
             )
         }
 
+        /**
+         * Create a language announcement message (BCP 47 tag, e.g. "cs-CZ")
+         */
+        fun language(locale: String): Message {
+            return Message(
+                messageType = MessageType.LANGUAGE,
+                payload = messageJson.encodeToString(LanguagePayload.serializer(), LanguagePayload(locale))
+            )
+        }
+
         /**
          * Create a word message with session ID
          */
@@ -112,6 +112,7 @@ class HomeViewModel @Inject constructor(
     init {
         observeInitialization()
         observeConnectionState()
+        observeRecognizerLanguage()
         observeSpeechState()
         observeSpeechResults()
         
@@ -195,6 +196,26 @@ class HomeViewModel @Inject constructor(
         }
     }
     
+    /**
+     * Announce the recognizer language on every connection and whenever it
+     * changes, so the desktop matches commands in the same language
+     */
+    private fun observeRecognizerLanguage() {
+        viewModelScope.launch {
+            combine(bleManager.connectionState, preferencesRepository.selectedLocale) { state, locale ->
+                state to locale
+            }
+                .distinctUntilChanged()
+                .filter { (state, _) -> state == BtConnectionState.CONNECTED }
+                .collect { (_, locale) ->
+                    Log.d(TAG, "Announcing recognizer language: $locale")
+                    if (!bleManager.sendMessage(Message.language(locale))) {
+                        Log.w(TAG, "Failed to announce recognizer language")
+                    }
+                }
+        }
+    }
+    
     /**
      * Observe speech recognition state changes
      */
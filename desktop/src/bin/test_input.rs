@@ -14,10 +14,10 @@
 
 //! Test utility for input injection.
 //!
-//! Usage: cargo run --bin test_input -- [text|key|combo]
+//! Usage: cargo run --bin test_input -- [text|key|combo|full|chord <keys>]
 
 use anyhow::Result;
-use prontafon_desktop::input::{create_injector, Key, Modifier};
+use prontafon_desktop::input::{create_injector, Chord, Key, Modifier};
 use std::env;
 use std::thread;
 use std::time::Duration;
@@ -49,7 +49,14 @@ fn main() -> Result<()> {
         }
         "combo" => {
             println!("Pressing Ctrl+A (select all)...");
-            injector.key_combo(&[Modifier::Ctrl], Key::A)?;
+            injector.key_combo(&[Modifier::Ctrl], Key::Letter('a'))?;
+            println!("Done!");
+        }
+        "chord" => {
+            let spec = args.get(2).map(|s| s.as_str()).unwrap_or("ctrl+a");
+            let chord: Chord = spec.parse()?;
+            println!("Pressing {}...", chord);
+            injector.press_chord(&chord)?;
             println!("Done!");
         }
         "full" => {
@@ -68,14 +75,14 @@ fn main() -> Result<()> {
             thread::sleep(Duration::from_millis(100));
 
             // Select all
-            injector.key_combo(&[Modifier::Ctrl], Key::A)?;
+            injector.key_combo(&[Modifier::Ctrl], Key::Letter('a'))?;
             thread::sleep(Duration::from_millis(500));
 
             println!("Done! Text should be selected.");
         }
         _ => {
             println!("Unknown mode: {}", mode);
-            println!("Usage: test_input [text|key|combo|full]");
+            println!("Usage: test_input [text|key|combo|full|chord <keys>]");
         }
     }
 
@@ -16,6 +16,8 @@
 
 use uuid::Uuid;
 
+use super::protocol::PROTOCOL_VERSION;
+
 /// Prontafon GATT service UUID.
 pub const SERVICE_UUID: Uuid = Uuid::from_u128(0xa1b2c3d4_e5f6_7890_abcd_ef1234567890);
 
@@ -35,6 +37,10 @@ pub const STATUS_UUID: Uuid = Uuid::from_u128(0xa1b2c3d4_e5f6_7890_abcd_ef123456
 /// Properties: Read
 pub const MTU_INFO_UUID: Uuid = Uuid::from_u128(0xa1b2c3d4_e5f6_7890_abcd_ef1234567894);
 
+/// Company identifier used for the manufacturer-data tag in advertisements.
+/// 0xFFFF is reserved by the Bluetooth SIG for internal use and testing.
+pub const MANUFACTURER_ID: u16 = 0xFFFF;
+
 /// BLE packet flags.
 pub mod flags {
     pub const FIRST: u8 = 0x08; // First packet of message
@@ -48,11 +54,55 @@ pub enum StatusCode {
     Idle = 0x00,            // Not paired
     AwaitingPairing = 0x01, // Awaiting pairing
     Paired = 0x02,          // Paired and ready
+    Rejected = 0x04,        // Pairing rejected by the user (0x03 is BUSY on Android)
+}
+
+/// Desktop input mode (for Status characteristic).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DesktopMode {
+    Dictating = 0x00,     // Typing received words
+    Paused = 0x01,        // Input is ignored
+    Recording = 0x02,     // Recording a phrase for a voice command
+    DictationOnly = 0x03, // Command phrases are typed as text
+}
+
+/// Input injection backend health (for Status characteristic).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum InjectorHealth {
+    Ok = 0x00,          // Last injection succeeded
+    Failing = 0x01,     // Last injection failed
+    Unavailable = 0x02, // Backend not usable (e.g. ydotool missing)
 }
 
-impl StatusCode {
+/// Version of the Status characteristic payload layout.
+pub const STATUS_PAYLOAD_VERSION: u8 = 1;
+
+/// Structured payload of the Status characteristic.
+///
+/// Layout (5 bytes, fits a single notification at the default MTU):
+/// - Byte 0: `StatusCode` (same position as the legacy one-byte payload)
+/// - Byte 1: `STATUS_PAYLOAD_VERSION`
+/// - Byte 2: Protocol version
+/// - Byte 3: `DesktopMode`
+/// - Byte 4: `InjectorHealth`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusPayload {
+    pub status: StatusCode,
+    pub mode: DesktopMode,
+    pub injector: InjectorHealth,
+}
+
+impl StatusPayload {
     pub fn as_bytes(&self) -> Vec<u8> {
-        vec![*self as u8]
+        vec![
+            self.status as u8,
+            STATUS_PAYLOAD_VERSION,
+            PROTOCOL_VERSION,
+            self.mode as u8,
+            self.injector as u8,
+        ]
     }
 }
 
@@ -68,6 +118,13 @@ pub mod config {
     pub const HEADER_SIZE_FIRST: usize = 4;
     pub const HEADER_SIZE_CONTINUATION: usize = 2;
 
+    /// Maximum manufacturer-data tag length, to fit a legacy 31-byte
+    /// advertisement next to the 128-bit service UUID.
+    pub const MAX_MANUFACTURER_TAG_LEN: usize = 8;
+
+    /// How often the advertising policy is re-evaluated.
+    pub const ADVERTISING_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
     /// Calculate effective payload size for a given MTU.
     pub fn effective_payload_size(mtu: usize, is_first: bool) -> usize {
         mtu - ATT_OVERHEAD
@@ -96,6 +153,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_status_payload_layout() {
+        let payload = StatusPayload {
+            status: StatusCode::Paired,
+            mode: DesktopMode::Paused,
+            injector: InjectorHealth::Unavailable,
+        };
+        let bytes = payload.as_bytes();
+
+        // Byte 0 stays compatible with clients reading the legacy status code
+        assert_eq!(bytes[0], StatusCode::Paired as u8);
+        assert_eq!(bytes[1], STATUS_PAYLOAD_VERSION);
+        assert_eq!(bytes[2], PROTOCOL_VERSION);
+        assert_eq!(bytes[3], 0x01);
+        assert_eq!(bytes[4], 0x02);
+
+        // Must fit a single notification at the default MTU
+        assert!(bytes.len() <= config::DEFAULT_MTU - config::ATT_OVERHEAD);
+    }
+
     #[test]
     fn test_effective_payload() {
         // With default MTU (23 bytes)
@@ -22,31 +22,50 @@ use bluer::gatt::local::{
     CharacteristicWriteMethod, CharacteristicWriteRequest, Service,
 };
 use bluer::Adapter;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex, RwLock};
 use tracing::{debug, error, info, warn};
 
 use super::ble_constants::*;
-use super::protocol::{Message, MessageType, PairAckPayload, PairRequestPayload, WordPayload};
+use super::protocol::{
+    FeedbackPayload, LanguagePayload, Message, MessageType, PairAckPayload, PairRequestPayload,
+    WordPayload,
+};
 use super::reassembler::{chunk_message, MessageReassembler};
+use crate::config::AdvertisingMode;
 use crate::crypto::ecdh::EcdhKeypair;
 use crate::crypto::CryptoContext;
+use crate::diagnostics::Diagnostics;
 
 /// Events emitted by the GATT server.
 #[derive(Debug, Clone)]
 pub enum ConnectionEvent {
     /// Text received from the Android app.
-    TextReceived(String),
+    TextReceived {
+        text: String,
+        msg_ts: u64, // Message timestamp, echoed in delivery feedback
+    },
     /// Word received from the Android app (with session info).
     WordReceived {
         word: String,
         seq: Option<u64>, // Optional for backward compatibility
         session: String,
+        ts: Option<u64>, // Phone timestamp in ms, for latency diagnostics
+        msg_ts: u64,     // Message timestamp, echoed in delivery feedback
     },
     /// Command received from the Android app.
-    CommandReceived(String),
+    CommandReceived {
+        command: String,
+        msg_ts: u64, // Message timestamp, echoed in delivery feedback
+    },
     /// Connection established.
-    Connected { device_name: String },
+    Connected {
+        device_id: String,
+        device_name: String,
+    },
+    /// The phone announced its speech recognizer language.
+    LanguageAnnounced { language: String },
     /// Connection closed.
     Disconnected,
     /// Pairing requested.
@@ -81,12 +100,15 @@ struct ServerState {
     state: ConnectionState,
     negotiated_mtu: usize,
     status_code: StatusCode,
+    desktop_mode: DesktopMode,
+    injector_health: InjectorHealth,
     pending_pairing: Option<PendingPairing>,
     last_connected_time: Option<std::time::Instant>,
+    diagnostics: Arc<Diagnostics>,
 }
 
 impl ServerState {
-    fn new() -> Self {
+    fn new(diagnostics: Arc<Diagnostics>) -> Self {
         Self {
             reassembler: MessageReassembler::new(),
             crypto: None,
@@ -94,8 +116,20 @@ impl ServerState {
             state: ConnectionState::AwaitingPair,
             negotiated_mtu: config::DEFAULT_MTU,
             status_code: StatusCode::Idle,
+            desktop_mode: DesktopMode::Dictating,
+            injector_health: InjectorHealth::Ok,
             pending_pairing: None,
             last_connected_time: None,
+            diagnostics,
+        }
+    }
+
+    /// Current Status characteristic payload.
+    fn status_payload(&self) -> StatusPayload {
+        StatusPayload {
+            status: self.status_code,
+            mode: self.desktop_mode,
+            injector: self.injector_health,
         }
     }
 }
@@ -109,13 +143,18 @@ pub struct GattServer {
     state: Arc<RwLock<ServerState>>,
     response_tx: Arc<Mutex<Option<mpsc::Sender<Vec<u8>>>>>,
     status_tx: Arc<Mutex<Option<mpsc::Sender<Vec<u8>>>>>,
-    _adv_handle: Option<AdvertisementHandle>,
+    advertising_mode: AdvertisingMode,
+    manufacturer_tag: Option<Vec<u8>>,
+    adv_handle: Arc<Mutex<Option<AdvertisementHandle>>>,
     _app_handle: Option<ApplicationHandle>,
 }
 
 impl GattServer {
     /// Create a new GATT server.
-    pub async fn new(event_tx: mpsc::Sender<ConnectionEvent>) -> Result<Self> {
+    pub async fn new(
+        event_tx: mpsc::Sender<ConnectionEvent>,
+        diagnostics: Arc<Diagnostics>,
+    ) -> Result<Self> {
         info!("Initializing BLE GATT server...");
 
         // Create BlueZ session
@@ -143,10 +182,12 @@ impl GattServer {
             linux_device_id,
             device_name: String::new(),
             event_tx,
-            state: Arc::new(RwLock::new(ServerState::new())),
+            state: Arc::new(RwLock::new(ServerState::new(diagnostics))),
             response_tx: Arc::new(Mutex::new(None)),
             status_tx: Arc::new(Mutex::new(None)),
-            _adv_handle: None,
+            advertising_mode: AdvertisingMode::default(),
+            manufacturer_tag: None,
+            adv_handle: Arc::new(Mutex::new(None)),
             _app_handle: None,
         })
     }
@@ -159,13 +200,34 @@ impl GattServer {
         Ok(())
     }
 
+    /// Set the advertising policy and optional manufacturer-data tag.
+    ///
+    /// Must be called before `start()`.
+    pub fn set_advertising(&mut self, mode: AdvertisingMode, manufacturer_tag: Option<&str>) {
+        self.advertising_mode = mode;
+        self.manufacturer_tag = manufacturer_tag.map(|tag| {
+            let mut bytes = tag.as_bytes().to_vec();
+            if bytes.len() > config::MAX_MANUFACTURER_TAG_LEN {
+                warn!(
+                    "Manufacturer tag '{}' longer than {} bytes, truncating",
+                    tag,
+                    config::MAX_MANUFACTURER_TAG_LEN
+                );
+                bytes.truncate(config::MAX_MANUFACTURER_TAG_LEN);
+            }
+            bytes
+        });
+        info!("Advertising mode set to: {:?}", mode);
+    }
+
     /// Start the GATT server and advertising.
     pub async fn start(&mut self) -> Result<()> {
         // Register GATT service
         self.register_gatt_service().await?;
 
-        // Start advertising
+        // Start advertising (and keep it in line with the advertising policy)
         self.start_advertising().await?;
+        self.start_advertising_monitor();
 
         // Start device disconnect monitoring
         self.start_disconnect_monitor();
@@ -214,36 +276,8 @@ impl GattServer {
                 debug!("BlueZ poll: checking device addresses...");
 
                 // Check if any device is currently connected
-                match adapter.device_addresses().await {
-                    Ok(addresses) => {
-                        debug!("BlueZ poll: found {} device addresses", addresses.len());
-                        let mut any_connected = false;
-
-                        for addr in &addresses {
-                            debug!("BlueZ poll: checking device {}", addr);
-                            if let Ok(device) = adapter.device(*addr) {
-                                match device.is_connected().await {
-                                    Ok(connected) => {
-                                        debug!(
-                                            "BlueZ poll: device {} connected={}",
-                                            addr, connected
-                                        );
-                                        if connected {
-                                            any_connected = true;
-                                            break;
-                                        }
-                                    }
-                                    Err(e) => {
-                                        debug!("BlueZ poll: failed to query connection state for {}: {}", addr, e);
-                                    }
-                                }
-                            } else {
-                                debug!("BlueZ poll: failed to get device object for {}", addr);
-                            }
-                        }
-
-                        debug!("BlueZ poll: any_connected={}", any_connected);
-
+                match Self::any_device_connected(&adapter).await {
+                    Ok(any_connected) => {
                         // If we think we're connected but no devices are actually connected
                         if !any_connected {
                             let state_guard = state.read().await;
@@ -275,6 +309,98 @@ impl GattServer {
         });
     }
 
+    /// Check via BlueZ whether any device currently has a link to the adapter.
+    async fn any_device_connected(adapter: &Adapter) -> Result<bool> {
+        let addresses = adapter.device_addresses().await?;
+        debug!("BlueZ poll: found {} device addresses", addresses.len());
+        let mut any_connected = false;
+
+        for addr in &addresses {
+            debug!("BlueZ poll: checking device {}", addr);
+            if let Ok(device) = adapter.device(*addr) {
+                match device.is_connected().await {
+                    Ok(connected) => {
+                        debug!("BlueZ poll: device {} connected={}", addr, connected);
+                        if connected {
+                            any_connected = true;
+                            break;
+                        }
+                    }
+                    Err(e) => {
+                        debug!(
+                            "BlueZ poll: failed to query connection state for {}: {}",
+                            addr, e
+                        );
+                    }
+                }
+            } else {
+                debug!("BlueZ poll: failed to get device object for {}", addr);
+            }
+        }
+
+        debug!("BlueZ poll: any_connected={}", any_connected);
+        Ok(any_connected)
+    }
+
+    /// Start monitoring the connection state to apply the advertising policy.
+    ///
+    /// Advertising is stopped by dropping the advertisement handle and
+    /// restarted when the policy allows it again.
+    fn start_advertising_monitor(&self) {
+        if self.advertising_mode == AdvertisingMode::Always {
+            return;
+        }
+
+        let adapter = self.adapter.clone();
+        let state = self.state.clone();
+        let adv_handle = self.adv_handle.clone();
+        let mode = self.advertising_mode;
+        let device_name = self.device_name.clone();
+        let manufacturer_tag = self.manufacturer_tag.clone();
+
+        tokio::spawn(async move {
+            info!("Starting advertising monitor ({:?})...", mode);
+
+            loop {
+                tokio::time::sleep(config::ADVERTISING_CHECK_INTERVAL).await;
+
+                let should_advertise = match mode {
+                    AdvertisingMode::Always => true,
+                    AdvertisingMode::WhileUnpaired => {
+                        let state_guard = state.read().await;
+                        state_guard.status_code != StatusCode::Paired
+                    }
+                    // Only the phone's own session counts: other devices such
+                    // as headsets may be connected to the adapter too
+                    AdvertisingMode::StopWhileConnected => {
+                        let state_guard = state.read().await;
+                        state_guard.state != ConnectionState::Authenticated
+                    }
+                };
+
+                let mut handle = adv_handle.lock().await;
+                if should_advertise && handle.is_none() {
+                    match adapter
+                        .advertise(Self::advertisement(&device_name, &manufacturer_tag))
+                        .await
+                    {
+                        Ok(h) => {
+                            *handle = Some(h);
+                            info!("BLE advertising resumed");
+                        }
+                        Err(e) => {
+                            error!("Failed to resume BLE advertising: {}", e);
+                        }
+                    }
+                } else if !should_advertise && handle.is_some() {
+                    // Dropping the handle unregisters the advertisement
+                    *handle = None;
+                    info!("BLE advertising paused");
+                }
+            }
+        });
+    }
+
     /// Register the GATT service with BlueZ.
     async fn register_gatt_service(&mut self) -> Result<()> {
         let state = self.state.clone();
@@ -296,6 +422,7 @@ impl GattServer {
             let event_tx = event_tx.clone();
             let linux_device_id = linux_device_id.clone();
             let response_tx = response_tx.clone();
+            let status_tx = status_tx.clone();
 
             Characteristic {
                 uuid: COMMAND_RX_UUID,
@@ -308,6 +435,7 @@ impl GattServer {
                             let event_tx = event_tx.clone();
                             let linux_device_id = linux_device_id.clone();
                             let response_tx = response_tx.clone();
+                            let status_tx = status_tx.clone();
 
                             Box::pin(async move {
                                 Self::handle_command_write(
@@ -317,6 +445,7 @@ impl GattServer {
                                     event_tx,
                                     linux_device_id,
                                     response_tx,
+                                    status_tx,
                                 )
                                 .await
                             })
@@ -406,6 +535,7 @@ impl GattServer {
                                     s.state = ConnectionState::AwaitingPair;
                                     s.crypto = None;
                                     s.device_id = None;
+                                    s.status_code = StatusCode::Idle;
                                     s.last_connected_time = None;
                                 }
                                 // Notify main loop
@@ -443,7 +573,7 @@ impl GattServer {
                         let state = state.clone();
                         Box::pin(async move {
                             let state = state.read().await;
-                            Ok(state.status_code.as_bytes())
+                            Ok(state.status_payload().as_bytes())
                         })
                     }),
                     ..Default::default()
@@ -578,6 +708,7 @@ impl GattServer {
         event_tx: mpsc::Sender<ConnectionEvent>,
         _linux_device_id: String,
         response_tx: Arc<Mutex<Option<mpsc::Sender<Vec<u8>>>>>,
+        status_tx: Arc<Mutex<Option<mpsc::Sender<Vec<u8>>>>>,
     ) -> Result<(), bluer::gatt::local::ReqError> {
         debug!(
             "📥 BLE WRITE RECEIVED: {} bytes, MTU={}, offset={}",
@@ -598,10 +729,18 @@ impl GattServer {
                 state_guard.negotiated_mtu, write_mtu
             );
             state_guard.negotiated_mtu = write_mtu;
+            state_guard.diagnostics.set_mtu(write_mtu);
         }
 
         // Process packet through reassembler
-        if let Some(complete_message) = state_guard.reassembler.process_packet(&data) {
+        state_guard.diagnostics.record_packet_in();
+        let complete_message = state_guard.reassembler.process_packet(&data);
+        let reassembly_errors = state_guard.reassembler.take_errors();
+        state_guard
+            .diagnostics
+            .record_reassembly_errors(reassembly_errors);
+
+        if let Some(complete_message) = complete_message {
             debug!(
                 "✅ Message reassembly complete: {} bytes",
                 complete_message.len()
@@ -612,6 +751,7 @@ impl GattServer {
                 Ok(s) => s,
                 Err(e) => {
                     error!("Invalid UTF-8 in message: {}", e);
+                    state_guard.diagnostics.record_decode_error();
                     return Ok(());
                 }
             };
@@ -622,9 +762,11 @@ impl GattServer {
                 Ok(m) => m,
                 Err(e) => {
                     error!("Failed to parse message: {}", e);
+                    state_guard.diagnostics.record_decode_error();
                     return Ok(());
                 }
             };
+            state_guard.diagnostics.record_message_in();
 
             // Verify and decrypt if we have crypto context
             // Note: Only verify messages that should be signed (not PAIR_REQ, PAIR_ACK, HEARTBEAT, ACK before auth)
@@ -632,12 +774,22 @@ impl GattServer {
                 // Only verify messages after authentication for types that require it
                 let should_verify = matches!(
                     message.message_type,
-                    MessageType::Text | MessageType::Word | MessageType::Command
+                    MessageType::Text
+                        | MessageType::Word
+                        | MessageType::Command
+                        | MessageType::Language
                 );
 
                 if should_verify {
                     if let Err(e) = message.verify_and_decrypt(crypto) {
                         error!("Message verification failed: {}", e);
+                        // The payload is left untouched on failure, so re-checking
+                        // the checksum tells a bad checksum from a decryption error
+                        if message.verify(crypto) {
+                            state_guard.diagnostics.record_decode_error();
+                        } else {
+                            state_guard.diagnostics.record_checksum_failure();
+                        }
                         return Ok(());
                     }
                 }
@@ -688,6 +840,8 @@ impl GattServer {
                         desktop_keypair,
                     });
 
+                    Self::notify_status(&state_guard, &status_tx).await;
+
                     // Emit pairing requested event with device name
                     info!("📤 Sending PairRequested event to main loop...");
                     let _ = event_tx
@@ -711,7 +865,10 @@ impl GattServer {
 
                     debug!("Text received: {}", message.payload);
                     let _ = event_tx
-                        .send(ConnectionEvent::TextReceived(message.payload.clone()))
+                        .send(ConnectionEvent::TextReceived {
+                            text: message.payload.clone(),
+                            msg_ts: message.timestamp,
+                        })
                         .await;
 
                     // Send ACK
@@ -736,11 +893,14 @@ impl GattServer {
                                     word: word_payload.word,
                                     seq: word_payload.seq,
                                     session: word_payload.session,
+                                    ts: word_payload.ts,
+                                    msg_ts: message.timestamp,
                                 })
                                 .await;
                         }
                         Err(e) => {
                             error!("Failed to parse WORD payload: {}", e);
+                            state_guard.diagnostics.record_decode_error();
                         }
                     }
 
@@ -756,13 +916,41 @@ impl GattServer {
 
                     debug!("Command received: {}", message.payload);
                     let _ = event_tx
-                        .send(ConnectionEvent::CommandReceived(message.payload.clone()))
+                        .send(ConnectionEvent::CommandReceived {
+                            command: message.payload.clone(),
+                            msg_ts: message.timestamp,
+                        })
                         .await;
 
                     // Send ACK
                     let ack = Message::ack(message.timestamp);
                     Self::send_response_internal(ack, &state_guard, response_tx.clone()).await;
                 }
+                MessageType::Language => {
+                    if state_guard.state != ConnectionState::Authenticated {
+                        warn!("Received LANGUAGE before authentication");
+                        return Ok(());
+                    }
+
+                    match LanguagePayload::from_json(&message.payload) {
+                        Ok(payload) => {
+                            info!("Phone recognizer language: {}", payload.language);
+                            let _ = event_tx
+                                .send(ConnectionEvent::LanguageAnnounced {
+                                    language: payload.language,
+                                })
+                                .await;
+                        }
+                        Err(e) => {
+                            error!("Failed to parse LANGUAGE payload: {}", e);
+                            state_guard.diagnostics.record_decode_error();
+                        }
+                    }
+
+                    // Send ACK
+                    let ack = Message::ack(message.timestamp);
+                    Self::send_response_internal(ack, &state_guard, response_tx.clone()).await;
+                }
                 MessageType::Heartbeat => {
                     // Respond with ACK
                     let ack = Message::ack(message.timestamp);
@@ -804,6 +992,7 @@ impl GattServer {
         let packets = chunk_message(json.as_bytes(), state.negotiated_mtu);
 
         // Send each packet as a notification
+        state.diagnostics.record_packets_out(packets.len());
         let tx_guard = response_tx.lock().await;
         if let Some(ref tx) = *tx_guard {
             for packet in packets {
@@ -855,9 +1044,18 @@ impl GattServer {
             pending.android_device_id
         );
 
+        let device_id = pending.android_device_id;
+        let device_name = pending
+            .android_device_name
+            .unwrap_or_else(|| device_id.clone());
+        state
+            .diagnostics
+            .start_session(&device_name, state.negotiated_mtu);
+
         // Send PAIR_ACK
         let json = response.to_json()?;
         let packets = chunk_message(json.as_bytes(), state.negotiated_mtu);
+        state.diagnostics.record_packets_out(packets.len());
 
         let tx_guard = self.response_tx.lock().await;
         if let Some(ref tx) = *tx_guard {
@@ -867,18 +1065,14 @@ impl GattServer {
         }
 
         // Notify status change
-        let status_tx_guard = self.status_tx.lock().await;
-        if let Some(ref tx) = *status_tx_guard {
-            let _ = tx.send(StatusCode::Paired.as_bytes()).await;
-        }
+        Self::notify_status(&state, &self.status_tx).await;
 
         // Emit connected event
         let _ = self
             .event_tx
             .send(ConnectionEvent::Connected {
-                device_name: pending
-                    .android_device_name
-                    .unwrap_or(pending.android_device_id),
+                device_id,
+                device_name,
             })
             .await;
 
@@ -887,7 +1081,9 @@ impl GattServer {
 
     /// Reject pairing request.
     pub async fn reject_pairing(&self, reason: &str) -> Result<()> {
-        let state = self.state.read().await;
+        let mut state = self.state.write().await;
+        state.status_code = StatusCode::Rejected;
+        state.pending_pairing = None;
 
         let device_id = state.device_id.as_deref().unwrap_or("unknown");
 
@@ -898,6 +1094,7 @@ impl GattServer {
         // Send PAIR_ACK (no signing since pairing failed)
         let json = response.to_json()?;
         let packets = chunk_message(json.as_bytes(), state.negotiated_mtu);
+        state.diagnostics.record_packets_out(packets.len());
 
         let tx_guard = self.response_tx.lock().await;
         if let Some(ref tx) = *tx_guard {
@@ -906,21 +1103,79 @@ impl GattServer {
             }
         }
 
+        // Notify status change
+        Self::notify_status(&state, &self.status_tx).await;
+
         info!("Pairing rejected for device {}: {}", device_id, reason);
         Ok(())
     }
 
-    /// Start BLE advertising.
-    async fn start_advertising(&mut self) -> Result<()> {
-        let adv = Advertisement {
+    /// Report the delivery outcome of a received message to the phone.
+    pub async fn send_feedback(&self, feedback: FeedbackPayload) -> Result<()> {
+        let state = self.state.read().await;
+        if state.state != ConnectionState::Authenticated {
+            debug!("Not authenticated, dropping feedback");
+            return Ok(());
+        }
+
+        let message = Message::new(MessageType::Feedback, feedback.to_json()?);
+        Self::send_response_internal(message, &state, self.response_tx.clone()).await;
+        Ok(())
+    }
+
+    /// Update the desktop mode and injector health reported in the Status
+    /// characteristic. Notifies the phone if anything changed.
+    pub async fn set_desktop_status(&self, mode: DesktopMode, injector: InjectorHealth) {
+        let mut state = self.state.write().await;
+        if state.desktop_mode == mode && state.injector_health == injector {
+            return;
+        }
+
+        debug!(
+            "Desktop status changed: {:?}, injector {:?}",
+            mode, injector
+        );
+        state.desktop_mode = mode;
+        state.injector_health = injector;
+        Self::notify_status(&state, &self.status_tx).await;
+    }
+
+    /// Send the current status payload via the Status characteristic.
+    async fn notify_status(
+        state: &ServerState,
+        status_tx: &Arc<Mutex<Option<mpsc::Sender<Vec<u8>>>>>,
+    ) {
+        // Don't wait for queue space: the phone may not have subscribed to
+        // status notifications, and we may be holding the state lock
+        let tx_guard = status_tx.lock().await;
+        if let Some(ref tx) = *tx_guard {
+            if let Err(e) = tx.try_send(state.status_payload().as_bytes()) {
+                debug!("Status notification not queued: {}", e);
+            }
+        }
+    }
+
+    /// Build the advertisement from the device name and manufacturer tag.
+    fn advertisement(device_name: &str, manufacturer_tag: &Option<Vec<u8>>) -> Advertisement {
+        let manufacturer_data = manufacturer_tag
+            .iter()
+            .map(|tag| (MANUFACTURER_ID, tag.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        Advertisement {
             service_uuids: vec![SERVICE_UUID].into_iter().collect(),
+            manufacturer_data,
             discoverable: Some(true),
-            local_name: Some(self.device_name.clone()),
+            local_name: Some(device_name.to_string()),
             ..Default::default()
-        };
+        }
+    }
 
+    /// Start BLE advertising.
+    async fn start_advertising(&mut self) -> Result<()> {
+        let adv = Self::advertisement(&self.device_name, &self.manufacturer_tag);
         let handle = self.adapter.advertise(adv).await?;
-        self._adv_handle = Some(handle);
+        *self.adv_handle.lock().await = Some(handle);
 
         info!("BLE advertising started");
         Ok(())
@@ -25,4 +25,6 @@ mod reassembler;
 mod protocol;
 
 // Export BLE components (only what's used externally)
+pub use ble_constants::{DesktopMode, InjectorHealth};
 pub use gatt_server::{ConnectionEvent, GattServer};
+pub use protocol::FeedbackPayload;
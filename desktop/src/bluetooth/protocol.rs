@@ -41,6 +41,10 @@ pub enum MessageType {
     PairReq,
     #[serde(rename = "PAIR_ACK")]
     PairAck,
+    #[serde(rename = "FEEDBACK")]
+    Feedback,
+    #[serde(rename = "LANGUAGE")]
+    Language,
 }
 
 impl MessageType {
@@ -54,6 +58,8 @@ impl MessageType {
             Self::Ack => "ACK",
             Self::PairReq => "PAIR_REQ",
             Self::PairAck => "PAIR_ACK",
+            Self::Feedback => "FEEDBACK",
+            Self::Language => "LANGUAGE",
         }
     }
 }
@@ -127,6 +133,8 @@ impl Message {
                 | MessageType::Command
                 | MessageType::PairReq
                 | MessageType::PairAck
+                | MessageType::Feedback
+                | MessageType::Language
         ) {
             self.payload = ctx.encrypt(&self.payload)?;
         }
@@ -174,6 +182,8 @@ impl Message {
                 | MessageType::Command
                 | MessageType::PairReq
                 | MessageType::PairAck
+                | MessageType::Feedback
+                | MessageType::Language
         ) {
             self.payload = ctx.decrypt(&self.payload)?;
         }
@@ -216,6 +226,19 @@ impl WordPayload {
     }
 }
 
+/// Payload for LANGUAGE messages - the phone's recognizer language.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguagePayload {
+    /// BCP 47 locale tag, e.g. "cs-CZ".
+    pub language: String,
+}
+
+impl LanguagePayload {
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+}
+
 /// Pairing request payload.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PairRequestPayload {
@@ -277,6 +300,76 @@ impl PairAckPayload {
     }
 }
 
+/// Delivery outcome reported in FEEDBACK messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Text was typed into the focused window.
+    Typed,
+    /// A voice command was executed.
+    Command,
+    /// Injection failed.
+    Failed,
+}
+
+/// Injection feedback payload, sent desktop to phone.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedbackPayload {
+    /// Timestamp of the phone message that produced this delivery
+    pub ref_ts: u64,
+    pub status: DeliveryStatus,
+    /// Resolved command code (for `command`, and failed commands)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// Failure reason (for `failed`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Exit status of an external program run by the command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+}
+
+impl FeedbackPayload {
+    pub fn typed(ref_ts: u64) -> Self {
+        Self {
+            ref_ts,
+            status: DeliveryStatus::Typed,
+            command: None,
+            error: None,
+            exit_code: None,
+        }
+    }
+
+    pub fn command(ref_ts: u64, command: impl Into<String>) -> Self {
+        Self {
+            ref_ts,
+            status: DeliveryStatus::Command,
+            command: Some(command.into()),
+            error: None,
+            exit_code: None,
+        }
+    }
+
+    pub fn failed(ref_ts: u64, command: Option<String>, error: impl Into<String>) -> Self {
+        Self {
+            ref_ts,
+            status: DeliveryStatus::Failed,
+            command,
+            error: Some(error.into()),
+            exit_code: None,
+        }
+    }
+
+    pub fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -340,4 +433,30 @@ mod tests {
         assert!(json.contains("linux-public-key"));
         assert!(json.contains("ok"));
     }
+
+    #[test]
+    fn test_feedback_payload() {
+        let json = FeedbackPayload::command(1234, "ENTER").to_json().unwrap();
+        assert_eq!(
+            json,
+            r#"{"ref_ts":1234,"status":"command","command":"ENTER"}"#
+        );
+
+        let json = FeedbackPayload::failed(1234, None, "ydotoold not running")
+            .to_json()
+            .unwrap();
+        assert_eq!(
+            json,
+            r#"{"ref_ts":1234,"status":"failed","error":"ydotoold not running"}"#
+        );
+
+        let json = FeedbackPayload::failed(1234, Some("RUN_TESTS".to_string()), "exited with 2")
+            .with_exit_code(Some(2))
+            .to_json()
+            .unwrap();
+        assert_eq!(
+            json,
+            r#"{"ref_ts":1234,"status":"failed","command":"RUN_TESTS","error":"exited with 2","exit_code":2}"#
+        );
+    }
 }
@@ -24,6 +24,8 @@ pub struct MessageReassembler {
     expected_length: usize,
     expected_seq: u8,
     in_progress: bool,
+    /// Packets dropped since the last `take_errors` call.
+    errors: u64,
 }
 
 impl MessageReassembler {
@@ -34,6 +36,7 @@ impl MessageReassembler {
             expected_length: 0,
             expected_seq: 0,
             in_progress: false,
+            errors: 0,
         }
     }
 
@@ -44,6 +47,7 @@ impl MessageReassembler {
     pub fn process_packet(&mut self, packet: &[u8]) -> Option<Vec<u8>> {
         if packet.len() < 2 {
             warn!("Packet too short: {} bytes", packet.len());
+            self.errors += 1;
             return None;
         }
 
@@ -56,6 +60,7 @@ impl MessageReassembler {
             // Start of new message
             if packet.len() < 4 {
                 warn!("First packet too short: {} bytes", packet.len());
+                self.errors += 1;
                 return None;
             }
 
@@ -78,6 +83,7 @@ impl MessageReassembler {
                     "Sequence error: expected {}, got {}",
                     self.expected_seq, seq
                 );
+                self.errors += 1;
                 self.reset();
                 return None;
             }
@@ -87,6 +93,7 @@ impl MessageReassembler {
         } else {
             // Received continuation without start
             warn!("Received continuation packet without start");
+            self.errors += 1;
             return None;
         }
 
@@ -104,6 +111,7 @@ impl MessageReassembler {
                     self.expected_length,
                     self.buffer.len()
                 );
+                self.errors += 1;
                 self.reset();
             }
         }
@@ -119,6 +127,11 @@ impl MessageReassembler {
         self.in_progress = false;
     }
 
+    /// Return the number of dropped packets since the last call, and reset it.
+    pub fn take_errors(&mut self) -> u64 {
+        std::mem::take(&mut self.errors)
+    }
+
     /// Check if reassembly is in progress.
     #[allow(dead_code)]
     pub fn is_in_progress(&self) -> bool {
@@ -247,6 +260,8 @@ mod tests {
         let result = reassembler.process_packet(&packet2);
         assert!(result.is_none());
         assert!(!reassembler.is_in_progress());
+        assert_eq!(reassembler.take_errors(), 1);
+        assert_eq!(reassembler.take_errors(), 0);
     }
 
     #[test]
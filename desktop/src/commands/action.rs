@@ -0,0 +1,341 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command actions.
+//!
+//! Commands are data: a command code bound to an action that is run
+//! through the input injector.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use super::{Attach, CapsMode};
+use crate::input::{Chord, Key, Modifier};
+
+/// What a command does when executed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CommandAction {
+    /// Press a key chord, e.g. `"ctrl+shift+t"`, `repeat` times.
+    Key {
+        keys: Chord,
+        #[serde(default = "one", skip_serializing_if = "is_one")]
+        repeat: u32,
+    },
+    /// Type literal text.
+    Text { text: String },
+    /// Type a punctuation mark, spaced against the surrounding words.
+    Punctuation {
+        text: String,
+        #[serde(default)]
+        attach: Attach,
+    },
+    /// Change capitalization of the following dictated words.
+    Caps { mode: CapsMode },
+    /// Type a stored block of text, expanding placeholders such as
+    /// `{date}`, `{time}` and `{clipboard}`.
+    Snippet { text: String },
+    /// Run steps in order, in the background. Stops at the first failing
+    /// step and can be cancelled between steps.
+    #[serde(alias = "sequence")]
+    Macro { steps: Vec<CommandAction> },
+    /// Wait before the next step.
+    Delay { ms: u64 },
+    /// Run another command by its code.
+    Command { command: String },
+    /// Run an external program with a fixed argument list, in the
+    /// background. No shell is involved. The program must be allowed for
+    /// the command in the config.
+    Run {
+        program: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        /// Ask on the desktop before running.
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        confirm: bool,
+        /// Kill the program after this long (default 30 s).
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_ms: Option<u64>,
+    },
+    /// Cancel the running macro or program.
+    Cancel,
+    /// Run the command waiting for a spoken confirmation.
+    Confirm,
+    /// Delete the last dictated utterance ("scratch that").
+    Undo,
+    /// Turn dictation-only mode on or off. While it is on, only phrases of
+    /// commands turning it off are recognized; all other words are typed.
+    DictationOnly { enabled: bool },
+    /// Turn spelling mode on or off. While it is on, words are typed as
+    /// single characters ("alpha" types "a"), and only phrases of commands
+    /// turning it off are recognized.
+    Spelling { enabled: bool },
+    /// Do nothing.
+    None,
+}
+
+/// Maximum nesting of `command` steps (guards against cycles).
+const MAX_COMMAND_DEPTH: usize = 8;
+
+fn one() -> u32 {
+    1
+}
+
+fn is_one(n: &u32) -> bool {
+    *n == 1
+}
+
+impl CommandAction {
+    /// Type a punctuation mark.
+    pub fn punctuation(text: &str, attach: Attach) -> Self {
+        Self::Punctuation {
+            text: text.to_string(),
+            attach,
+        }
+    }
+
+    /// Press a single key.
+    pub fn key(key: Key) -> Self {
+        Self::chord(&[], key)
+    }
+
+    /// Press a key with modifiers held.
+    pub fn chord(modifiers: &[Modifier], key: Key) -> Self {
+        Self::Key {
+            keys: Chord::new(modifiers, key),
+            repeat: 1,
+        }
+    }
+
+    /// Repeat a key action `count` times ("delete three words").
+    /// Returns `None` for other actions, which don't take a count.
+    pub fn repeated(&self, count: u32) -> Option<Self> {
+        match self {
+            Self::Key { keys, repeat } => Some(Self::Key {
+                keys: keys.clone(),
+                repeat: repeat.saturating_mul(count),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Whether the action runs in the background (macros, programs and
+    /// snippets, which may read the clipboard).
+    pub fn runs_in_background(&self) -> bool {
+        matches!(
+            self,
+            Self::Macro { .. } | Self::Run { .. } | Self::Snippet { .. }
+        )
+    }
+
+    /// Programs run by the action, including macro steps.
+    pub fn programs(&self) -> Vec<&str> {
+        match self {
+            Self::Run { program, .. } => vec![program.as_str()],
+            Self::Macro { steps } => steps.iter().flat_map(|step| step.programs()).collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether any program run by the action asks for confirmation.
+    pub fn needs_confirmation(&self) -> bool {
+        match self {
+            Self::Run { confirm, .. } => *confirm,
+            Self::Macro { steps } => steps.iter().any(|step| step.needs_confirmation()),
+            _ => false,
+        }
+    }
+
+    /// Replace `command` steps with the actions they refer to, so the result
+    /// can run without access to the command store.
+    pub fn expand(&self, resolve: &dyn Fn(&str) -> Option<CommandAction>) -> Result<Self> {
+        self.expand_depth(resolve, 0)
+    }
+
+    fn expand_depth(
+        &self,
+        resolve: &dyn Fn(&str) -> Option<CommandAction>,
+        depth: usize,
+    ) -> Result<Self> {
+        match self {
+            Self::Command { command } => {
+                if depth >= MAX_COMMAND_DEPTH {
+                    bail!("Command '{}' nested too deeply (cycle?)", command);
+                }
+                match resolve(command) {
+                    Some(action) => action.expand_depth(resolve, depth + 1),
+                    None => bail!("Unknown command '{}' in macro", command),
+                }
+            }
+            Self::Macro { steps } => Ok(Self::Macro {
+                steps: steps
+                    .iter()
+                    .map(|step| step.expand_depth(resolve, depth))
+                    .collect::<Result<_>>()?,
+            }),
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+impl fmt::Display for CommandAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Key { keys, repeat: 1 } => write!(f, "{}", keys),
+            Self::Key { keys, repeat } => write!(f, "{} ×{}", keys, repeat),
+            Self::Text { text } => write!(f, "type \"{}\"", text),
+            Self::Punctuation { text, .. } => write!(f, "punctuation {:?}", text),
+            Self::Caps { mode } => match mode {
+                CapsMode::Normal => write!(f, "caps off"),
+                CapsMode::Title => write!(f, "capitalize words"),
+                CapsMode::Upper => write!(f, "uppercase words"),
+                CapsMode::LowerNext => write!(f, "lowercase next word"),
+            },
+            Self::Snippet { text } => match text.lines().next() {
+                Some(first) if first.len() < text.trim_end().len() => {
+                    write!(f, "snippet \"{}…\"", first)
+                }
+                _ => write!(f, "snippet \"{}\"", text.trim_end()),
+            },
+            Self::Macro { steps } => {
+                for (i, step) in steps.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", step)?;
+                }
+                Ok(())
+            }
+            Self::Delay { ms } => write!(f, "wait {} ms", ms),
+            Self::Command { command } => write!(f, "run {}", command),
+            Self::Run { program, args, .. } => {
+                write!(f, "exec {}", program)?;
+                for arg in args {
+                    write!(f, " {}", arg)?;
+                }
+                Ok(())
+            }
+            Self::Cancel => write!(f, "cancel macro"),
+            Self::Confirm => write!(f, "confirm command"),
+            Self::Undo => write!(f, "undo last utterance"),
+            Self::DictationOnly { enabled: true } => write!(f, "dictation only"),
+            Self::DictationOnly { enabled: false } => write!(f, "leave dictation only"),
+            Self::Spelling { enabled: true } => write!(f, "start spelling"),
+            Self::Spelling { enabled: false } => write!(f, "stop spelling"),
+            Self::None => write!(f, "no action"),
+        }
+    }
+}
+
+/// A command resolved from a phrase or command code, ready to execute.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedCommand {
+    /// The command code (ENTER, COPY, etc.).
+    pub code: String,
+    /// The action to run.
+    pub action: CommandAction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_action_serialization() {
+        let action = CommandAction::chord(&[Modifier::Ctrl], Key::Letter('c'));
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(json, r#"{"type":"key","keys":"ctrl+c"}"#);
+
+        let parsed: CommandAction =
+            serde_json::from_str(r#"{"type":"sequence","steps":[{"type":"key","keys":"enter"},{"type":"text","text":"hi"}]}"#)
+                .unwrap();
+        assert_eq!(
+            parsed,
+            CommandAction::Macro {
+                steps: vec![
+                    CommandAction::key(Key::Enter),
+                    CommandAction::Text {
+                        text: "hi".to_string()
+                    },
+                ]
+            }
+        );
+        assert_eq!(parsed.to_string(), "enter, type \"hi\"");
+
+        let snippet = CommandAction::Snippet {
+            text: "Best regards,\nDaniel\n".to_string(),
+        };
+        assert_eq!(snippet.to_string(), "snippet \"Best regards,…\"");
+        assert!(snippet.runs_in_background());
+    }
+
+    #[test]
+    fn test_expand_command_steps() {
+        let resolve = |code: &str| match code {
+            "COPY" => Some(CommandAction::chord(&[Modifier::Ctrl], Key::Letter('c'))),
+            "LOOP" => Some(CommandAction::Command {
+                command: "LOOP".to_string(),
+            }),
+            _ => None,
+        };
+        let command = |code: &str| CommandAction::Command {
+            command: code.to_string(),
+        };
+
+        let action = CommandAction::Macro {
+            steps: vec![command("COPY"), CommandAction::Delay { ms: 100 }],
+        };
+        assert_eq!(
+            action.expand(&resolve).unwrap(),
+            CommandAction::Macro {
+                steps: vec![
+                    CommandAction::chord(&[Modifier::Ctrl], Key::Letter('c')),
+                    CommandAction::Delay { ms: 100 },
+                ]
+            }
+        );
+        assert_eq!(action.to_string(), "run COPY, wait 100 ms");
+
+        assert!(command("MISSING").expand(&resolve).is_err());
+        assert!(command("LOOP").expand(&resolve).is_err());
+    }
+
+    #[test]
+    fn test_run_action() {
+        let action: CommandAction = serde_json::from_str(
+            r#"{"type":"run","program":"loginctl","args":["lock-session"],"confirm":true}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            action,
+            CommandAction::Run {
+                program: "loginctl".to_string(),
+                args: vec!["lock-session".to_string()],
+                confirm: true,
+                timeout_ms: None,
+            }
+        );
+        assert_eq!(action.to_string(), "exec loginctl lock-session");
+        assert!(action.runs_in_background());
+        assert!(action.needs_confirmation());
+
+        let action = CommandAction::Macro {
+            steps: vec![CommandAction::key(Key::Enter), action],
+        };
+        assert_eq!(action.programs(), vec!["loginctl"]);
+        assert!(action.needs_confirmation());
+        assert!(CommandAction::key(Key::Enter).programs().is_empty());
+    }
+}
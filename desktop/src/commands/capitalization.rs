@@ -0,0 +1,231 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Automatic capitalization.
+//!
+//! Tracks whether the cursor is at the start of a sentence or line from the
+//! text, punctuation and keys sent, and capitalizes dictated words to match.
+//! Caps commands ("caps on", "all caps", "no caps") override it.
+
+use serde::{Deserialize, Serialize};
+
+/// Languages writing ordinals as a number with a period ("5. března").
+const ORDINAL_PERIOD_LANGUAGES: &[&str] = &["cs"];
+
+/// Capitalization set by a caps command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapsMode {
+    /// Automatic capitalization only.
+    Normal,
+    /// Capitalize Every Word until caps off.
+    Title,
+    /// UPPERCASE every word until caps off.
+    Upper,
+    /// Type the next word in lowercase, even at a sentence start.
+    LowerNext,
+}
+
+/// Sentence position and caps mode of the typed text.
+#[derive(Debug, Clone)]
+pub struct Capitalization {
+    /// Whether to capitalize at sentence and line starts.
+    auto: bool,
+    mode: CapsMode,
+    /// Whether the next word starts a sentence or line. Unknown, so false,
+    /// until a sentence end or line start is typed: the cursor may be
+    /// anywhere in the text when dictation starts.
+    at_sentence_start: bool,
+    /// Whether a number with a period is an ordinal, not a sentence end.
+    ordinal_periods: bool,
+}
+
+impl Default for Capitalization {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Capitalization {
+    /// Create state for an unknown cursor position, with automatic
+    /// capitalization off.
+    pub fn new() -> Self {
+        Self {
+            auto: false,
+            mode: CapsMode::Normal,
+            at_sentence_start: false,
+            ordinal_periods: false,
+        }
+    }
+
+    /// Set the language of the dictated text ("en", "cs"), which decides
+    /// whether "5." ends a sentence.
+    pub fn set_language(&mut self, language: &str) {
+        self.ordinal_periods = ORDINAL_PERIOD_LANGUAGES.contains(&language);
+    }
+
+    /// Enable or disable automatic capitalization.
+    pub fn set_auto(&mut self, auto: bool) {
+        self.auto = auto;
+    }
+
+    /// Apply a caps command.
+    pub fn set_mode(&mut self, mode: CapsMode) {
+        self.mode = mode;
+    }
+
+    /// Forget the sentence position and caps mode (e.g., on a new connection).
+    pub fn reset(&mut self) {
+        self.mode = CapsMode::Normal;
+        self.at_sentence_start = false;
+    }
+
+    /// Note that the cursor moved to a new line (e.g., Enter was pressed).
+    pub fn line_start(&mut self) {
+        self.at_sentence_start = true;
+    }
+
+    /// Capitalize dictated text and track where it leaves the cursor.
+    pub fn apply(&mut self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut word = String::new();
+
+        for c in text.chars() {
+            if c.is_whitespace() {
+                if !word.is_empty() {
+                    out.push_str(&self.word(&word));
+                    word.clear();
+                }
+                if c == '\n' {
+                    self.at_sentence_start = true;
+                }
+                out.push(c);
+            } else {
+                word.push(c);
+            }
+        }
+        if !word.is_empty() {
+            out.push_str(&self.word(&word));
+        }
+        out
+    }
+
+    /// Track a typed punctuation mark (it is not capitalized itself).
+    pub fn mark(&mut self, mark: &str) {
+        if mark.contains('\n') || self.ends_sentence(mark) {
+            self.at_sentence_start = true;
+        }
+    }
+
+    /// Track text typed as is, without capitalizing it.
+    pub fn track(&mut self, text: &str) {
+        self.at_sentence_start = self.ends_sentence(text);
+    }
+
+    /// Capitalize one word and update the sentence position.
+    fn word(&mut self, word: &str) -> String {
+        let out = match self.mode {
+            CapsMode::Upper => word.to_uppercase(),
+            CapsMode::Title => capitalize_first(word),
+            CapsMode::LowerNext => {
+                self.mode = CapsMode::Normal;
+                word.to_lowercase()
+            }
+            CapsMode::Normal if self.auto && self.at_sentence_start => capitalize_first(word),
+            CapsMode::Normal => word.to_string(),
+        };
+        self.at_sentence_start = self.ends_sentence(word);
+        out
+    }
+
+    /// Whether text ends a sentence, ignoring closing quotes and brackets.
+    /// In languages writing ordinals with a period ("5. března"), a number
+    /// with a period doesn't.
+    fn ends_sentence(&self, text: &str) -> bool {
+        let text = text.trim_end_matches(['"', '\'', ')', ']', '”', '’']);
+        let ordinal = self.ordinal_periods
+            && text
+                .strip_suffix('.')
+                .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+        text.ends_with(['.', '?', '!']) && !ordinal
+    }
+}
+
+/// Uppercase the first letter of a word (skipping leading quotes etc.).
+fn capitalize_first(word: &str) -> String {
+    match word.char_indices().find(|(_, c)| c.is_alphabetic()) {
+        Some((i, c)) => {
+            let mut out = String::with_capacity(word.len());
+            out.push_str(&word[..i]);
+            out.extend(c.to_uppercase());
+            out.push_str(&word[i + c.len_utf8()..]);
+            out
+        }
+        None => word.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sentence_start_capitalization() {
+        let mut caps = Capitalization::new();
+        caps.set_auto(true);
+
+        // The cursor may be mid-sentence until a sentence end is seen
+        assert_eq!(caps.apply("hello "), "hello ");
+        caps.mark(".");
+        assert_eq!(caps.apply("hello "), "Hello ");
+        assert_eq!(caps.apply("world "), "world ");
+        caps.mark(".");
+        assert_eq!(caps.apply("šťastný "), "Šťastný ");
+        assert_eq!(
+            caps.apply("is it? yes. \"quoted\" "),
+            "is it? Yes. \"Quoted\" "
+        );
+        caps.line_start();
+        assert_eq!(caps.apply("next line\nand more"), "Next line\nAnd more");
+        assert_eq!(
+            caps.apply(" I was born in 1984. then "),
+            " I was born in 1984. Then "
+        );
+
+        // Czech writes ordinals with a period
+        caps.set_language("cs");
+        assert_eq!(caps.apply("do 5. května "), "do 5. května ");
+
+        caps.set_auto(false);
+        caps.mark("!");
+        assert_eq!(caps.apply("off "), "off ");
+    }
+
+    #[test]
+    fn test_caps_modes() {
+        let mut caps = Capitalization::new();
+
+        caps.set_mode(CapsMode::Title);
+        assert_eq!(caps.apply("the great gatsby "), "The Great Gatsby ");
+        caps.set_mode(CapsMode::Upper);
+        assert_eq!(caps.apply("nasa "), "NASA ");
+        caps.set_mode(CapsMode::Normal);
+        assert_eq!(caps.apply("normal "), "normal ");
+
+        // "no caps" applies to the next word only
+        caps.mark(".");
+        caps.set_mode(CapsMode::LowerNext);
+        assert_eq!(caps.apply("iPhone sales "), "iphone sales ");
+    }
+}
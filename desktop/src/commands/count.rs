@@ -0,0 +1,123 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spoken repeat counts for key commands.
+//!
+//! A count goes inside a 2-word phrase ("delete three words") or after a
+//! phrase followed by "times" ("backspace five times").
+
+/// Word that ends a trailing count ("go left three times"), unless the
+/// locale pack sets another.
+pub const TIMES_WORD: &str = "times";
+
+/// Largest count accepted, so a misheard number can't run away.
+pub const MAX_REPEAT: u32 = 50;
+
+/// Number words from one upward, unless the locale pack sets others.
+const NUMBER_WORDS: &[&str] = &[
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+    "twenty",
+];
+
+/// Words of spoken counts in one language, in the form the phrase trie
+/// looks words up in.
+#[derive(Debug, Clone)]
+pub struct CountWords {
+    /// Word ending a trailing count.
+    pub times: String,
+    /// Forms of each number word, from one upward.
+    pub numbers: Vec<Vec<String>>,
+}
+
+impl Default for CountWords {
+    fn default() -> Self {
+        Self {
+            times: TIMES_WORD.to_string(),
+            numbers: NUMBER_WORDS.iter().map(|w| vec![w.to_string()]).collect(),
+        }
+    }
+}
+
+impl CountWords {
+    /// Parse a spoken count in lookup form: digits or a number word.
+    pub fn parse(&self, word: &str) -> Option<u32> {
+        let count = match word.parse::<u32>() {
+            Ok(n) => n,
+            Err(_) => {
+                let index = self
+                    .numbers
+                    .iter()
+                    .position(|forms| forms.iter().any(|f| f == word))?;
+                index as u32 + 1
+            }
+        };
+        (1..=MAX_REPEAT).contains(&count).then_some(count)
+    }
+}
+
+/// The singular of a counted word ("words" → "word"), if it is plural.
+pub fn singular(word: &str) -> Option<&str> {
+    word.strip_suffix("es")
+        .filter(|w| w.ends_with(['s', 'x', 'h']))
+        .or_else(|| word.strip_suffix('s'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_count() {
+        let words = CountWords::default();
+        assert_eq!(words.parse("three"), Some(3));
+        assert_eq!(words.parse("twenty"), Some(20));
+        assert_eq!(words.parse("12"), Some(12));
+        assert_eq!(words.parse("0"), None);
+        assert_eq!(words.parse("500"), None);
+        assert_eq!(words.parse("word"), None);
+
+        let czech = CountWords {
+            times: "krát".to_string(),
+            numbers: vec![
+                vec!["jeden".into(), "jedna".into()],
+                vec!["dva".into(), "dvě".into()],
+            ],
+        };
+        assert_eq!(czech.parse("dvě"), Some(2));
+        assert_eq!(czech.parse("two"), None);
+
+        assert_eq!(singular("words"), Some("word"));
+        assert_eq!(singular("lines"), Some("line"));
+        assert_eq!(singular("boxes"), Some("box"));
+        assert_eq!(singular("word"), None);
+    }
+}
@@ -0,0 +1,224 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fuzzy phrase matching.
+//!
+//! Recognizers often mishear a command slightly ("cancell" for "cancel").
+//! Spoken words are scored against a phrase by edit distance, both on
+//! their spelling and on a rough phonetic key, and match when both
+//! reach the command's threshold. Short phrases and short spoken words
+//! only match exactly unless their command has its own threshold, so
+//! short common words don't misfire.
+
+use std::collections::HashMap;
+
+/// Minimum score for a fuzzy match, unless configured otherwise.
+pub const DEFAULT_FUZZY_THRESHOLD: f32 = 0.85;
+
+/// Phrases and spoken words with fewer letters only match exactly,
+/// unless their command has its own threshold.
+const MIN_FUZZY_LETTERS: usize = 4;
+
+/// How far below its threshold a score is still logged as a near miss.
+pub const NEAR_MISS_MARGIN: f32 = 0.15;
+
+/// Thresholds for fuzzy matching.
+#[derive(Debug, Clone, Default)]
+pub struct FuzzySettings {
+    /// Minimum score for commands without their own threshold.
+    pub threshold: f32,
+    /// Minimum scores by command code; 1.0 means exact matches only.
+    pub thresholds: HashMap<String, f32>,
+}
+
+impl FuzzySettings {
+    /// The minimum score for spoken words to match a phrase of a
+    /// command, or `None` if they only match exactly.
+    pub fn threshold(&self, command: &str, spoken: &[String], phrase: &[String]) -> Option<f32> {
+        if let Some(&threshold) = self.thresholds.get(command) {
+            return (threshold < 1.0).then_some(threshold);
+        }
+        let letters = |words: &[String]| -> usize {
+            words
+                .iter()
+                .map(|w| w.chars().filter(|c| c.is_alphabetic()).count())
+                .sum()
+        };
+        (letters(spoken).min(letters(phrase)) >= MIN_FUZZY_LETTERS).then_some(self.threshold)
+    }
+}
+
+/// How closely spoken words match a phrase, from 0.0 to 1.0 (identical):
+/// the lower of their spelling and sound similarity, since words that
+/// only sound alike ("post", "paste") or are only spelled alike are
+/// usually different words. Both are expected in their lookup form (see
+/// `match_key`).
+pub fn score(spoken: &[String], phrase: &[String]) -> f32 {
+    let spelling = similarity(&spoken.join(" "), &phrase.join(" "));
+    let sound = similarity(&phonetic_key(spoken), &phonetic_key(phrase));
+    spelling.min(sound)
+}
+
+/// One minus the edit distance relative to the longer string.
+fn similarity(a: &str, b: &str) -> f32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(&a, &b) as f32 / longest as f32
+}
+
+/// Levenshtein distance.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+/// A rough phonetic key of words: their consonant sounds, with a leading
+/// vowel kept as "A" ("copy" and "copie" both give "KP").
+fn phonetic_key(words: &[String]) -> String {
+    words
+        .iter()
+        .map(|word| word_key(word))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn word_key(word: &str) -> String {
+    let chars: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    let mut key = String::new();
+    let mut push = |sound: char| {
+        if !key.ends_with(sound) {
+            key.push(sound);
+        }
+    };
+
+    let mut i = 0;
+    while i < chars.len() {
+        let next = chars.get(i + 1).copied();
+        match (chars[i], next) {
+            ('p', Some('h')) => {
+                push('F');
+                i += 1;
+            }
+            ('s' | 'c', Some('h')) => {
+                push('X');
+                i += 1;
+            }
+            ('t', Some('h')) => {
+                push('0');
+                i += 1;
+            }
+            ('g', Some('h')) => i += 1,
+            ('c', Some('e' | 'i' | 'y')) | ('z', _) => push('S'),
+            ('c' | 'k' | 'q', _) => push('K'),
+            ('x', _) => {
+                push('K');
+                push('S');
+            }
+            ('v', _) => push('F'),
+            ('a' | 'e' | 'i' | 'o' | 'u' | 'y' | 'w' | 'h', _) => {
+                if i == 0 {
+                    push('A');
+                }
+            }
+            (c, _) => c.to_uppercase().for_each(&mut push),
+        }
+        i += 1;
+    }
+    key
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(text: &str) -> Vec<String> {
+        text.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_fuzzy_scores() {
+        assert_eq!(score(&words("copy"), &words("copy")), 1.0);
+        assert_eq!(word_key("copy"), word_key("copie"));
+        assert_eq!(word_key("enter"), "ANTR");
+        assert!(score(&words("cancell"), &words("cancel")) >= DEFAULT_FUZZY_THRESHOLD);
+        assert!(score(&words("select al"), &words("select all")) >= DEFAULT_FUZZY_THRESHOLD);
+        assert!(score(&words("backspase"), &words("backspace")) >= DEFAULT_FUZZY_THRESHOLD);
+        assert!(score(&words("copied"), &words("copy")) < DEFAULT_FUZZY_THRESHOLD);
+        assert!(score(&words("hello"), &words("enter")) < 0.5);
+
+        // Common words resembling a phrase
+        for (spoken, phrase) in [
+            ("come", "comma"),
+            ("post", "paste"),
+            ("pasta", "paste"),
+            ("council", "cancel"),
+            ("colin", "colon"),
+            ("enters", "enter"),
+            ("center", "enter"),
+        ] {
+            assert!(
+                score(&words(spoken), &words(phrase)) < DEFAULT_FUZZY_THRESHOLD,
+                "{} matched {}",
+                spoken,
+                phrase
+            );
+        }
+
+        let mut settings = FuzzySettings {
+            threshold: DEFAULT_FUZZY_THRESHOLD,
+            thresholds: HashMap::new(),
+        };
+        assert_eq!(
+            settings.threshold("CUT", &words("cat"), &words("cut")),
+            None
+        );
+        assert_eq!(
+            settings.threshold("COPY", &words("kopy"), &words("copy")),
+            Some(DEFAULT_FUZZY_THRESHOLD)
+        );
+        // Short spoken words too
+        assert_eq!(
+            settings.threshold("PASTE", &words("pas"), &words("paste")),
+            None
+        );
+        settings.thresholds.insert("CUT".to_string(), 0.6);
+        settings.thresholds.insert("COPY".to_string(), 1.0);
+        assert_eq!(
+            settings.threshold("CUT", &words("cat"), &words("cut")),
+            Some(0.6)
+        );
+        assert_eq!(
+            settings.threshold("COPY", &words("kopy"), &words("copy")),
+            None
+        );
+    }
+}
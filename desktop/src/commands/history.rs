@@ -0,0 +1,126 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Undo history for "scratch that".
+//!
+//! Records how many characters each utterance typed, so the last ones can
+//! be removed with backspaces.
+
+use std::collections::VecDeque;
+
+/// Maximum number of utterances that can be undone.
+const MAX_HISTORY: usize = 20;
+
+/// Identifies an utterance: one TEXT message, or the words streamed
+/// between two pauses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UtteranceKey {
+    /// A TEXT message, by its timestamp.
+    Text(u64),
+    /// Words streamed in a session (`WordPayload.session`) without a pause,
+    /// numbered by the batch they came in.
+    Words(String, u64),
+}
+
+/// An utterance that can be undone.
+#[derive(Debug)]
+struct Utterance<S> {
+    key: UtteranceKey,
+    /// Characters typed for the utterance.
+    chars: usize,
+    /// Typing state before the utterance, restored on undo.
+    before: S,
+}
+
+/// Recent utterances, newest last. `S` is the typing state to restore.
+#[derive(Debug)]
+pub struct UndoHistory<S> {
+    entries: VecDeque<Utterance<S>>,
+}
+
+impl<S> Default for UndoHistory<S> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S> UndoHistory<S> {
+    /// Create an empty history.
+    pub fn new() -> Self {
+        Self {
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Record typed characters. They are added to the newest utterance if
+    /// it has the same key; otherwise a new utterance starts, remembering
+    /// the typing state from `before`.
+    pub fn record(&mut self, key: &UtteranceKey, chars: usize, before: S) {
+        if let Some(last) = self.entries.back_mut() {
+            if &last.key == key {
+                last.chars += chars;
+                return;
+            }
+        }
+        if self.entries.len() == MAX_HISTORY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Utterance {
+            key: key.clone(),
+            chars,
+            before,
+        });
+    }
+
+    /// Remove the newest utterance, returning its character count and the
+    /// typing state from before it.
+    pub fn pop(&mut self) -> Option<(usize, S)> {
+        self.entries.pop_back().map(|u| (u.chars, u.before))
+    }
+
+    /// Forget everything (the cursor moved, so the counts no longer apply).
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_undo_history() {
+        let mut history = UndoHistory::new();
+        let words = UtteranceKey::Words("s1".to_string(), 1);
+
+        history.record(&UtteranceKey::Text(1), 12, "start");
+        history.record(&words, 6, "after text");
+        history.record(&words, 4, "ignored");
+
+        assert_eq!(history.pop(), Some((10, "after text")));
+        assert_eq!(history.pop(), Some((12, "start")));
+        assert_eq!(history.pop(), None);
+
+        // Words after a pause are another utterance
+        history.record(&words, 6, "first batch");
+        history.record(&UtteranceKey::Words("s1".to_string(), 2), 3, "second batch");
+        assert_eq!(history.pop(), Some((3, "second batch")));
+
+        for i in 0..30 {
+            history.record(&UtteranceKey::Text(i), 1, "");
+        }
+        history.clear();
+        assert_eq!(history.pop(), None);
+    }
+}
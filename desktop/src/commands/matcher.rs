@@ -21,18 +21,14 @@
 use std::sync::Arc;
 use tracing::debug;
 
-use super::VoiceCommand;
+use super::{CommandAction, PhraseTrie, ResolvedCommand};
 use crate::storage::VoiceCommandStore;
 
-/// Default 2-word command phrases
-pub const DEFAULT_TWO_WORD_PHRASES: &[(&str, &str)] =
-    &[("select all", "SELECT_ALL"), ("new line", "ENTER")];
-
 /// Result of matching input text that may contain commands.
 #[derive(Debug, Clone)]
 pub enum MatchResult {
     /// Entire input is a command (no text to type).
-    ExactCommand(VoiceCommand),
+    ExactCommand(ResolvedCommand),
     /// No commands found - type the entire input.
     NoMatch,
     /// Command found within text - returns segments to process in order.
@@ -46,35 +42,69 @@ pub enum TextSegment {
     /// Text that should be typed as-is.
     Text(String),
     /// A command that should be executed.
-    Command(VoiceCommand),
+    Command(ResolvedCommand),
 }
 
 /// Combined matcher that checks custom phrases first, then built-in commands.
 /// Supports finding commands anywhere in the input text.
 pub struct CombinedMatcher {
     store: Arc<VoiceCommandStore>,
+    /// Only recognize phrases leaving dictation-only mode.
+    dictation_only: bool,
+    /// Only recognize phrases leaving spelling mode.
+    spelling: bool,
+    /// Whether a command waits for confirmation, so "confirm" is a command.
+    confirming: bool,
 }
 
 impl CombinedMatcher {
     /// Create a new combined matcher.
     pub fn new(store: Arc<VoiceCommandStore>) -> Self {
-        Self { store }
+        Self {
+            store,
+            dictation_only: false,
+            spelling: false,
+            confirming: false,
+        }
+    }
+
+    /// Turn dictation-only mode on or off.
+    pub fn set_dictation_only(&mut self, enabled: bool) {
+        self.dictation_only = enabled;
+    }
+
+    /// Turn spelling mode on or off.
+    pub fn set_spelling(&mut self, enabled: bool) {
+        self.spelling = enabled;
+    }
+
+    /// Set whether a command waits for confirmation. Otherwise the confirm
+    /// phrase is typed as text.
+    pub fn set_confirming(&mut self, confirming: bool) {
+        self.confirming = confirming;
     }
 
     /// Try to match as a spoken phrase first, then as a command code.
     /// This is the original exact-match method for backward compatibility.
-    pub fn match_input(&self, input: &str) -> Option<VoiceCommand> {
+    pub fn match_input(&self, input: &str) -> Option<ResolvedCommand> {
         // First, try to match as a spoken phrase (custom or default)
-        if let Some(command_code) = self.store.match_phrase(input) {
+        let phrases = self.phrases();
+        let words: Vec<&str> = input.split_whitespace().collect();
+        if let Some(command_code) = phrases.get(&words) {
             debug!(
                 "Matched spoken phrase '{}' to command '{}'",
                 input, command_code
             );
-            return VoiceCommand::parse(&command_code);
+            return self.store.resolve(command_code);
         }
 
-        // Fall back to direct command code parsing (for protocol messages)
-        VoiceCommand::parse(input)
+        // Fall back to direct command code lookup (for protocol messages)
+        if self.dictation_only || self.spelling || phrases.has_wake_word() {
+            return None;
+        }
+        self.store
+            .resolve(input)
+            .filter(|cmd| self.confirming || cmd.action != CommandAction::Confirm)
     }
 
     /// Match input that may contain commands anywhere in the text.
@@ -84,9 +114,14 @@ impl CombinedMatcher {
     ///
     /// Returns a MatchResult indicating how the input should be processed.
     ///
-    /// Commands ALWAYS execute when recognized - they are never typed as text.
+    /// Commands ALWAYS execute when recognized - they are never typed as text,
+    /// unless preceded by the literal escape word ("literal comma" → "comma")
+    /// or in dictation-only mode, where only its exit phrases are commands.
     /// The trailing space from Android word-by-word sending is irrelevant;
-    /// if a word matches a command, it executes.
+    /// if a word matches a command, it executes. Phrases of several words
+    /// such as "question mark" are matched too, the longest one winning,
+    /// and key commands take a repeat count ("delete three words",
+    /// "go left two times").
     pub fn match_with_context(&self, input: &str) -> MatchResult {
         // Try exact match first (handles single command words with or without trailing space)
         // Trim for matching but preserve original for mid-text processing
@@ -102,48 +137,84 @@ impl CombinedMatcher {
             return MatchResult::NoMatch;
         }
 
-        // Check each word to see if it matches a command phrase
+        // Locate each word in the original input to preserve spacing
+        let mut spans: Vec<(usize, usize)> = Vec::with_capacity(words.len());
+        let mut search_from = 0;
+        for word in &words {
+            if let Some(i) = input[search_from..].find(word) {
+                let start = search_from + i;
+                spans.push((start, start + word.len()));
+                search_from = start + word.len();
+            }
+        }
+
+        // Check the words starting at each word against command phrases
+        let phrases = self.phrases();
         let mut segments: Vec<TextSegment> = Vec::new();
         let mut text_buffer = String::new();
         let mut found_command = false;
+        let mut found_literal = false;
 
         // Track position in original input to preserve spacing
         let mut last_end = 0;
 
-        for word in &words {
-            // Find this word's position in the original input
-            let word_start = input[last_end..].find(word).map(|i| last_end + i);
-            if word_start.is_none() {
+        let mut i = 0;
+        while i < spans.len() {
+            let (word_start, word_end) = spans[i];
+            let word = &input[word_start..word_end];
+
+            // Literal escape: drop the escape word, type the command phrase
+            // after it as text
+            let escaped = (!self.dictation_only && phrases.is_literal(word))
+                .then(|| phrases.command_len(&words[i + 1..]))
+                .flatten();
+            if let Some(len) = escaped {
+                let (escaped_start, _) = spans[i + 1];
+                let (_, escaped_end) = spans[i + len];
+                text_buffer.push_str(&input[last_end..word_start]);
+                text_buffer.push_str(&input[escaped_start..escaped_end]);
+                last_end = escaped_end;
+                found_literal = true;
+                i += 1 + len;
                 continue;
             }
-            let word_start = word_start.unwrap();
-            let word_end = word_start + word.len();
-
-            // Check if this word matches a command
-            if let Some(command_code) = self.store.match_phrase(word) {
-                if let Some(cmd) = VoiceCommand::parse(&command_code) {
-                    // Found a command! First, flush any accumulated text (including leading whitespace)
-                    if word_start > last_end {
-                        text_buffer.push_str(&input[last_end..word_start]);
-                    }
-                    if !text_buffer.is_empty() {
-                        segments.push(TextSegment::Text(text_buffer.clone()));
-                        text_buffer.clear();
-                    }
-
-                    // Add the command
-                    debug!("Found command word '{}' → {:?} in text", word, cmd);
-                    segments.push(TextSegment::Command(cmd));
-                    found_command = true;
 
-                    last_end = word_end;
-                    continue;
+            // Prefer a counted key command, then the longest phrase starting at this word
+            let matched = phrases
+                .counted_match(&words[i..])
+                .and_then(|(code, count, consumed)| {
+                    Some((self.resolve_repeated(code, count)?, consumed))
+                })
+                .or_else(|| {
+                    let (code, consumed) = phrases.longest_match(&words[i..])?;
+                    Some((self.store.resolve(code)?, consumed))
+                })
+                .map(|(cmd, consumed)| (cmd, spans[i + consumed - 1].1, consumed));
+
+            if let Some((cmd, command_end, consumed)) = matched {
+                // Found a command! First, flush any accumulated text (including leading whitespace)
+                if word_start > last_end {
+                    text_buffer.push_str(&input[last_end..word_start]);
+                }
+                if !text_buffer.is_empty() {
+                    segments.push(TextSegment::Text(text_buffer.clone()));
+                    text_buffer.clear();
                 }
+
+                // Add the command
+                debug!("Found command phrase → {} in text", cmd.code);
+                segments.push(TextSegment::Command(cmd));
+                found_command = true;
+
+                last_end = command_end;
+                i += consumed;
+                continue;
             }
 
             // Not a command - add to text buffer (including any whitespace before it)
             text_buffer.push_str(&input[last_end..word_end]);
             last_end = word_end;
+            i += 1;
         }
 
         // Add any remaining text after the last word
@@ -156,70 +227,44 @@ impl CombinedMatcher {
             segments.push(TextSegment::Text(text_buffer));
         }
 
-        if found_command {
+        if found_command || found_literal {
             MatchResult::MidTextCommand(segments)
         } else {
             MatchResult::NoMatch
         }
     }
 
-    /// Match a single word against command phrases.
-    /// Returns the command code (e.g., "ENTER") if matched.
-    pub fn match_single_word(&self, word: &str) -> Option<String> {
-        let normalized = Self::normalize_for_matching(word);
-        // Check custom phrases first, then defaults
-        self.store.match_phrase(&normalized)
+    /// Match words against command phrases.
+    /// Returns the command code (e.g., "ENTER") if the words form a phrase.
+    pub fn match_words(&self, words: &[&str]) -> Option<String> {
+        self.phrases().get(words).map(str::to_string)
     }
 
-    /// Match two words as a potential 2-word command.
-    /// Returns the command code if the two words form a command.
-    pub fn match_two_words(&self, word1: &str, word2: &str) -> Option<String> {
-        let phrase = format!(
-            "{} {}",
-            Self::normalize_for_matching(word1),
-            Self::normalize_for_matching(word2)
-        );
-
-        // Check custom 2-word phrases first
-        if let Some(cmd) = self.store.match_phrase(&phrase) {
-            return Some(cmd);
-        }
-
-        // Check default 2-word phrases
-        for (default_phrase, command) in DEFAULT_TWO_WORD_PHRASES {
-            if phrase == *default_phrase {
-                return Some(command.to_string());
-            }
-        }
-
-        None
-    }
-
-    /// Check if a word could be the first word of a 2-word command.
-    /// Used for look-ahead buffering.
-    pub fn could_start_two_word_command(&self, word: &str) -> bool {
-        let normalized = Self::normalize_for_matching(word);
-
-        // Check default 2-word phrases
-        for (phrase, _) in DEFAULT_TWO_WORD_PHRASES {
-            if phrase.starts_with(&normalized) && phrase.contains(' ') {
-                let first_word = phrase.split_whitespace().next().unwrap_or("");
-                if first_word == normalized {
-                    return true;
-                }
-            }
+    /// The command phrases (custom first, then defaults) as a trie, for
+    /// matching words as they stream in. In spelling or dictation-only
+    /// mode, only the phrases leaving it; the confirm phrase only while a
+    /// command waits for confirmation.
+    pub fn phrases(&self) -> Arc<PhraseTrie> {
+        if self.spelling {
+            self.store
+                .phrase_trie_for(&CommandAction::Spelling { enabled: false })
+        } else if self.dictation_only {
+            self.store
+                .phrase_trie_for(&CommandAction::DictationOnly { enabled: false })
+        } else if !self.confirming {
+            self.store.phrase_trie_without(&CommandAction::Confirm)
+        } else {
+            self.store.phrase_trie()
         }
-
-        // Check custom phrases that have 2 words
-        // (Note: may need to add method to store to get all phrases)
-        self.store.could_start_two_word_command(&normalized)
     }
 
-    /// Normalize a word for matching: trim, lowercase, strip punctuation
-    fn normalize_for_matching(word: &str) -> String {
-        word.trim()
-            .trim_end_matches(['.', ',', '!', '?', ':', ';'])
-            .to_lowercase()
+    /// Resolve a command repeated `count` times, if it is a key command.
+    fn resolve_repeated(&self, code: &str, count: u32) -> Option<ResolvedCommand> {
+        let cmd = self.store.resolve(code)?;
+        Some(ResolvedCommand {
+            action: cmd.action.repeated(count)?,
+            code: cmd.code,
+        })
     }
 }
 
@@ -246,7 +291,7 @@ mod tests {
         // Should match the exact phrase
         let result = matcher.match_with_context("šmach");
         match result {
-            MatchResult::ExactCommand(cmd) => assert_eq!(cmd, VoiceCommand::Enter),
+            MatchResult::ExactCommand(cmd) => assert_eq!(cmd.code, "ENTER"),
             _ => panic!("Expected ExactCommand"),
         }
     }
@@ -260,7 +305,7 @@ mod tests {
         // Single word with trailing space should STILL be recognized as command
         let result = matcher.match_with_context("šmach ");
         match result {
-            MatchResult::ExactCommand(cmd) => assert_eq!(cmd, VoiceCommand::Enter),
+            MatchResult::ExactCommand(cmd) => assert_eq!(cmd.code, "ENTER"),
             _ => panic!(
                 "Expected ExactCommand - commands always execute regardless of trailing space"
             ),
@@ -283,7 +328,7 @@ mod tests {
                     _ => panic!("Expected Text at position 0"),
                 }
                 match &segments[1] {
-                    TextSegment::Command(cmd) => assert_eq!(*cmd, VoiceCommand::Enter),
+                    TextSegment::Command(cmd) => assert_eq!(cmd.code, "ENTER"),
                     _ => panic!("Expected Command at position 1"),
                 }
                 match &segments[2] {
@@ -317,7 +362,7 @@ mod tests {
             MatchResult::MidTextCommand(segments) => {
                 assert_eq!(segments.len(), 2);
                 match &segments[0] {
-                    TextSegment::Command(cmd) => assert_eq!(*cmd, VoiceCommand::Enter),
+                    TextSegment::Command(cmd) => assert_eq!(cmd.code, "ENTER"),
                     _ => panic!("Expected Command at position 0"),
                 }
                 match &segments[1] {
@@ -343,7 +388,7 @@ mod tests {
                     _ => panic!("Expected Text at position 0"),
                 }
                 match &segments[1] {
-                    TextSegment::Command(cmd) => assert_eq!(*cmd, VoiceCommand::Enter),
+                    TextSegment::Command(cmd) => assert_eq!(cmd.code, "ENTER"),
                     _ => panic!("Expected Command at position 1"),
                 }
                 match &segments[2] {
@@ -369,7 +414,7 @@ mod tests {
                     _ => panic!("Expected Text at position 0"),
                 }
                 match &segments[1] {
-                    TextSegment::Command(cmd) => assert_eq!(*cmd, VoiceCommand::Enter),
+                    TextSegment::Command(cmd) => assert_eq!(cmd.code, "ENTER"),
                     _ => panic!("Expected Command at position 1"),
                 }
             }
@@ -386,47 +431,41 @@ mod tests {
         // "enter" is a deliberate command
         let result = matcher.match_with_context("enter");
         match result {
-            MatchResult::ExactCommand(cmd) => assert_eq!(cmd, VoiceCommand::Enter),
+            MatchResult::ExactCommand(cmd) => assert_eq!(cmd.code, "ENTER"),
             _ => panic!("Expected ExactCommand for default 'enter' phrase"),
         }
 
         // "enter " with trailing space should STILL be a command
         let result = matcher.match_with_context("enter ");
         match result {
-            MatchResult::ExactCommand(cmd) => assert_eq!(cmd, VoiceCommand::Enter),
+            MatchResult::ExactCommand(cmd) => assert_eq!(cmd.code, "ENTER"),
             _ => panic!("Expected ExactCommand for 'enter ' - commands always execute"),
         }
     }
 
     #[test]
-    fn test_match_single_word_custom_phrase() {
+    fn test_match_one_word_custom_phrase() {
         let (_temp, store) = create_test_store_with_custom_phrase("šmach", "ENTER");
         let matcher = CombinedMatcher::new(store);
 
         // Should match custom phrase
-        assert_eq!(
-            matcher.match_single_word("šmach"),
-            Some("ENTER".to_string())
-        );
+        assert_eq!(matcher.match_words(&["šmach"]), Some("ENTER".to_string()));
 
         // Should not match unknown word
-        assert_eq!(matcher.match_single_word("unknown"), None);
+        assert_eq!(matcher.match_words(&["unknown"]), None);
     }
 
     #[test]
-    fn test_match_single_word_default_phrase() {
+    fn test_match_one_word_default_phrase() {
         let temp_dir = TempDir::new().unwrap();
         let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
         let matcher = CombinedMatcher::new(store);
 
         // Should match default "enter" phrase
-        assert_eq!(
-            matcher.match_single_word("enter"),
-            Some("ENTER".to_string())
-        );
+        assert_eq!(matcher.match_words(&["enter"]), Some("ENTER".to_string()));
 
         // Should match default "copy" phrase
-        assert_eq!(matcher.match_single_word("copy"), Some("COPY".to_string()));
+        assert_eq!(matcher.match_words(&["copy"]), Some("COPY".to_string()));
     }
 
     #[test]
@@ -437,7 +476,7 @@ mod tests {
 
         // "select all" should match SELECT_ALL
         assert_eq!(
-            matcher.match_two_words("select", "all"),
+            matcher.match_words(&["select", "all"]),
             Some("SELECT_ALL".to_string())
         );
     }
@@ -450,7 +489,7 @@ mod tests {
 
         // "new line" should match ENTER
         assert_eq!(
-            matcher.match_two_words("new", "line"),
+            matcher.match_words(&["new", "line"]),
             Some("ENTER".to_string())
         );
     }
@@ -462,26 +501,27 @@ mod tests {
         let matcher = CombinedMatcher::new(store);
 
         // Random words should not match
-        assert_eq!(matcher.match_two_words("hello", "world"), None);
+        assert_eq!(matcher.match_words(&["hello", "world"]), None);
     }
 
     #[test]
-    fn test_could_start_two_word_command() {
+    fn test_could_continue_phrase() {
         let temp_dir = TempDir::new().unwrap();
         let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
         let matcher = CombinedMatcher::new(store);
 
         // "select" starts "select all"
-        assert!(matcher.could_start_two_word_command("select"));
+        assert!(matcher.phrases().could_continue(&["select"]));
 
         // "new" starts "new line"
-        assert!(matcher.could_start_two_word_command("new"));
+        assert!(matcher.phrases().could_continue(&["new"]));
 
-        // "hello" doesn't start any 2-word command
-        assert!(!matcher.could_start_two_word_command("hello"));
+        // "hello" doesn't start any longer phrase
+        assert!(!matcher.phrases().could_continue(&["hello"]));
 
-        // "all" doesn't start a 2-word command (it's the second word)
-        assert!(!matcher.could_start_two_word_command("all"));
+        // "all" starts "all caps"; "mark" is only ever a second word
+        assert!(matcher.phrases().could_continue(&["all"]));
+        assert!(!matcher.phrases().could_continue(&["mark"]));
     }
 
     #[test]
@@ -490,40 +530,22 @@ mod tests {
         let matcher = CombinedMatcher::new(store);
 
         // "hello." should match "hello" (punctuation stripped)
-        assert_eq!(
-            matcher.match_single_word("hello."),
-            Some("ENTER".to_string())
-        );
+        assert_eq!(matcher.match_words(&["hello."]), Some("ENTER".to_string()));
 
         // "hello," should match "hello"
-        assert_eq!(
-            matcher.match_single_word("hello,"),
-            Some("ENTER".to_string())
-        );
+        assert_eq!(matcher.match_words(&["hello,"]), Some("ENTER".to_string()));
 
         // "hello!" should match "hello"
-        assert_eq!(
-            matcher.match_single_word("hello!"),
-            Some("ENTER".to_string())
-        );
+        assert_eq!(matcher.match_words(&["hello!"]), Some("ENTER".to_string()));
 
         // "hello?" should match "hello"
-        assert_eq!(
-            matcher.match_single_word("hello?"),
-            Some("ENTER".to_string())
-        );
+        assert_eq!(matcher.match_words(&["hello?"]), Some("ENTER".to_string()));
 
         // "hello:" should match "hello"
-        assert_eq!(
-            matcher.match_single_word("hello:"),
-            Some("ENTER".to_string())
-        );
+        assert_eq!(matcher.match_words(&["hello:"]), Some("ENTER".to_string()));
 
         // "hello;" should match "hello"
-        assert_eq!(
-            matcher.match_single_word("hello;"),
-            Some("ENTER".to_string())
-        );
+        assert_eq!(matcher.match_words(&["hello;"]), Some("ENTER".to_string()));
     }
 
     #[test]
@@ -534,14 +556,257 @@ mod tests {
 
         // "select all." should match (punctuation stripped from second word)
         assert_eq!(
-            matcher.match_two_words("select", "all."),
+            matcher.match_words(&["select", "all."]),
             Some("SELECT_ALL".to_string())
         );
 
         // "new line!" should match
         assert_eq!(
-            matcher.match_two_words("new", "line!"),
+            matcher.match_words(&["new", "line!"]),
             Some("ENTER".to_string())
         );
     }
+
+    #[test]
+    fn test_two_word_phrase_and_literal_in_text() {
+        let (_temp, store) = create_test_store_with_custom_phrase("šmach", "ENTER");
+        let matcher = CombinedMatcher::new(store);
+
+        let result = matcher.match_with_context("really question mark yes");
+        match result {
+            MatchResult::MidTextCommand(segments) => {
+                assert_eq!(segments.len(), 3);
+                match &segments[1] {
+                    TextSegment::Command(cmd) => assert_eq!(cmd.code, "QUESTION_MARK"),
+                    _ => panic!("Expected Command at position 1"),
+                }
+            }
+            _ => panic!("Expected MidTextCommand"),
+        }
+
+        // The escaped word is typed, the escape word is dropped
+        let result = matcher.match_with_context("say literal šmach now");
+        match result {
+            MatchResult::MidTextCommand(segments) => {
+                assert_eq!(segments.len(), 1);
+                match &segments[0] {
+                    TextSegment::Text(text) => assert_eq!(text, "say šmach now"),
+                    _ => panic!("Expected Text"),
+                }
+            }
+            _ => panic!("Expected MidTextCommand"),
+        }
+
+        // A whole phrase is escaped
+        let result = matcher.match_with_context("literal select all please");
+        match result {
+            MatchResult::MidTextCommand(segments) => {
+                assert_eq!(segments.len(), 1);
+                match &segments[0] {
+                    TextSegment::Text(text) => assert_eq!(text, "select all please"),
+                    _ => panic!("Expected Text"),
+                }
+            }
+            _ => panic!("Expected MidTextCommand"),
+        }
+
+        // Before other words, the escape word is typed
+        assert!(matches!(
+            matcher.match_with_context("the literal meaning"),
+            MatchResult::NoMatch
+        ));
+    }
+
+    #[test]
+    fn test_repeat_counts() {
+        let (_temp, store) = create_test_store_with_custom_phrase("šmach", "ENTER");
+        let matcher = CombinedMatcher::new(store);
+
+        let command_at = |input: &str, index: usize| match matcher.match_with_context(input) {
+            MatchResult::MidTextCommand(segments) => match &segments[index] {
+                TextSegment::Command(cmd) => cmd.clone(),
+                other => panic!("Expected Command, got {:?}", other),
+            },
+            other => panic!("Expected MidTextCommand, got {:?}", other),
+        };
+
+        let cmd = command_at("delete three words", 0);
+        assert_eq!(cmd.code, "DELETE_WORD");
+        assert!(matches!(cmd.action, CommandAction::Key { repeat: 3, .. }));
+
+        let cmd = command_at("hello go left 2 times", 1);
+        assert_eq!(cmd.code, "GO_LEFT");
+        assert!(matches!(cmd.action, CommandAction::Key { repeat: 2, .. }));
+
+        let cmd = command_at("backspace five times", 0);
+        assert!(matches!(cmd.action, CommandAction::Key { repeat: 5, .. }));
+
+        // Counts only apply to key commands
+        match matcher.match_with_context("cancel three times") {
+            MatchResult::MidTextCommand(segments) => assert_eq!(segments.len(), 2),
+            other => panic!("Expected MidTextCommand, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_long_phrase_in_text() {
+        let (_temp, store) = create_test_store_with_custom_phrase("open new browser tab", "ENTER");
+        let matcher = CombinedMatcher::new(store);
+
+        assert_eq!(
+            matcher.match_words(&["open", "new", "browser", "tab."]),
+            Some("ENTER".to_string())
+        );
+        assert!(matcher
+            .phrases()
+            .could_continue(&["open", "new", "browser"]));
+
+        let result = matcher.match_with_context("please open new browser tab now");
+        match result {
+            MatchResult::MidTextCommand(segments) => {
+                assert_eq!(segments.len(), 3);
+                match &segments[0] {
+                    TextSegment::Text(text) => assert_eq!(text, "please "),
+                    _ => panic!("Expected Text at position 0"),
+                }
+                match &segments[1] {
+                    TextSegment::Command(cmd) => assert_eq!(cmd.code, "ENTER"),
+                    _ => panic!("Expected Command at position 1"),
+                }
+                match &segments[2] {
+                    TextSegment::Text(text) => assert_eq!(text, " now"),
+                    _ => panic!("Expected Text at position 2"),
+                }
+            }
+            _ => panic!("Expected MidTextCommand"),
+        }
+
+        // An incomplete phrase is typed as text
+        assert!(matches!(
+            matcher.match_with_context("open new browser window"),
+            MatchResult::NoMatch
+        ));
+    }
+
+    #[test]
+    fn test_dictation_only() {
+        let (_temp, store) = create_test_store_with_custom_phrase("šmach", "ENTER");
+        let mut matcher = CombinedMatcher::new(store);
+        assert!(matches!(
+            matcher.match_with_context("dictation only"),
+            MatchResult::ExactCommand(ResolvedCommand {
+                action: CommandAction::DictationOnly { enabled: true },
+                ..
+            })
+        ));
+
+        matcher.set_dictation_only(true);
+        for input in [
+            "copy",
+            "šmach",
+            "ENTER",
+            "press enter and copy",
+            "dictation only",
+        ] {
+            assert!(
+                matches!(matcher.match_with_context(input), MatchResult::NoMatch),
+                "{} should be text",
+                input
+            );
+        }
+        assert!(!matcher.phrases().could_continue(&["select"]));
+
+        // Only the exit phrase is a command
+        match matcher.match_with_context("that is all command mode") {
+            MatchResult::MidTextCommand(segments) => match &segments[1] {
+                TextSegment::Command(cmd) => assert_eq!(cmd.code, "COMMAND_MODE"),
+                other => panic!("Expected Command, got {:?}", other),
+            },
+            other => panic!("Expected MidTextCommand, got {:?}", other),
+        }
+        assert_eq!(
+            matcher.match_words(&["command", "mode"]),
+            Some("COMMAND_MODE".to_string())
+        );
+
+        // The escape word is typed like any other word
+        match matcher.match_with_context("literal command mode") {
+            MatchResult::MidTextCommand(segments) => {
+                assert!(matches!(&segments[0], TextSegment::Text(text) if text == "literal "));
+                assert!(
+                    matches!(&segments[1], TextSegment::Command(cmd) if cmd.code == "COMMAND_MODE")
+                );
+            }
+            other => panic!("Expected MidTextCommand, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_spelling_phrases() {
+        let (_temp, store) = create_test_store_with_custom_phrase("šmach", "ENTER");
+        let mut matcher = CombinedMatcher::new(store);
+        matcher.set_dictation_only(true);
+        matcher.set_spelling(true);
+
+        // Only the phrase leaving spelling mode is a command
+        for input in ["copy", "ENTER", "command mode", "start spelling"] {
+            assert!(
+                matches!(matcher.match_with_context(input), MatchResult::NoMatch),
+                "{} should be spelled",
+                input
+            );
+        }
+        assert_eq!(
+            matcher.match_words(&["stop", "spelling"]),
+            Some("SPELL_OFF".to_string())
+        );
+    }
+
+    #[test]
+    fn test_wake_word() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path()).unwrap());
+        store.set_wake_word(Some("computer"));
+        let matcher = CombinedMatcher::new(store);
+
+        // Command words and codes without the wake word are text
+        for input in [
+            "paste",
+            "PASTE",
+            "copy that and paste it",
+            "delete three words",
+        ] {
+            assert!(
+                matches!(matcher.match_with_context(input), MatchResult::NoMatch),
+                "{} should be text",
+                input
+            );
+        }
+
+        assert!(matches!(
+            matcher.match_with_context("computer paste"),
+            MatchResult::ExactCommand(ResolvedCommand { ref code, .. }) if code == "PASTE"
+        ));
+        match matcher.match_with_context("paste this computer delete three words") {
+            MatchResult::MidTextCommand(segments) => {
+                assert_eq!(segments.len(), 2);
+                match &segments[0] {
+                    TextSegment::Text(text) => assert_eq!(text, "paste this "),
+                    other => panic!("Expected Text, got {:?}", other),
+                }
+                match &segments[1] {
+                    TextSegment::Command(cmd) => {
+                        assert_eq!(cmd.code, "DELETE_WORD");
+                        assert!(matches!(cmd.action, CommandAction::Key { repeat: 3, .. }));
+                    }
+                    other => panic!("Expected Command, got {:?}", other),
+                }
+            }
+            other => panic!("Expected MidTextCommand, got {:?}", other),
+        }
+        assert!(matches!(
+            matcher.match_with_context("my computer crashed"),
+            MatchResult::NoMatch
+        ));
+    }
 }
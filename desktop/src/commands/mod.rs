@@ -14,84 +14,377 @@
 
 //! Voice command execution module.
 //!
-//! Maps received commands to keyboard actions.
+//! Maps received commands to actions and runs them through the injector.
 
-use anyhow::Result;
-use tracing::debug;
+use anyhow::{Context, Result};
+use std::fmt;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
 
-use crate::input::{InputInjector, Key, Modifier};
+use crate::input::InputInjector;
+
+mod action;
+pub use action::{CommandAction, ResolvedCommand};
+
+mod capitalization;
+pub use capitalization::{Capitalization, CapsMode};
+
+mod punctuation;
+pub use punctuation::{Attach, SmartSpacing};
+
+mod snippet;
+use snippet::expand_placeholders;
+
+mod count;
+
+mod history;
+pub use history::{UndoHistory, UtteranceKey};
+
+mod normalize;
+pub use normalize::{normalizer_for, Normalizer, SpokenFormHold};
+
+mod spelling;
+pub use spelling::Speller;
+
+mod safety;
+pub use safety::{SafetyCheck, SafetyGuard, SafetyPolicy};
+
+mod fuzzy;
+pub use fuzzy::{FuzzySettings, DEFAULT_FUZZY_THRESHOLD};
+
+mod trie;
+pub use trie::PhraseTrie;
 
 mod matcher;
 pub use matcher::{CombinedMatcher, MatchResult, TextSegment};
 
 mod word_buffer;
-pub use word_buffer::{ProcessedItem, WordBuffer};
-
-/// Voice command types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum VoiceCommand {
-    /// Press Enter key.
-    Enter,
-    /// Select all (Ctrl+A).
-    SelectAll,
-    /// Copy (Ctrl+C).
-    Copy,
-    /// Paste (Ctrl+V).
-    Paste,
-    /// Cut (Ctrl+X).
-    Cut,
-    /// Cancel/discard (no action).
-    Cancel,
+pub use word_buffer::{ProcessedItem, WordBuffer, LOOK_AHEAD_TIMEOUT};
+
+/// Spoken word that makes the command phrase after it typed literally, never
+/// run (e.g., "literal comma" types "comma"). Before other words it is typed.
+/// Locale packs may set their own.
+pub const LITERAL_WORD: &str = "literal";
+
+/// How often a running delay checks for cancellation.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// How long an external program may run when the action sets no timeout.
+const DEFAULT_PROGRAM_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Error returned when a macro is cancelled.
+#[derive(Debug)]
+pub struct MacroCancelled;
+
+impl fmt::Display for MacroCancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Macro cancelled")
+    }
+}
+
+impl std::error::Error for MacroCancelled {}
+
+/// Error returned when an external program fails or times out.
+#[derive(Debug)]
+pub struct ProgramFailed {
+    pub program: String,
+    /// Exit status, `None` if the program was killed.
+    pub exit_code: Option<i32>,
 }
 
-impl VoiceCommand {
-    /// Parse from string code.
-    pub fn parse(s: &str) -> Option<Self> {
-        match s.trim().to_uppercase().as_str() {
-            "ENTER" => Some(Self::Enter),
-            "SELECT_ALL" => Some(Self::SelectAll),
-            "COPY" => Some(Self::Copy),
-            "PASTE" => Some(Self::Paste),
-            "CUT" => Some(Self::Cut),
-            "CANCEL" => Some(Self::Cancel),
-            _ => None,
+impl fmt::Display for ProgramFailed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.exit_code {
+            Some(code) => write!(f, "{} exited with status {}", self.program, code),
+            None => write!(f, "{} was killed", self.program),
         }
     }
 }
 
-/// Execute a voice command using the given injector.
-pub fn execute(command: &VoiceCommand, injector: &dyn InputInjector) -> Result<()> {
-    debug!("Executing command: {:?}", command);
-
-    match command {
-        VoiceCommand::Enter => injector.press_key(Key::Enter),
-        VoiceCommand::SelectAll => injector.key_combo(&[Modifier::Ctrl], Key::A),
-        VoiceCommand::Copy => injector.key_combo(&[Modifier::Ctrl], Key::C),
-        VoiceCommand::Paste => injector.key_combo(&[Modifier::Ctrl], Key::V),
-        VoiceCommand::Cut => injector.key_combo(&[Modifier::Ctrl], Key::X),
-        VoiceCommand::Cancel => {
-            debug!("Cancel command - no action taken");
+impl std::error::Error for ProgramFailed {}
+
+/// Execute a command action using the given injector.
+///
+/// Macros stop at the first failing step, and return `MacroCancelled` if
+/// `cancel` is set between steps or during a delay. `command` steps must
+/// have been expanded (see `CommandAction::expand`).
+pub fn execute(
+    action: &CommandAction,
+    injector: &dyn InputInjector,
+    cancel: &AtomicBool,
+) -> Result<()> {
+    debug!("Executing action: {:?}", action);
+
+    match action {
+        CommandAction::Key { keys, repeat } => {
+            for _ in 0..*repeat {
+                injector.press_chord(keys)?;
+            }
+            Ok(())
+        }
+        CommandAction::Text { text } | CommandAction::Punctuation { text, .. } => {
+            injector.type_text(text)
+        }
+        CommandAction::Snippet { text } => {
+            let text =
+                expand_placeholders(text, &chrono::Local::now(), &|| injector.clipboard_text())?;
+            injector.type_text(&text)
+        }
+        CommandAction::Macro { steps } => {
+            for step in steps {
+                if cancel.load(Ordering::Relaxed) {
+                    return Err(MacroCancelled.into());
+                }
+                execute(step, injector, cancel)?;
+            }
+            Ok(())
+        }
+        CommandAction::Delay { ms } => {
+            let deadline = Instant::now() + Duration::from_millis(*ms);
+            while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+                if cancel.load(Ordering::Relaxed) {
+                    return Err(MacroCancelled.into());
+                }
+                thread::sleep(remaining.min(CANCEL_POLL_INTERVAL));
+            }
             Ok(())
         }
+        CommandAction::Command { command } => {
+            anyhow::bail!("Command '{}' was not expanded", command)
+        }
+        CommandAction::Run {
+            program,
+            args,
+            timeout_ms,
+            ..
+        } => {
+            let timeout = timeout_ms.map_or(DEFAULT_PROGRAM_TIMEOUT, Duration::from_millis);
+            run_program(program, args, timeout, cancel)
+        }
+        // Caps, undo and the dictation modes only affect dictated text,
+        // which macros don't produce
+        CommandAction::Cancel
+        | CommandAction::Confirm
+        | CommandAction::Caps { .. }
+        | CommandAction::Undo
+        | CommandAction::DictationOnly { .. }
+        | CommandAction::Spelling { .. }
+        | CommandAction::None => {
+            debug!("No-op action - no action taken");
+            Ok(())
+        }
+    }
+}
+
+/// Run a program directly (no shell) and wait for it, killing it and the
+/// processes it started on timeout or cancellation. Its output is
+/// discarded. Fails with `ProgramFailed` on a non-zero exit.
+fn run_program(
+    program: &str,
+    args: &[String],
+    timeout: Duration,
+    cancel: &AtomicBool,
+) -> Result<()> {
+    info!("Running program: {} {:?}", program, args);
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .with_context(|| format!("Failed to start {}", program))?;
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            debug!("{} finished: {}", program, status);
+            return match status.code() {
+                Some(0) => Ok(()),
+                exit_code => Err(ProgramFailed {
+                    program: program.to_string(),
+                    exit_code,
+                }
+                .into()),
+            };
+        }
+        if cancel.load(Ordering::Relaxed) {
+            kill_process_group(&mut child);
+            return Err(MacroCancelled.into());
+        }
+        if Instant::now() >= deadline {
+            warn!("{} timed out after {:?}, killing it", program, timeout);
+            kill_process_group(&mut child);
+            return Err(ProgramFailed {
+                program: program.to_string(),
+                exit_code: None,
+            })
+            .with_context(|| format!("{} timed out after {} s", program, timeout.as_secs()));
+        }
+        thread::sleep(CANCEL_POLL_INTERVAL);
     }
 }
 
+/// Kill a program started in its own process group, along with the
+/// processes it started, and reap it.
+fn kill_process_group(child: &mut Child) {
+    // SAFETY: killpg only sends a signal; the group id is the child's pid
+    // (`process_group(0)`), and the child isn't reaped yet, so the id
+    // can't have been reused
+    unsafe {
+        libc::killpg(child.id() as libc::pid_t, libc::SIGKILL);
+    }
+    let _ = child.wait();
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::Key;
+    use anyhow::anyhow;
+    use parking_lot::Mutex;
+
+    /// Injector that records calls and fails on a given text.
+    #[derive(Default)]
+    struct RecordingInjector {
+        calls: Mutex<Vec<String>>,
+        fail_on: Option<String>,
+    }
+
+    impl InputInjector for RecordingInjector {
+        fn backend_name(&self) -> &'static str {
+            "Test"
+        }
+
+        fn type_text(&self, text: &str) -> Result<()> {
+            if self.fail_on.as_deref() == Some(text) {
+                return Err(anyhow!("injection failed"));
+            }
+            self.calls.lock().push(format!("type {}", text));
+            Ok(())
+        }
+
+        fn press_key(&self, key: Key) -> Result<()> {
+            self.calls.lock().push(format!("key {:?}", key));
+            Ok(())
+        }
+
+        fn key_combo(&self, modifiers: &[crate::input::Modifier], key: Key) -> Result<()> {
+            self.calls
+                .lock()
+                .push(format!("combo {:?} {:?}", modifiers, key));
+            Ok(())
+        }
+    }
+
+    fn text(text: &str) -> CommandAction {
+        CommandAction::Text {
+            text: text.to_string(),
+        }
+    }
 
     #[test]
-    fn test_voice_command_parse() {
-        assert_eq!(VoiceCommand::parse("ENTER"), Some(VoiceCommand::Enter));
-        assert_eq!(VoiceCommand::parse("enter"), Some(VoiceCommand::Enter));
+    fn test_execute_sequence_stops_on_error() {
+        let injector = RecordingInjector {
+            fail_on: Some("bad".to_string()),
+            ..Default::default()
+        };
+        let action = CommandAction::Macro {
+            steps: vec![text("hello"), text("bad"), CommandAction::key(Key::Enter)],
+        };
+
+        assert!(execute(&action, &injector, &AtomicBool::new(false)).is_err());
+        assert_eq!(*injector.calls.lock(), vec!["type hello".to_string()]);
+    }
+
+    #[test]
+    fn test_execute_macro_cancelled() {
+        let injector = RecordingInjector::default();
+        let action = CommandAction::Macro {
+            steps: vec![text("hello"), CommandAction::key(Key::Enter)],
+        };
+
+        let result = execute(&action, &injector, &AtomicBool::new(true));
+        assert!(result.unwrap_err().is::<MacroCancelled>());
+        assert!(injector.calls.lock().is_empty());
+    }
+
+    #[test]
+    fn test_run_program_exit_status() {
+        let run = |program: &str, args: &[&str], timeout_ms| {
+            let action = CommandAction::Run {
+                program: program.to_string(),
+                args: args.iter().map(|a| a.to_string()).collect(),
+                confirm: false,
+                timeout_ms: Some(timeout_ms),
+            };
+            execute(
+                &action,
+                &RecordingInjector::default(),
+                &AtomicBool::new(false),
+            )
+        };
+
+        assert!(run("true", &[], 5000).is_ok());
+
+        let err = run("sh", &["-c", "exit 3"], 5000).unwrap_err();
         assert_eq!(
-            VoiceCommand::parse("SELECT_ALL"),
-            Some(VoiceCommand::SelectAll)
+            err.downcast_ref::<ProgramFailed>().unwrap().exit_code,
+            Some(3)
         );
-        assert_eq!(VoiceCommand::parse("COPY"), Some(VoiceCommand::Copy));
-        assert_eq!(VoiceCommand::parse("PASTE"), Some(VoiceCommand::Paste));
-        assert_eq!(VoiceCommand::parse("CUT"), Some(VoiceCommand::Cut));
-        assert_eq!(VoiceCommand::parse("CANCEL"), Some(VoiceCommand::Cancel));
-        assert_eq!(VoiceCommand::parse("INVALID"), None);
+
+        // Arguments are passed as-is, not through a shell
+        assert!(run("test", &["$PRONTAFON_UNSET", "!=", ""], 5000).is_ok());
+
+        let err = run("sleep", &["5"], 100).unwrap_err();
+        assert_eq!(err.downcast_ref::<ProgramFailed>().unwrap().exit_code, None);
+
+        assert!(run("/nonexistent/program", &[], 5000).is_err());
+    }
+
+    #[test]
+    fn test_cancel_program_kills_its_children() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let pid_file = temp_dir.path().join("child.pid");
+        let action = CommandAction::Run {
+            program: "sh".to_string(),
+            args: vec![
+                "-c".to_string(),
+                format!("sleep 30 & echo $! > {}; wait", pid_file.display()),
+            ],
+            confirm: false,
+            timeout_ms: Some(30_000),
+        };
+        let cancel = std::sync::Arc::new(AtomicBool::new(false));
+        let task_cancel = cancel.clone();
+        let runner =
+            thread::spawn(move || execute(&action, &RecordingInjector::default(), &task_cancel));
+
+        let pid = loop {
+            let pid = std::fs::read_to_string(&pid_file).unwrap_or_default();
+            if pid.ends_with('\n') {
+                break pid.trim().to_string();
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        cancel.store(true, Ordering::Relaxed);
+        let result = runner.join().unwrap();
+        assert!(result.unwrap_err().is::<MacroCancelled>());
+
+        // The script's child is gone too, or dead and waiting to be reaped
+        let child_running = || {
+            let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid));
+            stat.is_ok_and(|stat| stat.split_whitespace().nth(2) != Some("Z"))
+        };
+        let deadline = Instant::now() + Duration::from_secs(2);
+        while child_running() && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!child_running());
+        Ok(())
     }
 }
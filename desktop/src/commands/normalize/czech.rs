@@ -0,0 +1,166 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Czech numbers, amounts, times and dates.
+
+use super::lexicon::{strings, valued, Currency, Lexicon, OrdinalStyle, Unit};
+
+pub fn lexicon() -> Lexicon {
+    Lexicon {
+        language: "cs".to_string(),
+        cardinals: valued(&[
+            ("nula", 0),
+            ("jeden", 1),
+            ("jedna", 1),
+            ("jedno", 1),
+            ("dva", 2),
+            ("dvě", 2),
+            ("tři", 3),
+            ("čtyři", 4),
+            ("pět", 5),
+            ("šest", 6),
+            ("sedm", 7),
+            ("osm", 8),
+            ("devět", 9),
+            ("deset", 10),
+            ("jedenáct", 11),
+            ("dvanáct", 12),
+            ("třináct", 13),
+            ("čtrnáct", 14),
+            ("patnáct", 15),
+            ("šestnáct", 16),
+            ("sedmnáct", 17),
+            ("osmnáct", 18),
+            ("devatenáct", 19),
+            ("dvacet", 20),
+            ("třicet", 30),
+            ("čtyřicet", 40),
+            ("padesát", 50),
+            ("šedesát", 60),
+            ("sedmdesát", 70),
+            ("osmdesát", 80),
+            ("devadesát", 90),
+        ]),
+        hundreds: strings(&["sto", "stě", "sta", "set"]),
+        scales: valued(&[
+            ("tisíc", 1_000),
+            ("tisíce", 1_000),
+            ("tisíci", 1_000),
+            ("milion", 1_000_000),
+            ("miliony", 1_000_000),
+            ("milionů", 1_000_000),
+            ("miliarda", 1_000_000_000),
+            ("miliardy", 1_000_000_000),
+            ("miliard", 1_000_000_000),
+        ]),
+        // Stems, inflected with the endings below ("pát" + "ého")
+        ordinals: valued(&[
+            ("prvn", 1),
+            ("druh", 2),
+            ("třet", 3),
+            ("čtvrt", 4),
+            ("pát", 5),
+            ("šest", 6),
+            ("sedm", 7),
+            ("osm", 8),
+            ("devát", 9),
+            ("desát", 10),
+            ("jedenáct", 11),
+            ("dvanáct", 12),
+            ("třináct", 13),
+            ("čtrnáct", 14),
+            ("patnáct", 15),
+            ("šestnáct", 16),
+            ("sedmnáct", 17),
+            ("osmnáct", 18),
+            ("devatenáct", 19),
+            ("dvacát", 20),
+            ("třicát", 30),
+            ("čtyřicát", 40),
+            ("padesát", 50),
+            ("šedesát", 60),
+            ("sedmdesát", 70),
+            ("osmdesát", 80),
+            ("devadesát", 90),
+            ("st", 100),
+            ("tisíc", 1_000),
+        ]),
+        ordinal_endings: strings(&[
+            "ý", "á", "é", "ého", "ému", "ém", "ým", "ou", "ých", "ými", "í", "ího", "ímu", "ím",
+            "ích", "ími",
+        ]),
+        compound_infix: Some("a".to_string()),
+        conjunctions: Vec::new(),
+        one_words: Vec::new(),
+        zero_digits: Vec::new(),
+        decimal_words: strings(&["celá", "celé", "celých"]),
+        decimal_separator: ",".to_string(),
+        group_separator: " ".to_string(),
+        ordinal_style: OrdinalStyle::Period,
+        currencies: vec![
+            Currency {
+                words: strings(&["koruna", "koruny", "korun", "korunu"]),
+                minor_words: strings(&["haléř", "haléře", "haléřů"]),
+                template: "{} Kč".to_string(),
+            },
+            Currency {
+                words: strings(&["euro", "eura", "eur"]),
+                minor_words: strings(&["cent", "centy", "centů"]),
+                template: "{} €".to_string(),
+            },
+            Currency {
+                words: strings(&["dolar", "dolary", "dolarů"]),
+                minor_words: strings(&["cent", "centy", "centů"]),
+                template: "{} $".to_string(),
+            },
+        ],
+        units: vec![
+            Unit {
+                words: strings(&["procento", "procenta", "procent"]),
+                template: "{} %".to_string(),
+            },
+            Unit {
+                words: strings(&["stupeň", "stupně", "stupňů"]),
+                template: "{}°".to_string(),
+            },
+        ],
+        oclock_words: Vec::new(),
+        hour_words: strings(&["hodina", "hodiny", "hodin"]),
+        minute_words: strings(&["minuta", "minuty", "minut"]),
+        meridiems: Vec::new(),
+        months: [
+            ["leden", "ledna"],
+            ["únor", "února"],
+            ["březen", "března"],
+            ["duben", "dubna"],
+            ["květen", "května"],
+            ["červen", "června"],
+            ["červenec", "července"],
+            ["srpen", "srpna"],
+            ["září", "září"],
+            ["říjen", "října"],
+            ["listopad", "listopadu"],
+            ["prosinec", "prosince"],
+        ]
+        .iter()
+        .map(|forms| strings(forms))
+        .collect(),
+        capitalize_months: false,
+        of_words: Vec::new(),
+        date_articles: Vec::new(),
+        date_template: "{day}. {month}".to_string(),
+        date_year_template: "{day}. {month} {year}".to_string(),
+        paired_years: false,
+    }
+}
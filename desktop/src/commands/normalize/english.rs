@@ -0,0 +1,158 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! English numbers, amounts, times and dates (US conventions).
+
+use super::lexicon::{strings, valued, Currency, Lexicon, OrdinalStyle, Unit};
+
+pub fn lexicon() -> Lexicon {
+    Lexicon {
+        language: "en".to_string(),
+        cardinals: valued(&[
+            ("zero", 0),
+            ("one", 1),
+            ("two", 2),
+            ("three", 3),
+            ("four", 4),
+            ("five", 5),
+            ("six", 6),
+            ("seven", 7),
+            ("eight", 8),
+            ("nine", 9),
+            ("ten", 10),
+            ("eleven", 11),
+            ("twelve", 12),
+            ("thirteen", 13),
+            ("fourteen", 14),
+            ("fifteen", 15),
+            ("sixteen", 16),
+            ("seventeen", 17),
+            ("eighteen", 18),
+            ("nineteen", 19),
+            ("twenty", 20),
+            ("thirty", 30),
+            ("forty", 40),
+            ("fifty", 50),
+            ("sixty", 60),
+            ("seventy", 70),
+            ("eighty", 80),
+            ("ninety", 90),
+        ]),
+        hundreds: strings(&["hundred"]),
+        scales: valued(&[
+            ("thousand", 1_000),
+            ("million", 1_000_000),
+            ("billion", 1_000_000_000),
+        ]),
+        ordinals: valued(&[
+            ("first", 1),
+            ("second", 2),
+            ("third", 3),
+            ("fourth", 4),
+            ("fifth", 5),
+            ("sixth", 6),
+            ("seventh", 7),
+            ("eighth", 8),
+            ("ninth", 9),
+            ("tenth", 10),
+            ("eleventh", 11),
+            ("twelfth", 12),
+            ("thirteenth", 13),
+            ("fourteenth", 14),
+            ("fifteenth", 15),
+            ("sixteenth", 16),
+            ("seventeenth", 17),
+            ("eighteenth", 18),
+            ("nineteenth", 19),
+            ("twentieth", 20),
+            ("thirtieth", 30),
+            ("fortieth", 40),
+            ("fiftieth", 50),
+            ("sixtieth", 60),
+            ("seventieth", 70),
+            ("eightieth", 80),
+            ("ninetieth", 90),
+            ("hundredth", 100),
+            ("thousandth", 1_000),
+        ]),
+        ordinal_endings: Vec::new(),
+        compound_infix: None,
+        conjunctions: strings(&["and"]),
+        one_words: strings(&["a"]),
+        zero_digits: strings(&["oh"]),
+        decimal_words: strings(&["point"]),
+        decimal_separator: ".".to_string(),
+        group_separator: ",".to_string(),
+        ordinal_style: OrdinalStyle::English,
+        currencies: vec![
+            Currency {
+                words: strings(&["dollar", "dollars"]),
+                minor_words: strings(&["cent", "cents"]),
+                template: "${}".to_string(),
+            },
+            Currency {
+                words: strings(&["euro", "euros"]),
+                minor_words: Vec::new(),
+                template: "€{}".to_string(),
+            },
+            Currency {
+                words: strings(&["pound", "pounds"]),
+                minor_words: strings(&["pence"]),
+                template: "£{}".to_string(),
+            },
+        ],
+        units: vec![
+            Unit {
+                words: strings(&["percent"]),
+                template: "{}%".to_string(),
+            },
+            Unit {
+                words: strings(&["degree", "degrees"]),
+                template: "{}°".to_string(),
+            },
+        ],
+        oclock_words: strings(&["o'clock", "o’clock"]),
+        hour_words: Vec::new(),
+        minute_words: Vec::new(),
+        meridiems: vec![
+            ("am".to_string(), "AM".to_string()),
+            ("a.m".to_string(), "AM".to_string()),
+            ("pm".to_string(), "PM".to_string()),
+            ("p.m".to_string(), "PM".to_string()),
+        ],
+        months: [
+            "january",
+            "february",
+            "march",
+            "april",
+            "may",
+            "june",
+            "july",
+            "august",
+            "september",
+            "october",
+            "november",
+            "december",
+        ]
+        .iter()
+        .map(|month| vec![month.to_string()])
+        .collect(),
+        capitalize_months: true,
+        of_words: strings(&["of"]),
+        date_articles: strings(&["the"]),
+        date_template: "{month} {day}".to_string(),
+        date_year_template: "{month} {day}, {year}".to_string(),
+        paired_years: true,
+    }
+}
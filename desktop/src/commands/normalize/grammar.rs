@@ -0,0 +1,508 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lexicon-driven parsing of spoken numbers, amounts, times and dates.
+//!
+//! Each parser takes the lowercased words starting at the current position
+//! and returns the written form with the number of words it used.
+
+use super::lexicon::{has, Lexicon, NumberWord, OrdinalStyle};
+use super::Normalizer;
+
+/// Single number words below this stay spelled out ("two cats").
+const MIN_WRITTEN_VALUE: u64 = 10;
+
+/// Numbers from this value up have their thousands grouped.
+const MIN_GROUPED_VALUE: u64 = 10_000;
+
+/// A parsed spoken number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Number {
+    value: u64,
+    /// Words used.
+    len: usize,
+    ordinal: bool,
+    /// Written with digits already ("5").
+    digits: bool,
+}
+
+/// Where in a number the last word was.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Last {
+    Start,
+    Unit,
+    Teen,
+    Tens,
+    Hundred,
+    Scale,
+}
+
+/// Normalizer driven by a language's lexicon.
+#[derive(Debug, Clone)]
+pub struct LexiconNormalizer {
+    lexicon: Lexicon,
+}
+
+impl LexiconNormalizer {
+    pub fn new(lexicon: Lexicon) -> Self {
+        Self { lexicon }
+    }
+
+    /// Parse the expression starting at the first word.
+    fn parse(&self, words: &[&str]) -> Option<(String, usize)> {
+        if let Some(date) = self.date(words) {
+            return Some(date);
+        }
+        if self.lexicon.paired_years {
+            if let Some((year, len)) = self.paired_year(words) {
+                // "twenty twenty" is a year, "twenty twenty pm" is not
+                if !self.is_meridiem(words.get(len)) {
+                    return Some((year.to_string(), len));
+                }
+            }
+        }
+
+        let number = self.cardinal(words)?;
+        let rest = &words[number.len..];
+        if number.ordinal {
+            let written = self.written_ordinal(number.value);
+            return (number.len > 1 || number.value >= MIN_WRITTEN_VALUE)
+                .then_some((written, number.len));
+        }
+
+        if let Some((time, len)) = self.time(number, rest) {
+            return Some((time, number.len + len));
+        }
+
+        // Digits are kept as dictated
+        let mut amount = match number.digits {
+            true => words[0].to_string(),
+            false => self.written_integer(number.value),
+        };
+
+        // Decimal part
+        let mut len = number.len;
+        let mut fraction = None;
+        if let Some(word) = words.get(len) {
+            if has(&self.lexicon.decimal_words, word) {
+                if let Some((digits, fraction_len)) = self.fraction(&words[len + 1..]) {
+                    fraction = Some(digits);
+                    len += 1 + fraction_len;
+                }
+            }
+        }
+
+        if let Some((written, currency_len)) =
+            self.currency(number.value, fraction.as_deref(), &words[len..])
+        {
+            return Some((written, len + currency_len));
+        }
+
+        if let Some(fraction) = &fraction {
+            amount = format!("{}{}{}", amount, self.lexicon.decimal_separator, fraction);
+        }
+        if let Some(word) = words.get(len) {
+            if let Some(unit) = self.lexicon.units.iter().find(|u| has(&u.words, word)) {
+                return Some((unit.template.replace("{}", &amount), len + 1));
+            }
+        }
+
+        let spelled_small = len == 1 && !number.digits && number.value < MIN_WRITTEN_VALUE;
+        (!spelled_small).then_some((amount, len))
+    }
+
+    /// Parse a cardinal or ordinal number.
+    fn cardinal(&self, words: &[&str]) -> Option<Number> {
+        let first = *words.first()?;
+        if first.bytes().all(|b| b.is_ascii_digit()) {
+            return Some(Number {
+                value: first.parse().ok()?,
+                len: 1,
+                ordinal: false,
+                digits: true,
+            });
+        }
+
+        let (mut total, mut current) = (0u64, 0u64);
+        let mut last = Last::Start;
+        let mut last_scale = u64::MAX;
+        let mut ordinal = false;
+        let mut len = 0;
+        let mut i = 0;
+
+        while let Some(&word) = words.get(i) {
+            let next_is_number = words
+                .get(i + 1)
+                .is_some_and(|next| self.lexicon.cardinal(next).is_some());
+            if matches!(last, Last::Hundred | Last::Scale)
+                && has(&self.lexicon.conjunctions, word)
+                && next_is_number
+            {
+                i += 1;
+                continue;
+            }
+            if last == Last::Start && has(&self.lexicon.one_words, word) {
+                let next = words
+                    .get(i + 1)
+                    .and_then(|next| self.lexicon.cardinal(next));
+                if matches!(next, Some(NumberWord::Hundred | NumberWord::Scale(_))) {
+                    current = 1;
+                    last = Last::Unit;
+                    i += 1;
+                    continue;
+                }
+                break;
+            }
+
+            // After an ordinal only ordinals may follow ("dvacátý první")
+            let (number_word, is_ordinal) = match self.lexicon.ordinal(word) {
+                Some(number_word) if ordinal || self.lexicon.cardinal(word).is_none() => {
+                    (number_word, true)
+                }
+                _ if ordinal => break,
+                _ => match self.lexicon.cardinal(word) {
+                    Some(number_word) => (number_word, false),
+                    None => break,
+                },
+            };
+
+            let after_group = matches!(last, Last::Start | Last::Hundred | Last::Scale);
+            match number_word {
+                NumberWord::Unit(value) if after_group || (last == Last::Tens && value > 0) => {
+                    current += value;
+                    last = Last::Unit;
+                }
+                NumberWord::Teen(value) if after_group => {
+                    current += value;
+                    last = Last::Teen;
+                }
+                NumberWord::Tens(value) if after_group => {
+                    current += value;
+                    last = Last::Tens;
+                }
+                NumberWord::Hundred
+                    if last == Last::Start
+                        || (matches!(last, Last::Unit | Last::Teen) && current < 100) =>
+                {
+                    current = current.max(1) * 100;
+                    last = Last::Hundred;
+                }
+                NumberWord::Scale(scale) if scale < last_scale && last != Last::Scale => {
+                    total += current.max(1) * scale;
+                    current = 0;
+                    last_scale = scale;
+                    last = Last::Scale;
+                }
+                _ => break,
+            }
+            ordinal |= is_ordinal;
+            i += 1;
+            len = i;
+        }
+
+        (len > 0).then_some(Number {
+            value: total + current,
+            len,
+            ordinal,
+            digits: false,
+        })
+    }
+
+    /// Parse the digits after a decimal word: one digit per word
+    /// ("point one four"), or a number ("celá čtrnáct").
+    fn fraction(&self, words: &[&str]) -> Option<(String, usize)> {
+        let digits: String = words
+            .iter()
+            .map_while(|word| self.lexicon.digit(word))
+            .map(|d| d.to_string())
+            .collect();
+        if !digits.is_empty() {
+            let len = digits.len();
+            return Some((digits, len));
+        }
+        let number = self.cardinal(words).filter(|n| !n.ordinal)?;
+        Some((number.value.to_string(), number.len))
+    }
+
+    /// Parse a currency after an amount, with an optional minor amount
+    /// ("dollars fifty", "dollars and five cents"), or a minor amount alone
+    /// ("fifty cents").
+    fn currency(
+        &self,
+        value: u64,
+        fraction: Option<&str>,
+        words: &[&str],
+    ) -> Option<(String, usize)> {
+        let word = *words.first()?;
+        let sep = &self.lexicon.decimal_separator;
+
+        if let Some(currency) = self
+            .lexicon
+            .currencies
+            .iter()
+            .find(|c| has(&c.minor_words, word))
+        {
+            if fraction.is_some() || value >= 100 {
+                return None;
+            }
+            let amount = format!("0{}{:02}", sep, value);
+            return Some((currency.template.replace("{}", &amount), 1));
+        }
+
+        let currency = self
+            .lexicon
+            .currencies
+            .iter()
+            .find(|c| has(&c.words, word))?;
+        let mut amount = self.written_integer(value);
+        let mut len = 1;
+        if let Some(fraction) = fraction {
+            amount = format!("{}{}{}", amount, sep, fraction);
+        } else {
+            let conjunction = words
+                .get(1)
+                .is_some_and(|w| has(&self.lexicon.conjunctions, w));
+            let start = 1 + usize::from(conjunction);
+            let minor = words
+                .get(start..)
+                .and_then(|rest| self.cardinal(rest))
+                .filter(|n| !n.ordinal && n.value < 100);
+            if let Some(minor) = minor {
+                let minor_word = words
+                    .get(start + minor.len)
+                    .is_some_and(|w| has(&currency.minor_words, w));
+                // "dollars fifty" and "dollars and five cents", but not
+                // "dollars two apples"
+                if minor_word || (!conjunction && minor.value >= 10) {
+                    amount = format!("{}{}{:02}", amount, sep, minor.value);
+                    len = start + minor.len + usize::from(minor_word);
+                }
+            }
+        }
+        Some((currency.template.replace("{}", &amount), len))
+    }
+
+    /// Parse a time after an hour: "five o'clock", "five thirty pm",
+    /// "pět hodin třicet".
+    fn time(&self, hour: Number, words: &[&str]) -> Option<(String, usize)> {
+        let word = *words.first()?;
+        if hour.value > 24 || hour.len > 2 {
+            return None;
+        }
+        if has(&self.lexicon.oclock_words, word) {
+            return Some((format!("{}:00", hour.value), 1));
+        }
+        if has(&self.lexicon.hour_words, word) {
+            let Some((minutes, len)) = self.minutes(&words[1..]) else {
+                return Some((format!("{}:00", hour.value), 1));
+            };
+            let minute_word = words
+                .get(1 + len)
+                .is_some_and(|w| has(&self.lexicon.minute_words, w));
+            return Some((
+                format!("{}:{:02}", hour.value, minutes),
+                1 + len + usize::from(minute_word),
+            ));
+        }
+
+        if !(1..=12).contains(&hour.value) {
+            return None;
+        }
+        if let Some(meridiem) = self.meridiem(word) {
+            return Some((format!("{} {}", hour.value, meridiem), 1));
+        }
+        let (minutes, len) = self.minutes(words)?;
+        let meridiem = self.meridiem(words.get(len)?)?;
+        Some((
+            format!("{}:{:02} {}", hour.value, minutes, meridiem),
+            len + 1,
+        ))
+    }
+
+    /// Parse minutes: "thirty", "oh five".
+    fn minutes(&self, words: &[&str]) -> Option<(u64, usize)> {
+        if let [zero, digit, ..] = words {
+            if has(&self.lexicon.zero_digits, zero) {
+                return Some((self.lexicon.digit(digit)?, 2));
+            }
+        }
+        let number = self.cardinal(words)?;
+        (!number.ordinal && number.value < 60).then_some((number.value, number.len))
+    }
+
+    fn meridiem(&self, word: &str) -> Option<&str> {
+        self.lexicon
+            .meridiems
+            .iter()
+            .find(|(spoken, _)| spoken == word)
+            .map(|(_, written)| written.as_str())
+    }
+
+    fn is_meridiem(&self, word: Option<&&str>) -> bool {
+        word.is_some_and(|w| self.meridiem(w).is_some())
+    }
+
+    /// Parse a date: "march fifth [twenty twenty six]", "the fifth of march",
+    /// "pátého března [dva tisíce dvacet šest]".
+    fn date(&self, words: &[&str]) -> Option<(String, usize)> {
+        let first = *words.first()?;
+
+        // Month first: the day must be an ordinal or followed by a year
+        if let Some(month) = self.lexicon.month(first) {
+            let day = self
+                .cardinal(&words[1..])
+                .filter(|d| (1..=31).contains(&d.value))?;
+            let year = self.year(&words[1 + day.len..]);
+            if !day.ordinal && year.is_none() {
+                return None;
+            }
+            return Some(self.written_date(first, month, day, year, 1));
+        }
+
+        let article = usize::from(has(&self.lexicon.date_articles, first));
+        let day = self
+            .cardinal(&words[article..])
+            .filter(|d| d.ordinal && (1..=31).contains(&d.value))?;
+        let mut len = article + day.len;
+        if words
+            .get(len)
+            .is_some_and(|w| has(&self.lexicon.of_words, w))
+        {
+            len += 1;
+        }
+        let month_word = *words.get(len)?;
+        let month = self.lexicon.month(month_word)?;
+        let year = self.year(&words[len + 1..]);
+        Some(self.written_date(month_word, month, day, year, len + 1 - day.len))
+    }
+
+    /// Parse a year: paired ("twenty twenty six") or a full number.
+    fn year(&self, words: &[&str]) -> Option<(u64, usize)> {
+        if self.lexicon.paired_years {
+            if let Some(year) = self.paired_year(words) {
+                return Some(year);
+            }
+        }
+        let number = self.cardinal(words)?;
+        (!number.ordinal && (1000..3000).contains(&number.value))
+            .then_some((number.value, number.len))
+    }
+
+    /// Parse a year spoken as two pairs of digits: "nineteen eighty four",
+    /// "twenty twenty six", "nineteen oh five", "nineteen hundred".
+    fn paired_year(&self, words: &[&str]) -> Option<(u64, usize)> {
+        let (century, len) = self.two_digits(words)?;
+        if !(17..=20).contains(&century) {
+            return None;
+        }
+        let rest = &words[len..];
+        let first = *rest.first()?;
+        if matches!(self.lexicon.cardinal(first), Some(NumberWord::Hundred)) {
+            return Some((century * 100, len + 1));
+        }
+        if has(&self.lexicon.zero_digits, first) {
+            let digit = self.lexicon.digit(rest.get(1)?).filter(|d| *d > 0)?;
+            return Some((century * 100 + digit, len + 2));
+        }
+        let (year, year_len) = self.two_digits(rest)?;
+        Some((century * 100 + year, len + year_len))
+    }
+
+    /// Parse 10-99 spoken as one number ("nineteen", "twenty six").
+    fn two_digits(&self, words: &[&str]) -> Option<(u64, usize)> {
+        match self.lexicon.cardinal(words.first()?)? {
+            NumberWord::Teen(value) => Some((value, 1)),
+            NumberWord::Tens(tens) => match words.get(1).and_then(|w| self.lexicon.cardinal(w)) {
+                Some(NumberWord::Unit(unit)) if unit > 0 => Some((tens + unit, 2)),
+                _ => Some((tens, 1)),
+            },
+            _ => None,
+        }
+    }
+
+    fn written_date(
+        &self,
+        month_word: &str,
+        month: usize,
+        day: Number,
+        year: Option<(u64, usize)>,
+        len: usize,
+    ) -> (String, usize) {
+        let month_name = if self.lexicon.capitalize_months {
+            let canonical = &self.lexicon.months[month - 1][0];
+            super::capitalize(canonical)
+        } else {
+            month_word.to_string()
+        };
+        let template = match year {
+            Some(_) => &self.lexicon.date_year_template,
+            None => &self.lexicon.date_template,
+        };
+        let written = template
+            .replace("{day}", &day.value.to_string())
+            .replace("{month}", &month_name)
+            .replace("{year}", &year.map_or(0, |(y, _)| y).to_string());
+        (written, len + day.len + year.map_or(0, |(_, l)| l))
+    }
+
+    fn written_ordinal(&self, value: u64) -> String {
+        match self.lexicon.ordinal_style {
+            OrdinalStyle::Period => format!("{}.", value),
+            OrdinalStyle::English => {
+                let suffix = match (value % 10, value % 100) {
+                    (_, 11..=13) => "th",
+                    (1, _) => "st",
+                    (2, _) => "nd",
+                    (3, _) => "rd",
+                    _ => "th",
+                };
+                format!("{}{}", value, suffix)
+            }
+        }
+    }
+
+    /// Write an integer, grouping thousands in large numbers.
+    fn written_integer(&self, value: u64) -> String {
+        let digits = value.to_string();
+        if value < MIN_GROUPED_VALUE {
+            return digits;
+        }
+        let mut out = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i).is_multiple_of(3) {
+                out.push_str(&self.lexicon.group_separator);
+            }
+            out.push(digit);
+        }
+        out
+    }
+}
+
+impl Normalizer for LexiconNormalizer {
+    fn language(&self) -> &str {
+        &self.lexicon.language
+    }
+
+    fn is_spoken_form_word(&self, word: &str) -> bool {
+        self.lexicon.contains(&super::word_key(word))
+    }
+
+    fn starts_spoken_form(&self, word: &str) -> bool {
+        self.lexicon.starts(&super::word_key(word))
+    }
+
+    fn normalize(&self, text: &str) -> String {
+        super::rewrite(text, &|words| self.parse(words))
+    }
+}
@@ -0,0 +1,266 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Words and written forms of one language.
+
+/// A currency: spoken names and how amounts are written.
+#[derive(Debug, Clone)]
+pub struct Currency {
+    /// Spoken names of the main unit ("dollars", "korun").
+    pub words: Vec<String>,
+    /// Spoken names of the 1/100 unit ("cents", "haléřů").
+    pub minor_words: Vec<String>,
+    /// Written form, `{}` is replaced by the amount ("${}", "{} Kč").
+    pub template: String,
+}
+
+/// A unit written after a number, e.g. percent.
+#[derive(Debug, Clone)]
+pub struct Unit {
+    /// Spoken names ("percent", "procent").
+    pub words: Vec<String>,
+    /// Written form, `{}` is replaced by the number ("{}%", "{} km").
+    pub template: String,
+}
+
+/// How ordinal numbers are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrdinalStyle {
+    /// 1st, 2nd, 3rd, 4th.
+    English,
+    /// 1., 2., 3.
+    Period,
+}
+
+/// Vocabulary and writing conventions for one language.
+#[derive(Debug, Clone)]
+pub struct Lexicon {
+    /// Language code, e.g. "en".
+    pub language: String,
+    /// Number words from 0 to 90 with their values ("twenty" = 20),
+    /// including gender forms ("jeden", "jedna").
+    pub cardinals: Vec<(String, u64)>,
+    /// Words multiplying by 100 ("hundred"; "sto", "stě", "sta", "set").
+    pub hundreds: Vec<String>,
+    /// Words multiplying by larger powers of ten ("thousand" = 1000).
+    pub scales: Vec<(String, u64)>,
+    /// Ordinal words with their values ("first" = 1). With
+    /// `ordinal_endings`, these are stems ("pát" + "ého").
+    pub ordinals: Vec<(String, u64)>,
+    /// Inflection endings added to ordinal stems.
+    pub ordinal_endings: Vec<String>,
+    /// Joins units and tens in one word ("dva" + "a" + "dvacet").
+    pub compound_infix: Option<String>,
+    /// Words allowed between parts of a number ("one hundred and five").
+    pub conjunctions: Vec<String>,
+    /// Words meaning one before "hundred" or a scale ("a hundred").
+    pub one_words: Vec<String>,
+    /// Words for a zero digit inside times and years ("oh").
+    pub zero_digits: Vec<String>,
+    /// Decimal point words ("point"; "celá", "celé", "celých").
+    pub decimal_words: Vec<String>,
+    pub decimal_separator: String,
+    /// Separator between groups of thousands, used from 10 000 up.
+    pub group_separator: String,
+    pub ordinal_style: OrdinalStyle,
+    pub currencies: Vec<Currency>,
+    pub units: Vec<Unit>,
+    /// Words after an hour for a full hour ("o'clock").
+    pub oclock_words: Vec<String>,
+    /// Words after an hour, optionally followed by minutes ("hodin").
+    pub hour_words: Vec<String>,
+    /// Words after the minutes ("minut").
+    pub minute_words: Vec<String>,
+    /// Spoken and written forms of AM and PM.
+    pub meridiems: Vec<(String, String)>,
+    /// Spoken forms of each month, January first.
+    pub months: Vec<Vec<String>>,
+    /// Capitalize month names in dates ("March").
+    pub capitalize_months: bool,
+    /// Words between a day and its month ("fifth of march").
+    pub of_words: Vec<String>,
+    /// Words before a day that belong to the date ("the fifth").
+    pub date_articles: Vec<String>,
+    /// Written date with and without a year, `{day}`, `{month}` and
+    /// `{year}` are replaced.
+    pub date_template: String,
+    pub date_year_template: String,
+    /// Years spoken in pairs ("nineteen eighty four").
+    pub paired_years: bool,
+}
+
+/// A number word by its role.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum NumberWord {
+    /// 0 to 9.
+    Unit(u64),
+    /// 10 to 19, or tens and units in one word.
+    Teen(u64),
+    /// 20, 30, ... 90.
+    Tens(u64),
+    Hundred,
+    Scale(u64),
+}
+
+impl NumberWord {
+    fn from_value(value: u64) -> Self {
+        match value {
+            0..=9 => Self::Unit(value),
+            10..=19 => Self::Teen(value),
+            _ if value.is_multiple_of(10) => Self::Tens(value),
+            _ => Self::Teen(value),
+        }
+    }
+}
+
+/// Whether a list contains a word.
+pub(super) fn has(list: &[String], word: &str) -> bool {
+    list.iter().any(|w| w == word)
+}
+
+impl Lexicon {
+    /// Look up a cardinal number word, including compounds such as
+    /// "twenty-three" and "dvaadvacet".
+    pub(super) fn cardinal(&self, word: &str) -> Option<NumberWord> {
+        if let Some(value) = self.value(&self.cardinals, word) {
+            return Some(NumberWord::from_value(value));
+        }
+        if has(&self.hundreds, word) {
+            return Some(NumberWord::Hundred);
+        }
+        if let Some(scale) = self.value(&self.scales, word) {
+            return Some(NumberWord::Scale(scale));
+        }
+        self.compound(word, |part| self.value(&self.cardinals, part))
+    }
+
+    /// Look up an ordinal number word ("fifth" → 5, "twenty-first" → 21).
+    pub(super) fn ordinal(&self, word: &str) -> Option<NumberWord> {
+        let value = self.ordinal_value(word).or_else(|| {
+            // Only the last part of a compound is an ordinal
+            let (tens, unit) = word.split_once('-')?;
+            match self.cardinal(tens)? {
+                NumberWord::Tens(tens) => {
+                    Some(tens + self.ordinal_value(unit).filter(|u| *u < 10)?)
+                }
+                _ => None,
+            }
+        })?;
+        Some(match value {
+            100 => NumberWord::Hundred,
+            1000.. => NumberWord::Scale(value),
+            _ => NumberWord::from_value(value),
+        })
+    }
+
+    /// The digit value of a word inside times, years and decimals.
+    pub(super) fn digit(&self, word: &str) -> Option<u64> {
+        if has(&self.zero_digits, word) {
+            return Some(0);
+        }
+        match self.cardinal(word)? {
+            NumberWord::Unit(digit) => Some(digit),
+            _ => None,
+        }
+    }
+
+    /// The month (1-12) named by a word.
+    pub(super) fn month(&self, word: &str) -> Option<usize> {
+        self.months
+            .iter()
+            .position(|forms| has(forms, word))
+            .map(|i| i + 1)
+    }
+
+    /// Whether a word can be part of a number, amount, time or date.
+    pub fn contains(&self, word: &str) -> bool {
+        if word.is_empty() {
+            return false;
+        }
+        word.bytes().all(|b| b.is_ascii_digit())
+            || self.cardinal(word).is_some()
+            || self.ordinal(word).is_some()
+            || self.month(word).is_some()
+            || [
+                &self.conjunctions,
+                &self.one_words,
+                &self.zero_digits,
+                &self.decimal_words,
+                &self.oclock_words,
+                &self.hour_words,
+                &self.minute_words,
+                &self.of_words,
+                &self.date_articles,
+            ]
+            .iter()
+            .any(|list| has(list, word))
+            || self.meridiems.iter().any(|(spoken, _)| spoken == word)
+            || self
+                .currencies
+                .iter()
+                .any(|c| has(&c.words, word) || has(&c.minor_words, word))
+            || self.units.iter().any(|u| has(&u.words, word))
+    }
+
+    /// Whether a word can start a number, time or date: a digit string, a
+    /// number word or a month.
+    pub fn starts(&self, word: &str) -> bool {
+        !word.is_empty()
+            && (word.bytes().all(|b| b.is_ascii_digit())
+                || self.cardinal(word).is_some()
+                || self.ordinal(word).is_some()
+                || self.month(word).is_some())
+    }
+
+    fn value(&self, list: &[(String, u64)], word: &str) -> Option<u64> {
+        list.iter().find(|(w, _)| w == word).map(|(_, v)| *v)
+    }
+
+    fn ordinal_value(&self, word: &str) -> Option<u64> {
+        if self.ordinal_endings.is_empty() {
+            return self.value(&self.ordinals, word);
+        }
+        self.ordinal_endings.iter().find_map(|ending| {
+            word.strip_suffix(ending.as_str())
+                .and_then(|stem| self.value(&self.ordinals, stem))
+        })
+    }
+
+    /// Tens and a unit in one word: "twenty-three", or with the compound
+    /// infix, unit first ("tři" + "a" + "dvacet").
+    fn compound(&self, word: &str, lookup: impl Fn(&str) -> Option<u64>) -> Option<NumberWord> {
+        let (tens, unit) = match word.split_once('-') {
+            Some((tens, unit)) => (lookup(tens)?, lookup(unit)?),
+            None => {
+                let infix = self.compound_infix.as_deref()?;
+                self.cardinals.iter().find_map(|(tens_word, tens)| {
+                    let unit = word.strip_suffix(tens_word.as_str())?.strip_suffix(infix)?;
+                    Some((*tens, lookup(unit)?))
+                })?
+            }
+        };
+        (tens >= 20 && tens % 10 == 0 && (1..10).contains(&unit))
+            .then_some(NumberWord::Teen(tens + unit))
+    }
+}
+
+/// Owned copies of a word list.
+pub(super) fn strings(words: &[&str]) -> Vec<String> {
+    words.iter().map(|w| w.to_string()).collect()
+}
+
+/// Owned copies of a list of words with values.
+pub(super) fn valued(words: &[(&str, u64)]) -> Vec<(String, u64)> {
+    words.iter().map(|(w, v)| (w.to_string(), *v)).collect()
+}
@@ -0,0 +1,311 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Inverse text normalization.
+//!
+//! Rewrites spoken numbers, ordinals, amounts, times and dates in dictated
+//! text into written form ("twenty three dollars fifty" → "$23.50").
+//! Each language implements `Normalizer`; English and Czech are built on
+//! a shared lexicon-driven grammar.
+
+mod czech;
+mod english;
+mod grammar;
+mod lexicon;
+
+use std::time::{Duration, Instant};
+
+use grammar::LexiconNormalizer;
+
+/// How long streamed words are held while the next word may continue a
+/// spoken number.
+pub const HOLD_TIMEOUT: Duration = Duration::from_millis(800);
+
+/// Rewrites spoken forms in dictated text for one language.
+pub trait Normalizer: Send + Sync {
+    /// Language code, e.g. "en".
+    fn language(&self) -> &str;
+
+    /// Whether a word can be part of a spoken number, amount, time or date
+    /// (streamed words are held while it may continue).
+    fn is_spoken_form_word(&self, word: &str) -> bool;
+
+    /// Whether a word can start a spoken number, time or date. Words such
+    /// as "a", "the", "and" and "of" are only held after one has started.
+    fn starts_spoken_form(&self, word: &str) -> bool;
+
+    /// Rewrite spoken forms in the text, keeping everything else as-is.
+    fn normalize(&self, text: &str) -> String;
+}
+
+/// Create the normalizer for a language code ("en", "cs").
+pub fn normalizer_for(language: &str) -> Option<Box<dyn Normalizer>> {
+    let lexicon = match language.to_lowercase().as_str() {
+        "en" => english::lexicon(),
+        "cs" => czech::lexicon(),
+        _ => return None,
+    };
+    Some(Box::new(LexiconNormalizer::new(lexicon)))
+}
+
+/// Streamed words held while they may be part of a spoken number.
+#[derive(Debug, Default)]
+pub struct SpokenFormHold {
+    text: String,
+    /// Message timestamp of the last held word.
+    msg_ts: u64,
+    last_word_at: Option<Instant>,
+}
+
+impl SpokenFormHold {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a streamed word from the message with timestamp `msg_ts`.
+    /// Returns the held text, ending with this word, once the word can't
+    /// continue a spoken number.
+    pub fn push(
+        &mut self,
+        word: &str,
+        msg_ts: u64,
+        normalizer: &dyn Normalizer,
+    ) -> Option<(u64, String)> {
+        let spoken_form = if self.text.is_empty() {
+            normalizer.starts_spoken_form(word)
+        } else {
+            normalizer.is_spoken_form_word(word)
+        };
+        self.text.push_str(word);
+        self.msg_ts = msg_ts;
+        let ends_with_punctuation = word.trim_end().ends_with(|c: char| !c.is_alphanumeric());
+        if spoken_form && !ends_with_punctuation {
+            self.last_word_at = Some(Instant::now());
+            return None;
+        }
+        self.take()
+    }
+
+    /// Release the held text (e.g., before a command), with the message
+    /// timestamp of its last word.
+    pub fn take(&mut self) -> Option<(u64, String)> {
+        self.last_word_at = None;
+        (!self.text.is_empty()).then(|| (self.msg_ts, std::mem::take(&mut self.text)))
+    }
+
+    /// Release the held text if no word continued it in time.
+    pub fn take_expired(&mut self) -> Option<(u64, String)> {
+        match self.last_word_at {
+            Some(at) if at.elapsed() >= HOLD_TIMEOUT => self.take(),
+            _ => None,
+        }
+    }
+
+    /// Drop the held text (e.g., on a new connection).
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.last_word_at = None;
+    }
+}
+
+/// A written form and the number of words it replaces.
+type Rewrite = (String, usize);
+
+/// A word with its surrounding punctuation split off.
+struct Token {
+    start: usize,
+    end: usize,
+    /// Punctuation before the word, e.g. an opening quote.
+    prefix: String,
+    /// Lowercased word without surrounding punctuation.
+    key: String,
+    /// Punctuation after the word, e.g. a comma.
+    suffix: String,
+}
+
+/// The lookup form of a word: lowercased, surrounding punctuation removed.
+fn word_key(word: &str) -> String {
+    word.trim_matches(|c: char| !c.is_alphanumeric())
+        .to_lowercase()
+}
+
+/// Uppercase the first letter.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some(i),
+            (Some(s), true) => {
+                let word = &text[s..i];
+                let inner = word.trim_matches(|c: char| !c.is_alphanumeric());
+                let prefix_len = word.find(inner).unwrap_or(0);
+                tokens.push(Token {
+                    start: s,
+                    end: i,
+                    prefix: word[..prefix_len].to_string(),
+                    key: inner.to_lowercase(),
+                    suffix: word[prefix_len + inner.len()..].to_string(),
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// Replace the expressions found by `parse` in the text. `parse` gets the
+/// word keys from a position up to the next punctuation, and returns the
+/// written form with the number of words it replaces.
+fn rewrite(text: &str, parse: &dyn Fn(&[&str]) -> Option<Rewrite>) -> String {
+    let tokens = tokenize(text);
+    let mut out = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut i = 0;
+
+    while i < tokens.len() {
+        // An expression can't span punctuation
+        let mut end = i + 1;
+        while end < tokens.len()
+            && tokens[end - 1].suffix.is_empty()
+            && tokens[end].prefix.is_empty()
+        {
+            end += 1;
+        }
+        let keys: Vec<&str> = tokens[i..end].iter().map(|t| t.key.as_str()).collect();
+
+        match parse(&keys) {
+            Some((written, len)) if len > 0 => {
+                let last = &tokens[i + len - 1];
+                out.push_str(&text[copied..tokens[i].start]);
+                out.push_str(&tokens[i].prefix);
+                out.push_str(&written);
+                out.push_str(&last.suffix);
+                copied = last.end;
+                i += len;
+            }
+            _ => i += 1,
+        }
+    }
+    out.push_str(&text[copied..]);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_english() {
+        let en = normalizer_for("en").unwrap();
+
+        let cases = [
+            ("twenty three dollars fifty", "$23.50"),
+            ("it costs five dollars and ten cents.", "it costs $5.10."),
+            ("fifty cents", "$0.50"),
+            ("march fifth twenty twenty six", "March 5, 2026"),
+            ("on the twenty first of june", "on June 21"),
+            ("born in nineteen eighty four", "born in 1984"),
+            ("two thousand and twenty six", "2026"),
+            ("one hundred and five people", "105 people"),
+            ("a hundred twenty thousand", "120,000"),
+            ("three point one four", "3.14"),
+            ("meet at five thirty pm", "meet at 5:30 PM"),
+            ("at seven o'clock, ok", "at 7:00, ok"),
+            ("fifty percent", "50%"),
+            ("the twenty second time", "the 22nd time"),
+            ("twenty-three", "23"),
+            ("I have two cats and one dog", "I have two cats and one dog"),
+            (
+                "the first time, a second chance",
+                "the first time, a second chance",
+            ),
+            ("March forward", "March forward"),
+            ("\"twelve\" apples", "\"12\" apples"),
+        ];
+        for (spoken, written) in cases {
+            assert_eq!(en.normalize(spoken), written, "normalizing {:?}", spoken);
+        }
+    }
+
+    #[test]
+    fn test_czech() {
+        let cs = normalizer_for("cs").unwrap();
+
+        let cases = [
+            ("dvacet tři korun padesát", "23,50 Kč"),
+            ("pátého března dva tisíce dvacet šest", "5. března 2026"),
+            ("dvacátého prvního května", "21. května"),
+            ("dvaadvacet", "22"),
+            ("tři sta padesát tisíc", "350 000"),
+            ("tři celá čtrnáct", "3,14"),
+            ("v pět hodin třicet minut", "v 5:30"),
+            ("padesát procent", "50 %"),
+            ("mám dva psy", "mám dva psy"),
+            ("první den", "první den"),
+        ];
+        for (spoken, written) in cases {
+            assert_eq!(cs.normalize(spoken), written, "normalizing {:?}", spoken);
+        }
+    }
+
+    #[test]
+    fn test_hold_streamed_words() {
+        let en = normalizer_for("en").unwrap();
+        let mut hold = SpokenFormHold::new();
+
+        assert_eq!(
+            hold.push("hello ", 1, en.as_ref()),
+            Some((1, "hello ".to_string()))
+        );
+        assert_eq!(hold.push("twenty ", 2, en.as_ref()), None);
+        assert_eq!(hold.push("three ", 3, en.as_ref()), None);
+        assert_eq!(hold.push("dollars ", 4, en.as_ref()), None);
+        assert_eq!(
+            hold.push("today ", 5, en.as_ref()),
+            Some((5, "twenty three dollars today ".to_string()))
+        );
+
+        assert_eq!(
+            hold.push("five. ", 6, en.as_ref()),
+            Some((6, "five. ".to_string()))
+        );
+        assert_eq!(hold.push("ten ", 7, en.as_ref()), None);
+        assert_eq!(hold.take_expired(), None);
+        assert_eq!(hold.take(), Some((7, "ten ".to_string())));
+        assert_eq!(hold.take(), None);
+
+        // Common words are only held inside a number
+        assert_eq!(
+            hold.push("the ", 8, en.as_ref()),
+            Some((8, "the ".to_string()))
+        );
+        assert_eq!(hold.push("one ", 9, en.as_ref()), None);
+        assert_eq!(hold.push("hundred ", 10, en.as_ref()), None);
+        assert_eq!(hold.push("and ", 11, en.as_ref()), None);
+        assert_eq!(
+            hold.push("cats ", 12, en.as_ref()),
+            Some((12, "one hundred and cats ".to_string()))
+        );
+    }
+}
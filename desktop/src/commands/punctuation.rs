@@ -0,0 +1,180 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spoken punctuation spacing.
+//!
+//! Words arrive with a trailing space ("word "), so typing punctuation
+//! directly would give "word , next". `SmartSpacing` holds back trailing
+//! whitespace until it knows what comes next, and joins punctuation marks
+//! to the words around them.
+
+use serde::{Deserialize, Serialize};
+
+/// How a punctuation mark joins the surrounding text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Attach {
+    /// Joins the previous word, space after: `,` `.` `?` closing quote.
+    #[default]
+    Left,
+    /// Space before, joins the next word: opening quote or bracket.
+    Right,
+    /// No space on either side: line breaks, `-`.
+    Both,
+    /// Spaced like a word: `&`.
+    Neither,
+}
+
+/// How the next piece of text joins what was typed last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Join {
+    /// Use the whitespace that came with the text.
+    AsIs,
+    /// Separate with a space (after `,` and the like).
+    Space,
+    /// No space (line start, after an opening quote).
+    Glue,
+}
+
+/// Spacing state between typed text and punctuation marks.
+#[derive(Debug, Clone)]
+pub struct SmartSpacing {
+    /// Trailing whitespace not typed yet.
+    held: String,
+    join: Join,
+}
+
+impl Default for SmartSpacing {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SmartSpacing {
+    /// Create spacing state for a fresh line.
+    pub fn new() -> Self {
+        Self {
+            held: String::new(),
+            join: Join::Glue,
+        }
+    }
+
+    /// Forget held whitespace (e.g., on a new connection).
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Space dictated text. Returns what to type now; trailing whitespace is
+    /// held back until the next text or mark.
+    pub fn text(&mut self, text: &str) -> String {
+        let trimmed = text.trim_end();
+        let trailing = &text[trimmed.len()..];
+        let body = trimmed.trim_start();
+        let leading = &trimmed[..trimmed.len() - body.len()];
+
+        if body.is_empty() {
+            self.held.push_str(text);
+            return String::new();
+        }
+
+        let gap = std::mem::take(&mut self.held) + leading;
+        let mut out = match self.join {
+            Join::Glue => String::new(),
+            Join::Space if gap.is_empty() => " ".to_string(),
+            _ => gap,
+        };
+        out.push_str(body);
+
+        self.held = trailing.to_string();
+        self.join = Join::AsIs;
+        out
+    }
+
+    /// Space a punctuation mark. Returns what to type now.
+    pub fn punctuation(&mut self, mark: &str, attach: Attach) -> String {
+        let gap = std::mem::take(&mut self.held);
+        let mut out = match attach {
+            Attach::Left | Attach::Both => String::new(),
+            Attach::Right | Attach::Neither => match self.join {
+                Join::Glue => String::new(),
+                _ if gap.is_empty() => " ".to_string(),
+                _ => gap,
+            },
+        };
+        out.push_str(mark);
+
+        self.join = match attach {
+            Attach::Left | Attach::Neither => Join::Space,
+            Attach::Right | Attach::Both => Join::Glue,
+        };
+        out
+    }
+
+    /// Release held whitespace before something that isn't text (e.g., a
+    /// key command), so it lands where it would have without punctuation.
+    pub fn flush(&mut self) -> String {
+        let held = std::mem::take(&mut self.held);
+        let out = match self.join {
+            Join::Space if held.is_empty() => " ".to_string(),
+            Join::Glue => String::new(),
+            _ => held,
+        };
+        self.join = Join::AsIs;
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words_and_punctuation() {
+        let mut spacing = SmartSpacing::new();
+        let mut typed = String::new();
+
+        typed += &spacing.text("hello ");
+        typed += &spacing.punctuation(",", Attach::Left);
+        typed += &spacing.text("world ");
+        typed += &spacing.punctuation("?", Attach::Left);
+        typed += &spacing.punctuation("\"", Attach::Right);
+        typed += &spacing.text("quoted ");
+        typed += &spacing.text("text ");
+        typed += &spacing.punctuation("\"", Attach::Left);
+        typed += &spacing.punctuation("\n\n", Attach::Both);
+        typed += &spacing.text("next ");
+        typed += &spacing.punctuation("&", Attach::Neither);
+        typed += &spacing.text("more ");
+
+        assert_eq!(typed, "hello, world? \"quoted text\"\n\nnext & more");
+    }
+
+    #[test]
+    fn test_text_segments_and_flush() {
+        let mut spacing = SmartSpacing::new();
+
+        // Mid-text segments keep their own spacing
+        assert_eq!(spacing.text("hello "), "hello");
+        assert_eq!(spacing.punctuation(".", Attach::Left), ".");
+        assert_eq!(spacing.text(" next"), " next");
+
+        // Held whitespace is released before commands
+        assert_eq!(spacing.text(" word "), " word");
+        assert_eq!(spacing.flush(), " ");
+        assert_eq!(spacing.text("after"), "after");
+
+        assert_eq!(spacing.punctuation(",", Attach::Left), ",");
+        assert_eq!(spacing.flush(), " ");
+    }
+}
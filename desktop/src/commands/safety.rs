@@ -0,0 +1,224 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Safety policies for destructive commands.
+//!
+//! A misrecognized phrase shouldn't cut a selection or close a window, so
+//! a command can require a spoken confirmation ("cut", then "confirm"),
+//! ignore repeats within a cooldown, or ask on the desktop before running.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::ResolvedCommand;
+
+/// Checks before a command runs, set in its definition.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SafetyPolicy {
+    /// Run only if the confirm command is said within this many seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_secs: Option<u64>,
+    /// Ignore the command if it ran less than this many seconds ago.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown_secs: Option<u64>,
+    /// Ask in a desktop dialog before running.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub confirm_on_desktop: bool,
+}
+
+impl SafetyPolicy {
+    /// Whether the policy has no checks.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Outcome of checking a command against its policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SafetyCheck {
+    /// Run the command now.
+    Run,
+    /// Wait for the confirm command within this time.
+    AwaitConfirmation(Duration),
+    /// The command ran too recently; it may run again after this time.
+    CoolingDown(Duration),
+}
+
+/// A command waiting for a spoken confirmation.
+#[derive(Debug, Clone)]
+struct Pending {
+    cmd: ResolvedCommand,
+    deadline: Instant,
+}
+
+/// Enforces safety policies: the command waiting for confirmation and
+/// when each command last ran.
+#[derive(Debug, Default)]
+pub struct SafetyGuard {
+    pending: Option<Pending>,
+    last_run: HashMap<String, Instant>,
+}
+
+impl SafetyGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check a command about to run. A command needing confirmation
+    /// replaces the one waiting, if any; a command allowed to run starts
+    /// its cooldown.
+    pub fn check(
+        &mut self,
+        cmd: &ResolvedCommand,
+        policy: &SafetyPolicy,
+        now: Instant,
+    ) -> SafetyCheck {
+        if let Some(remaining) = self.cooldown_remaining(&cmd.code, policy, now) {
+            return SafetyCheck::CoolingDown(remaining);
+        }
+        if let Some(secs) = policy.confirm_secs {
+            let window = Duration::from_secs(secs);
+            self.pending = Some(Pending {
+                cmd: cmd.clone(),
+                deadline: now + window,
+            });
+            return SafetyCheck::AwaitConfirmation(window);
+        }
+        self.last_run.insert(cmd.code.clone(), now);
+        SafetyCheck::Run
+    }
+
+    /// Take the command waiting for confirmation, unless its time ran out,
+    /// and start its cooldown.
+    pub fn confirm(&mut self, now: Instant) -> Option<ResolvedCommand> {
+        let pending = self.pending.take().filter(|p| now <= p.deadline)?;
+        self.last_run.insert(pending.cmd.code.clone(), now);
+        Some(pending.cmd)
+    }
+
+    /// Whether a command waits for confirmation and its time hasn't run out.
+    pub fn is_pending(&self, now: Instant) -> bool {
+        self.pending.as_ref().is_some_and(|p| now <= p.deadline)
+    }
+
+    /// Drop the command waiting for confirmation, returning its code.
+    pub fn cancel(&mut self) -> Option<String> {
+        self.pending.take().map(|p| p.cmd.code)
+    }
+
+    /// Forget pending confirmations and cooldowns (e.g. on a new connection).
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn cooldown_remaining(
+        &self,
+        code: &str,
+        policy: &SafetyPolicy,
+        now: Instant,
+    ) -> Option<Duration> {
+        let cooldown = Duration::from_secs(policy.cooldown_secs?);
+        let elapsed = now.saturating_duration_since(*self.last_run.get(code)?);
+        (elapsed < cooldown).then(|| cooldown - elapsed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::CommandAction;
+    use crate::input::Key;
+
+    fn cut() -> ResolvedCommand {
+        ResolvedCommand {
+            code: "CUT".to_string(),
+            action: CommandAction::key(Key::Delete),
+        }
+    }
+
+    #[test]
+    fn test_policy_serialization() {
+        let policy: SafetyPolicy =
+            serde_json::from_str(r#"{"confirm_secs": 5, "confirm_on_desktop": true}"#).unwrap();
+        assert_eq!(policy.confirm_secs, Some(5));
+        assert!(policy.confirm_on_desktop);
+        assert!(!policy.is_empty());
+        assert!(SafetyPolicy::default().is_empty());
+        assert_eq!(
+            serde_json::to_string(&SafetyPolicy {
+                cooldown_secs: Some(3),
+                ..Default::default()
+            })
+            .unwrap(),
+            r#"{"cooldown_secs":3}"#
+        );
+    }
+
+    #[test]
+    fn test_confirmation_and_cooldown() {
+        let mut guard = SafetyGuard::new();
+        let start = Instant::now();
+        let policy = SafetyPolicy {
+            confirm_secs: Some(5),
+            cooldown_secs: Some(10),
+            confirm_on_desktop: false,
+        };
+
+        // Nothing to confirm
+        assert_eq!(guard.confirm(start), None);
+        assert!(!guard.is_pending(start));
+
+        // Confirmed in time, which starts the cooldown
+        assert_eq!(
+            guard.check(&cut(), &policy, start),
+            SafetyCheck::AwaitConfirmation(Duration::from_secs(5))
+        );
+        let confirmed = start + Duration::from_secs(4);
+        assert!(guard.is_pending(confirmed));
+        assert!(!guard.is_pending(start + Duration::from_secs(6)));
+        assert_eq!(guard.confirm(confirmed), Some(cut()));
+        assert_eq!(guard.confirm(confirmed), None);
+        assert_eq!(
+            guard.check(&cut(), &policy, start + Duration::from_secs(8)),
+            SafetyCheck::CoolingDown(Duration::from_secs(6))
+        );
+
+        // Confirmed too late
+        let later = start + Duration::from_secs(20);
+        guard.check(&cut(), &policy, later);
+        assert_eq!(guard.confirm(later + Duration::from_secs(6)), None);
+
+        // Cancelled
+        guard.check(&cut(), &policy, later);
+        assert_eq!(guard.cancel(), Some("CUT".to_string()));
+        assert_eq!(guard.confirm(later), None);
+
+        // Cooldown only
+        let policy = SafetyPolicy {
+            cooldown_secs: Some(2),
+            ..Default::default()
+        };
+        let mut guard = SafetyGuard::new();
+        assert_eq!(guard.check(&cut(), &policy, start), SafetyCheck::Run);
+        assert_eq!(
+            guard.check(&cut(), &policy, start + Duration::from_secs(1)),
+            SafetyCheck::CoolingDown(Duration::from_secs(1))
+        );
+        assert_eq!(
+            guard.check(&cut(), &policy, start + Duration::from_secs(2)),
+            SafetyCheck::Run
+        );
+    }
+}
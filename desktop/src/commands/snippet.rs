@@ -0,0 +1,116 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Snippet placeholder expansion.
+//!
+//! Snippet text may contain `{date}`, `{time}`, `{date:FORMAT}` (a chrono
+//! format string, e.g. `{date:%d.%m.%Y}`) and `{clipboard}`. `{{` and `}}`
+//! produce literal braces; unknown placeholders are kept as-is.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, TimeZone};
+use std::fmt::{Display, Write};
+use tracing::warn;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Expand the placeholders in a snippet.
+/// The clipboard is only read if the text uses `{clipboard}`.
+pub fn expand_placeholders<Tz: TimeZone>(
+    text: &str,
+    now: &DateTime<Tz>,
+    clipboard: &dyn Fn() -> Result<String>,
+) -> Result<String>
+where
+    Tz::Offset: Display,
+{
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            out.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        let end = match tail.strip_prefix('{').and_then(|t| t.find('}')) {
+            Some(end) => end + 1,
+            None => {
+                // Lone brace
+                out.push_str(&tail[..1]);
+                rest = &tail[1..];
+                continue;
+            }
+        };
+        let placeholder = &tail[1..end];
+        rest = &tail[end + 1..];
+
+        let (name, format) = match placeholder.split_once(':') {
+            Some((name, format)) => (name, Some(format)),
+            None => (placeholder, None),
+        };
+        match name {
+            "date" => format_time(&mut out, now, format.unwrap_or(DATE_FORMAT))?,
+            "time" => format_time(&mut out, now, format.unwrap_or(TIME_FORMAT))?,
+            "clipboard" => out.push_str(&clipboard()?),
+            _ => {
+                warn!("Unknown snippet placeholder {{{}}}", placeholder);
+                out.push('{');
+                out.push_str(placeholder);
+                out.push('}');
+            }
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Format a time, failing on invalid format strings instead of panicking.
+fn format_time<Tz: TimeZone>(out: &mut String, now: &DateTime<Tz>, format: &str) -> Result<()>
+where
+    Tz::Offset: Display,
+{
+    write!(out, "{}", now.format(format))
+        .map_err(|_| anyhow!("Invalid date/time format '{}' in snippet", format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_expand_placeholders() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 14, 9, 5, 0).unwrap();
+        let clipboard = || Ok("pasted".to_string());
+        let expand = |text: &str| expand_placeholders(text, &now, &clipboard).unwrap();
+
+        assert_eq!(expand("Plain text"), "Plain text");
+        assert_eq!(expand("On {date} at {time}"), "On 2026-03-14 at 09:05");
+        assert_eq!(expand("{date:%d.%m.%Y}"), "14.03.2026");
+        assert_eq!(expand("Ref: {clipboard}\n"), "Ref: pasted\n");
+        assert_eq!(expand("{{date}} {unknown} {"), "{date} {unknown} {");
+
+        let no_clipboard = || Err(anyhow!("no clipboard"));
+        assert!(expand_placeholders("{clipboard}", &now, &no_clipboard).is_err());
+        assert!(expand_placeholders("{date}", &now, &no_clipboard).is_ok());
+        assert!(expand_placeholders("{date:%Q}", &now, &clipboard).is_err());
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Spelling mode.
+//!
+//! Each spoken word becomes a single character, typed without spaces:
+//! letters ("b", "bravo"), digits ("seven", "digit seven") and symbols
+//! ("dash", "underscore"). "capital" upper-cases the next letter. Other
+//! words are typed as spoken, so "john dot smith" gives `john.smith`.
+
+use super::trie::match_key;
+
+/// Words upper-casing the next letter ("capital bravo" → `B`).
+const CAPITAL_WORDS: &[&str] = &["capital", "cap", "uppercase"];
+
+/// Word before a number word, for recognizers that need the context
+/// ("digit seven" → `7`).
+const DIGIT_WORD: &str = "digit";
+
+/// NATO alphabet, with common alternative spellings.
+const NATO_ALPHABET: &[(&str, char)] = &[
+    ("alpha", 'a'),
+    ("alfa", 'a'),
+    ("bravo", 'b'),
+    ("charlie", 'c'),
+    ("delta", 'd'),
+    ("echo", 'e'),
+    ("foxtrot", 'f'),
+    ("golf", 'g'),
+    ("hotel", 'h'),
+    ("india", 'i'),
+    ("juliet", 'j'),
+    ("juliett", 'j'),
+    ("kilo", 'k'),
+    ("lima", 'l'),
+    ("mike", 'm'),
+    ("november", 'n'),
+    ("oscar", 'o'),
+    ("papa", 'p'),
+    ("quebec", 'q'),
+    ("romeo", 'r'),
+    ("sierra", 's'),
+    ("tango", 't'),
+    ("uniform", 'u'),
+    ("victor", 'v'),
+    ("whiskey", 'w'),
+    ("whisky", 'w'),
+    ("x-ray", 'x'),
+    ("xray", 'x'),
+    ("yankee", 'y'),
+    ("zulu", 'z'),
+];
+
+const DIGITS: &[(&str, char)] = &[
+    ("zero", '0'),
+    ("one", '1'),
+    ("two", '2'),
+    ("three", '3'),
+    ("four", '4'),
+    ("five", '5'),
+    ("six", '6'),
+    ("seven", '7'),
+    ("eight", '8'),
+    ("nine", '9'),
+];
+
+const SYMBOLS: &[(&str, char)] = &[
+    ("dash", '-'),
+    ("hyphen", '-'),
+    ("minus", '-'),
+    ("underscore", '_'),
+    ("dot", '.'),
+    ("period", '.'),
+    ("point", '.'),
+    ("comma", ','),
+    ("space", ' '),
+    ("at", '@'),
+    ("slash", '/'),
+    ("backslash", '\\'),
+    ("colon", ':'),
+    ("semicolon", ';'),
+    ("plus", '+'),
+    ("equals", '='),
+    ("hash", '#'),
+    ("pound", '#'),
+    ("star", '*'),
+    ("asterisk", '*'),
+    ("dollar", '$'),
+    ("percent", '%'),
+    ("ampersand", '&'),
+    ("exclamation", '!'),
+    ("bang", '!'),
+    ("question", '?'),
+    ("tilde", '~'),
+    ("caret", '^'),
+    ("pipe", '|'),
+];
+
+/// State of spelling mode.
+#[derive(Debug, Clone, Default)]
+pub struct Speller {
+    /// Upper-case the next letter.
+    capital_next: bool,
+}
+
+impl Speller {
+    /// Start spelling.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The text a spoken word spells, or `None` for words changing how the
+    /// next one is spelled ("capital", "digit").
+    pub fn spell(&mut self, word: &str) -> Option<String> {
+        // Recognizers may add punctuation ("Charlie.")
+        let key = match_key(word);
+        let key = key.trim_matches(|c: char| !c.is_alphanumeric());
+        if CAPITAL_WORDS.contains(&key) {
+            self.capital_next = true;
+            return None;
+        }
+        if key == DIGIT_WORD {
+            return None;
+        }
+
+        let capital = std::mem::take(&mut self.capital_next);
+        let text = match spelled_char(key) {
+            Some(c) => c.to_string(),
+            // Other words and symbols are typed as spoken ("." for "dot")
+            None => word.trim().to_string(),
+        };
+        Some(if capital {
+            capitalize_first(&text)
+        } else {
+            text
+        })
+    }
+}
+
+/// The character a spelling word stands for.
+fn spelled_char(key: &str) -> Option<char> {
+    let mut chars = key.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        if c.is_alphanumeric() {
+            return Some(c);
+        }
+    }
+    NATO_ALPHABET
+        .iter()
+        .chain(DIGITS)
+        .chain(SYMBOLS)
+        .find(|(word, _)| *word == key)
+        .map(|(_, c)| *c)
+}
+
+fn capitalize_first(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spell_all(words: &str) -> String {
+        let mut speller = Speller::new();
+        words
+            .split_whitespace()
+            .filter_map(|word| speller.spell(word))
+            .collect()
+    }
+
+    #[test]
+    fn test_spelling() {
+        assert_eq!(spell_all("alpha capital bravo Charlie."), "aBc");
+        assert_eq!(spell_all("x-ray dash digit seven 4"), "x-74");
+        assert_eq!(
+            spell_all("j o h n dot smith at example dot com"),
+            "john.smith@example.com"
+        );
+        assert_eq!(spell_all("capital john underscore . b"), "John_.b");
+        assert_eq!(spell_all("capital capital sierra"), "S");
+    }
+}
@@ -0,0 +1,537 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Phrase trie for command phrases of any length.
+//!
+//! Phrases are stored word by word, so words can be matched as they
+//! stream in: a sequence of words completes a phrase, may still grow into
+//! a longer one, or neither. With fuzzy matching on, words that complete
+//! no phrase exactly may still match one approximately.
+//!
+//! Phrases and words are compared in their Unicode-normalized, lowercased
+//! form, so recognizers returning decomposed accents still match. Locales
+//! may also ignore diacritics ("smach" matches "šmach").
+//!
+//! With a wake word set, phrases only match right after it ("computer
+//! enter"), so command words in ordinary speech are typed.
+//!
+//! Countable commands also match with a repeat count ("delete three
+//! words", "backspace five times").
+
+use std::collections::{HashMap, HashSet};
+use tracing::{debug, info};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use super::count::{singular, CountWords};
+use super::fuzzy::{score, FuzzySettings, NEAR_MISS_MARGIN};
+use super::LITERAL_WORD;
+
+/// Command phrases indexed word by word.
+#[derive(Debug)]
+pub struct PhraseTrie {
+    root: Node,
+    /// Phrase words and command of every phrase, for fuzzy matching.
+    phrases: Vec<(Vec<String>, String)>,
+    /// Fuzzy matching thresholds; exact matching only when unset.
+    fuzzy: Option<FuzzySettings>,
+    /// Compare phrases and words without their diacritics.
+    ignore_diacritics: bool,
+    /// Words every phrase must follow; empty when no wake word is set.
+    wake_word: Vec<String>,
+    /// Commands taking a repeat count.
+    countable: HashSet<String>,
+    /// Words of spoken repeat counts.
+    counts: CountWords,
+    /// Word escaping the command phrase after it.
+    literal: String,
+}
+
+impl Default for PhraseTrie {
+    fn default() -> Self {
+        Self {
+            root: Node::default(),
+            phrases: Vec::new(),
+            fuzzy: None,
+            ignore_diacritics: false,
+            wake_word: Vec::new(),
+            countable: HashSet::new(),
+            counts: CountWords::default(),
+            literal: LITERAL_WORD.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Node {
+    children: HashMap<String, Node>,
+    /// Command code of the phrase ending at this node.
+    command: Option<String>,
+}
+
+impl PhraseTrie {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A trie that compares phrases and words without their diacritics.
+    pub fn ignoring_diacritics() -> Self {
+        Self {
+            ignore_diacritics: true,
+            ..Self::default()
+        }
+    }
+
+    /// Add a phrase for a command. The first command added for a phrase
+    /// keeps it.
+    pub fn insert(&mut self, phrase: &str, command: &str) {
+        let words = self.words(phrase);
+        if words.is_empty() {
+            return;
+        }
+        let mut node = &mut self.root;
+        for word in &words {
+            node = node.children.entry(word.clone()).or_default();
+        }
+        if node.command.is_none() {
+            node.command = Some(command.to_string());
+            self.phrases.push((words, command.to_string()));
+        }
+    }
+
+    /// The words of a phrase in the form they are stored and looked up in.
+    pub fn words(&self, phrase: &str) -> Vec<String> {
+        phrase
+            .split_whitespace()
+            .map(|word| self.key(word))
+            .filter(|word| !word.is_empty())
+            .collect()
+    }
+
+    /// Also match phrases approximately, with these thresholds.
+    pub fn set_fuzzy(&mut self, fuzzy: FuzzySettings) {
+        self.fuzzy = Some(fuzzy);
+    }
+
+    /// Require phrases to follow a wake word (one or more words).
+    pub fn set_wake_word(&mut self, wake_word: &str) {
+        self.wake_word = wake_word
+            .split_whitespace()
+            .map(|word| self.key(word))
+            .filter(|word| !word.is_empty())
+            .collect();
+    }
+
+    /// Let a command take a repeat count (key commands).
+    pub fn set_countable(&mut self, command: &str) {
+        self.countable.insert(command.to_string());
+    }
+
+    /// Use another word for "literal" (the locale's).
+    pub fn set_literal_word(&mut self, word: &str) {
+        self.literal = self.key(word);
+    }
+
+    /// Use other words for "times" and the number words of repeat counts
+    /// (the locale's); unset ones stay English. Each number word entry
+    /// lists its forms separated by spaces ("dva dvě"), from one upward.
+    pub fn set_count_words(&mut self, times: Option<&str>, numbers: &[String]) {
+        if let Some(times) = times {
+            self.counts.times = self.key(times);
+        }
+        if !numbers.is_empty() {
+            self.counts.numbers = numbers.iter().map(|forms| self.words(forms)).collect();
+        }
+    }
+
+    /// Whether a word is the escape word making the command phrase after
+    /// it typed as text.
+    pub fn is_literal(&self, word: &str) -> bool {
+        !self.literal.is_empty() && self.key(word) == self.literal
+    }
+
+    /// Whether phrases must follow a wake word.
+    pub fn has_wake_word(&self) -> bool {
+        !self.wake_word.is_empty()
+    }
+
+    /// Whether the words are just the wake word, waiting for a phrase.
+    pub fn is_wake_word<S: AsRef<str>>(&self, words: &[S]) -> bool {
+        self.has_wake_word()
+            && self
+                .after_wake_word(words)
+                .is_some_and(|rest| rest.is_empty())
+    }
+
+    /// The words after the wake word, or all of them without one. `None`
+    /// if the words don't start with the wake word.
+    pub fn after_wake_word<'a, S: AsRef<str>>(&self, words: &'a [S]) -> Option<&'a [S]> {
+        if words.len() < self.wake_word.len() {
+            return None;
+        }
+        let (prefix, rest) = words.split_at(self.wake_word.len());
+        self.starts_wake_word(prefix).then_some(rest)
+    }
+
+    /// The command whose phrase is these words, after the wake word.
+    pub fn get<S: AsRef<str>>(&self, words: &[S]) -> Option<&str> {
+        self.command(self.after_wake_word(words)?)
+    }
+
+    /// The command whose phrase is these words, ignoring the wake word.
+    pub fn command<S: AsRef<str>>(&self, words: &[S]) -> Option<&str> {
+        self.node(words)
+            .and_then(|node| node.command.as_deref())
+            .or_else(|| {
+                self.fuzzy_match(words, |len| len == words.len())
+                    .map(|(command, _)| command)
+            })
+    }
+
+    /// Whether more words could extend these words into a phrase, or
+    /// into a phrase with a repeat count.
+    pub fn could_continue<S: AsRef<str>>(&self, words: &[S]) -> bool {
+        if words.len() <= self.wake_word.len() {
+            return !words.is_empty() && self.starts_wake_word(words);
+        }
+        let Some(rest) = self.after_wake_word(words) else {
+            return false;
+        };
+        self.node(rest).is_some_and(|n| !n.children.is_empty()) || self.could_count(rest)
+    }
+
+    /// Whether more words could add a repeat count to these words:
+    /// "delete three" (then "words"), "backspace" (then "five times") or
+    /// "backspace five" (then "times").
+    fn could_count<S: AsRef<str>>(&self, words: &[S]) -> bool {
+        if self.countable.is_empty() {
+            return false;
+        }
+        if let [first, count] = words {
+            let counts_inside = self.node(&[first]).is_some_and(|node| {
+                node.children.values().any(|child| {
+                    child
+                        .command
+                        .as_ref()
+                        .is_some_and(|command| self.countable.contains(command))
+                })
+            });
+            if counts_inside && self.parse_count(count.as_ref()).is_some() {
+                return true;
+            }
+        }
+        self.countable_command(words).is_some()
+            || words.split_last().is_some_and(|(count, phrase)| {
+                self.parse_count(count.as_ref()).is_some()
+                    && self.countable_command(phrase).is_some()
+            })
+    }
+
+    /// A countable command with a repeat count at the start of the words,
+    /// after the wake word: inside a 2-word phrase ("delete three words")
+    /// or followed by "times" ("go left three times"). Returns the
+    /// command, the count and how many words it spans, including the wake
+    /// word.
+    pub fn counted_match<S: AsRef<str>>(&self, words: &[S]) -> Option<(&str, u32, usize)> {
+        let rest = self.after_wake_word(words)?;
+        let skipped = words.len() - rest.len();
+        self.counted_phrase(rest)
+            .map(|(command, count, len)| (command, count, len + skipped))
+    }
+
+    fn counted_phrase<S: AsRef<str>>(&self, words: &[S]) -> Option<(&str, u32, usize)> {
+        if let [first, count, second, ..] = words {
+            if let Some(count) = self.parse_count(count.as_ref()) {
+                let first = first.as_ref();
+                let second = match_key(second.as_ref());
+                let command = self
+                    .countable_command(&[first, second.as_str()])
+                    .or_else(|| {
+                        singular(&second)
+                            .and_then(|second| self.countable_command(&[first, second]))
+                    });
+                if let Some(command) = command {
+                    return Some((command, count, 3));
+                }
+            }
+        }
+
+        for len in (1..words.len().saturating_sub(1)).rev() {
+            if self.key(words[len + 1].as_ref()) != self.counts.times {
+                continue;
+            }
+            let Some(count) = self.parse_count(words[len].as_ref()) else {
+                continue;
+            };
+            if let Some(command) = self.countable_command(&words[..len]) {
+                return Some((command, count, len + 2));
+            }
+        }
+        None
+    }
+
+    /// A spoken repeat count: digits or a number word.
+    fn parse_count(&self, word: &str) -> Option<u32> {
+        self.counts.parse(&self.key(word))
+    }
+
+    /// The command whose phrase is these words, if it takes a count.
+    fn countable_command<S: AsRef<str>>(&self, words: &[S]) -> Option<&str> {
+        self.command(words)
+            .filter(|command| self.countable.contains(*command))
+    }
+
+    /// The longest phrase the words start with, after the wake word: its
+    /// command and how many words it spans, including the wake word.
+    pub fn longest_match<S: AsRef<str>>(&self, words: &[S]) -> Option<(&str, usize)> {
+        let rest = self.after_wake_word(words)?;
+        let skipped = words.len() - rest.len();
+
+        let mut node = &self.root;
+        let mut longest = None;
+        for (i, word) in rest.iter().enumerate() {
+            match node.children.get(&self.key(word.as_ref())) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if let Some(command) = &node.command {
+                longest = Some((command.as_str(), i + 1));
+            }
+        }
+        longest
+            .or_else(|| self.fuzzy_match(rest, |len| len <= rest.len()))
+            .map(|(command, len)| (command, len + skipped))
+    }
+
+    /// How many words the command phrase the words start with spans, with
+    /// or without a repeat count.
+    pub fn command_len<S: AsRef<str>>(&self, words: &[S]) -> Option<usize> {
+        let counted = self.counted_match(words).map(|(_, _, len)| len);
+        let plain = self.longest_match(words).map(|(_, len)| len);
+        counted.max(plain)
+    }
+
+    /// Whether the words are the start of the wake word.
+    fn starts_wake_word<S: AsRef<str>>(&self, words: &[S]) -> bool {
+        words.len() <= self.wake_word.len()
+            && words
+                .iter()
+                .zip(&self.wake_word)
+                .all(|(word, wake)| self.key(word.as_ref()) == *wake)
+    }
+
+    /// The best approximate match among phrases whose word count is
+    /// accepted: its command and how many words it spans. Near misses are
+    /// logged so phrases and thresholds can be tuned.
+    fn fuzzy_match<S: AsRef<str>>(
+        &self,
+        words: &[S],
+        accept_len: impl Fn(usize) -> bool,
+    ) -> Option<(&str, usize)> {
+        let fuzzy = self.fuzzy.as_ref()?;
+        let spoken: Vec<String> = words.iter().map(|w| self.key(w.as_ref())).collect();
+
+        let mut best: Option<(f32, &str, usize)> = None;
+        let mut near_miss: Option<(f32, f32, &[String], &str)> = None;
+        for (phrase, command) in &self.phrases {
+            let len = phrase.len();
+            if !accept_len(len) {
+                continue;
+            }
+            let Some(threshold) = fuzzy.threshold(command, &spoken[..len], phrase) else {
+                continue;
+            };
+            let score = score(&spoken[..len], phrase);
+            if score >= threshold {
+                if best.is_none_or(|(s, _, l)| (score, len) > (s, l)) {
+                    best = Some((score, command, len));
+                }
+            } else if score >= threshold - NEAR_MISS_MARGIN
+                && near_miss.is_none_or(|(s, ..)| score > s)
+            {
+                near_miss = Some((score, threshold, phrase, command));
+            }
+        }
+
+        match best {
+            Some((score, command, len)) => {
+                debug!(
+                    "Fuzzy matched '{}' to {} (score {:.2})",
+                    spoken[..len].join(" "),
+                    command,
+                    score
+                );
+                Some((command, len))
+            }
+            None => {
+                if let Some((score, threshold, phrase, command)) = near_miss {
+                    info!(
+                        "Near miss: '{}' scored {:.2} against '{}' ({}), threshold {:.2}",
+                        spoken[..phrase.len()].join(" "),
+                        score,
+                        phrase.join(" "),
+                        command,
+                        threshold
+                    );
+                }
+                None
+            }
+        }
+    }
+
+    fn node<S: AsRef<str>>(&self, words: &[S]) -> Option<&Node> {
+        words.iter().try_fold(&self.root, |node, word| {
+            node.children.get(&self.key(word.as_ref()))
+        })
+    }
+
+    /// The form a word is stored and looked up in.
+    fn key(&self, word: &str) -> String {
+        let key = match_key(word);
+        if self.ignore_diacritics {
+            strip_diacritics(&key)
+        } else {
+            key
+        }
+    }
+}
+
+/// The lookup form of a spoken word: trimmed, NFKC-normalized and
+/// lowercased, with trailing punctuation removed.
+pub fn match_key(word: &str) -> String {
+    word.trim()
+        .trim_end_matches(['.', ',', '!', '?', ':', ';'])
+        .nfkc()
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// A word without its diacritics ("šmach" → "smach").
+fn strip_diacritics(word: &str) -> String {
+    word.nfkd().filter(|c| !is_combining_mark(*c)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::fuzzy::DEFAULT_FUZZY_THRESHOLD;
+
+    #[test]
+    fn test_phrase_trie() {
+        let mut trie = PhraseTrie::new();
+        trie.insert("open", "OPEN");
+        trie.insert("Open new browser tab", "NEW_TAB");
+        trie.insert("select all", "SELECT_ALL");
+        trie.insert("select all", "SHADOWED");
+
+        assert_eq!(trie.get(&["select", "All."]), Some("SELECT_ALL"));
+        assert_eq!(trie.get(&["select"]), None);
+        assert_eq!(trie.get(&["open", "new"]), None);
+
+        assert!(trie.could_continue(&["open"]));
+        assert!(trie.could_continue(&["open", "new", "browser"]));
+        assert!(!trie.could_continue(&["open", "new", "browser", "tab"]));
+        assert!(!trie.could_continue(&["hello"]));
+        assert!(!trie.could_continue::<&str>(&[]));
+
+        let words = ["open", "new", "browser", "tab", "now"];
+        assert_eq!(trie.longest_match(&words), Some(("NEW_TAB", 4)));
+        assert_eq!(
+            trie.longest_match(&["open", "new", "door"]),
+            Some(("OPEN", 1))
+        );
+        assert_eq!(trie.longest_match(&["hello", "open"]), None);
+    }
+
+    #[test]
+    fn test_fuzzy_phrases() {
+        let mut trie = PhraseTrie::new();
+        trie.insert("cancel", "CANCEL");
+        trie.insert("paste", "PASTE");
+        trie.insert("cut", "CUT");
+        trie.insert("select all", "SELECT_ALL");
+        assert_eq!(trie.get(&["cancell"]), None);
+
+        trie.set_fuzzy(FuzzySettings {
+            threshold: DEFAULT_FUZZY_THRESHOLD,
+            thresholds: HashMap::new(),
+        });
+        assert_eq!(trie.get(&["cancell"]), Some("CANCEL"));
+        assert_eq!(trie.get(&["cancel"]), Some("CANCEL"));
+        assert_eq!(trie.get(&["cancell", "that"]), None);
+        assert_eq!(trie.get(&["cat"]), None);
+        assert_eq!(trie.get(&["hello"]), None);
+        // Common words that only sound or look alike
+        assert_eq!(trie.get(&["council"]), None);
+        assert_eq!(trie.get(&["pasta"]), None);
+        assert_eq!(trie.get(&["post"]), None);
+        assert_eq!(
+            trie.longest_match(&["select", "al", "now"]),
+            Some(("SELECT_ALL", 2))
+        );
+        assert_eq!(
+            trie.longest_match(&["cancell", "that"]),
+            Some(("CANCEL", 1))
+        );
+        // Fuzzy matching doesn't buffer words that only resemble a prefix
+        assert!(!trie.could_continue(&["selekt"]));
+    }
+
+    #[test]
+    fn test_unicode_normalization() {
+        // Decomposed "š" (s + combining caron) matches the composed one
+        assert_eq!(match_key("S\u{30C}MACH."), "šmach");
+        assert_eq!(match_key("ﬁle"), "file");
+
+        let mut trie = PhraseTrie::new();
+        trie.insert("šmach", "ENTER");
+        assert_eq!(trie.get(&["s\u{30C}mach"]), Some("ENTER"));
+        assert_eq!(trie.get(&["smach"]), None);
+
+        let mut trie = PhraseTrie::ignoring_diacritics();
+        trie.insert("šmach", "ENTER");
+        trie.insert("nový řádek", "NEW_LINE");
+        assert_eq!(trie.get(&["smach"]), Some("ENTER"));
+        assert_eq!(trie.get(&["šmach"]), Some("ENTER"));
+        assert_eq!(trie.get(&["novy", "radek"]), Some("NEW_LINE"));
+        assert!(trie.could_continue(&["novy"]));
+    }
+
+    #[test]
+    fn test_wake_word() {
+        let mut trie = PhraseTrie::new();
+        trie.insert("enter", "ENTER");
+        trie.insert("select all", "SELECT_ALL");
+        trie.set_wake_word("Hey Computer");
+
+        assert_eq!(trie.get(&["enter"]), None);
+        assert_eq!(trie.get(&["hey", "computer", "enter"]), Some("ENTER"));
+        assert_eq!(trie.command(&["enter"]), Some("ENTER"));
+        assert_eq!(trie.longest_match(&["enter", "now"]), None);
+        assert_eq!(
+            trie.longest_match(&["hey", "computer,", "select", "all", "now"]),
+            Some(("SELECT_ALL", 4))
+        );
+
+        assert!(trie.could_continue(&["hey"]));
+        assert!(trie.could_continue(&["hey", "computer"]));
+        assert!(trie.could_continue(&["hey", "computer", "select"]));
+        assert!(!trie.could_continue(&["select"]));
+        assert!(!trie.could_continue(&["hey", "there"]));
+
+        assert!(trie.is_wake_word(&["hey", "computer"]));
+        assert!(!trie.is_wake_word(&["hey"]));
+        assert!(!PhraseTrie::new().is_wake_word::<&str>(&[]));
+    }
+}
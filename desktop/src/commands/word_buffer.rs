@@ -12,44 +12,81 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Word buffer for handling look-ahead for 2-word commands.
+//! Word buffer for handling look-ahead for multi-word commands.
 //!
 //! This module provides a buffer that:
-//! - Implements look-ahead for potential 2-word commands
+//! - Holds words while they may be the start of a longer command phrase,
+//!   or of a command with a repeat count
 //! - Handles session changes gracefully
+//! - Types the command phrase after the literal escape word as text
 
 use std::time::{Duration, Instant};
 use tracing::debug;
 
-/// Default look-ahead timeout for 2-word command matching.
+use super::PhraseTrie;
+
+/// Default look-ahead timeout for multi-word command matching.
 pub const LOOK_AHEAD_TIMEOUT: Duration = Duration::from_millis(100);
 
-/// Result of processing a word.
+/// How long a wake word waits for the command phrase after it before it
+/// is typed as text.
+pub const WAKE_WORD_TIMEOUT: Duration = Duration::from_millis(1500);
+
+/// Result of processing a word, with the timestamp of the message it
+/// came from (for feedback correlation).
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ProcessedItem {
     /// A word to be typed (with trailing space).
-    Text(String),
-    /// A command to execute.
-    Command(String), // Command code like "ENTER", "COPY"
+    Text(String, u64),
+    /// A command to execute, timestamped with its phrase's last word.
+    Command(String, u64), // Command code like "ENTER", "COPY"
+    /// A key command to repeat a spoken number of times.
+    Repeated(String, u32, u64),
 }
 
-/// A word pending for look-ahead (waiting for potential 2nd word of command).
+/// Words pending for look-ahead (waiting for the rest of a command phrase).
 #[derive(Debug, Clone)]
-struct PendingWord {
-    word: String,
+struct PendingWords {
+    words: Vec<String>,
+    /// Message timestamp of each word.
+    msg_ts: Vec<u64>,
+    /// When the last word arrived.
     received_at: Instant,
 }
 
-/// Buffer for handling look-ahead for 2-word commands.
+impl PendingWords {
+    /// Remove the first word, to be typed.
+    fn text(&mut self) -> ProcessedItem {
+        let word = self.words.remove(0);
+        let msg_ts = self.msg_ts.remove(0);
+        ProcessedItem::Text(format!("{} ", word), msg_ts)
+    }
+
+    /// Remove the first `len` words, returning the message timestamp of
+    /// the last one.
+    fn drain(&mut self, len: usize) -> u64 {
+        let msg_ts = self.msg_ts[len - 1];
+        self.words.drain(..len);
+        self.msg_ts.drain(..len);
+        msg_ts
+    }
+}
+
+/// Buffer for handling look-ahead for multi-word commands.
 ///
-/// Words arrive from the Android app and are processed immediately.
-/// This buffer implements look-ahead for 2-word commands (e.g., "select all").
+/// Words arrive from the Android app and are processed immediately,
+/// except while they may still grow into a command phrase (e.g., "select"
+/// before "select all"). Buffered words are resolved once the next word
+/// doesn't continue any phrase, or after `LOOK_AHEAD_TIMEOUT`
+/// (`WAKE_WORD_TIMEOUT` while only the wake word is buffered).
 #[derive(Debug)]
 pub struct WordBuffer {
     /// Current session ID.
     current_session: Option<String>,
-    /// Pending word for look-ahead (waiting for potential 2nd word of command).
-    pending: Option<PendingWord>,
+    /// Words pending for look-ahead.
+    pending: Option<PendingWords>,
+    /// Whether the literal escape word applies.
+    literal_escape: bool,
 }
 
 impl Default for WordBuffer {
@@ -64,6 +101,7 @@ impl WordBuffer {
         Self {
             current_session: None,
             pending: None,
+            literal_escape: true,
         }
     }
 
@@ -76,106 +114,114 @@ impl WordBuffer {
         self.pending = None;
     }
 
+    /// Turn the literal escape word on or off. It is off in dictation-only
+    /// mode, where it is typed like the command words it would escape.
+    pub fn set_literal_escape(&mut self, enabled: bool) {
+        self.literal_escape = enabled;
+    }
+
     /// Process an incoming word. Returns items ready to be processed.
     ///
     /// May return empty vec if word is buffered for look-ahead.
     ///
     /// # Arguments
     /// * `word` - The word received
+    /// * `msg_ts` - Timestamp of the word's message
     /// * `session` - Session ID
-    /// * `command_matcher` - Function to match single-word commands
-    /// * `two_word_matcher` - Function to match two-word commands
-    /// * `could_start_two_word` - Function to check if word could be first of 2-word command
+    /// * `phrases` - Command phrases to match
     pub fn process_word(
         &mut self,
         word: String,
+        msg_ts: u64,
         session: &str,
-        command_matcher: &dyn Fn(&str) -> Option<String>,
-        two_word_matcher: &dyn Fn(&str, &str) -> Option<String>,
-        could_start_two_word: &dyn Fn(&str) -> bool,
+        phrases: &PhraseTrie,
     ) -> Vec<ProcessedItem> {
         // Check for session change
         if self.current_session.as_deref() != Some(session) {
             self.reset_for_session(session);
         }
 
-        let mut results = Vec::new();
-
-        // Process immediately - no sequence buffering!
-        self.process_single_word(
-            word,
-            command_matcher,
-            two_word_matcher,
-            could_start_two_word,
-            &mut results,
-        );
+        let pending = self.pending.get_or_insert_with(|| PendingWords {
+            words: Vec::new(),
+            msg_ts: Vec::new(),
+            received_at: Instant::now(),
+        });
+        pending.words.push(word);
+        pending.msg_ts.push(msg_ts);
+        pending.received_at = Instant::now();
 
+        let mut results = Vec::new();
+        self.resolve(phrases, true, &mut results);
         results
     }
 
-    /// Process a single word.
-    fn process_single_word(
-        &mut self,
-        word: String,
-        command_matcher: &dyn Fn(&str) -> Option<String>,
-        two_word_matcher: &dyn Fn(&str, &str) -> Option<String>,
-        could_start_two_word: &dyn Fn(&str) -> bool,
-        results: &mut Vec<ProcessedItem>,
-    ) {
-        // If there's a pending word, try to match 2-word command
-        if let Some(pending) = self.pending.take() {
-            if let Some(cmd) = two_word_matcher(&pending.word, &word) {
-                // Two-word command matched!
-                results.push(ProcessedItem::Command(cmd));
+    /// Resolve pending words from the front: the longest command phrase
+    /// is executed, other words are typed. With `wait`, stops while the
+    /// pending words may still grow into a longer phrase.
+    fn resolve(&mut self, phrases: &PhraseTrie, wait: bool, results: &mut Vec<ProcessedItem>) {
+        let Some(pending) = self.pending.as_mut() else {
+            return;
+        };
+
+        while !pending.words.is_empty() {
+            // The escape word types the command phrase after it as text
+            let literal = self.literal_escape && phrases.is_literal(&pending.words[0]);
+            let words = &pending.words[usize::from(literal)..];
+
+            if wait && (words.is_empty() || phrases.could_continue(words)) {
+                // Buffer for look-ahead
                 return;
             }
 
-            // No 2-word match - emit pending as text
-            results.push(ProcessedItem::Text(format!("{} ", pending.word)));
-
-            // Now process the new word
-        }
+            if literal {
+                match phrases.command_len(words) {
+                    Some(len) => {
+                        debug!("Literal escape, typing {} command words as text", len);
+                        pending.drain(1);
+                        for _ in 0..len {
+                            results.push(pending.text());
+                        }
+                    }
+                    // Not before a command, so an ordinary word
+                    None => results.push(pending.text()),
+                }
+                continue;
+            }
 
-        // Check if this word could start a 2-word command
-        if could_start_two_word(&word) {
-            // Buffer for look-ahead
-            self.pending = Some(PendingWord {
-                word,
-                received_at: Instant::now(),
-            });
-            return;
-        }
+            if let Some((cmd, count, len)) = phrases.counted_match(&pending.words) {
+                let cmd = cmd.to_string();
+                results.push(ProcessedItem::Repeated(cmd, count, pending.drain(len)));
+                continue;
+            }
 
-        // Check for single-word command
-        if let Some(cmd) = command_matcher(&word) {
-            results.push(ProcessedItem::Command(cmd));
-            return;
+            match phrases.longest_match(&pending.words) {
+                Some((cmd, len)) => {
+                    let cmd = cmd.to_string();
+                    results.push(ProcessedItem::Command(cmd, pending.drain(len)));
+                }
+                None => {
+                    // Regular word - emit as text
+                    results.push(pending.text());
+                }
+            }
         }
-
-        // Regular word - emit as text
-        results.push(ProcessedItem::Text(format!("{} ", word)));
+        self.pending = None;
     }
 
-    /// Flush any pending look-ahead word if timed out.
+    /// Flush pending look-ahead words if timed out.
     ///
     /// Call this periodically (e.g., every 50ms).
-    pub fn flush_pending(
-        &mut self,
-        command_matcher: &dyn Fn(&str) -> Option<String>,
-    ) -> Vec<ProcessedItem> {
+    pub fn flush_pending(&mut self, phrases: &PhraseTrie) -> Vec<ProcessedItem> {
         let mut results = Vec::new();
 
         if let Some(ref pending) = self.pending {
-            if pending.received_at.elapsed() >= LOOK_AHEAD_TIMEOUT {
-                let pending = self.pending.take().unwrap();
-
-                // Check if it's a single-word command
-                if let Some(cmd) = command_matcher(&pending.word) {
-                    results.push(ProcessedItem::Command(cmd));
-                } else {
-                    // Emit as text
-                    results.push(ProcessedItem::Text(format!("{} ", pending.word)));
-                }
+            let timeout = if phrases.is_wake_word(&pending.words) {
+                WAKE_WORD_TIMEOUT
+            } else {
+                LOOK_AHEAD_TIMEOUT
+            };
+            if pending.received_at.elapsed() >= timeout {
+                self.resolve(phrases, false, &mut results);
             }
         }
 
@@ -190,20 +236,19 @@ impl WordBuffer {
         );
         self.current_session = Some(session.to_string());
 
-        // Discard any pending word from old session
+        // Discard any pending words from old session
         self.pending = None;
     }
 
-    /// Check if there's a pending word waiting for look-ahead.
-    #[cfg(test)]
-    fn has_pending(&self) -> bool {
+    /// Check if there are pending words waiting for look-ahead.
+    pub fn has_pending(&self) -> bool {
         self.pending.is_some()
     }
 
-    /// Get the pending word (for testing).
+    /// Get the pending words (for testing).
     #[cfg(test)]
-    fn pending_word(&self) -> Option<&str> {
-        self.pending.as_ref().map(|p| p.word.as_str())
+    fn pending_words(&self) -> Option<String> {
+        self.pending.as_ref().map(|p| p.words.join(" "))
     }
 }
 
@@ -212,60 +257,28 @@ mod tests {
     use super::*;
 
     // Test helpers
-    fn no_command(_word: &str) -> Option<String> {
-        None
-    }
-
-    fn no_two_word(_w1: &str, _w2: &str) -> Option<String> {
-        None
-    }
-
-    fn never_starts_two_word(_word: &str) -> bool {
-        false
-    }
-
-    fn simple_command_matcher(word: &str) -> Option<String> {
-        match word.to_lowercase().as_str() {
-            "enter" => Some("ENTER".to_string()),
-            "copy" => Some("COPY".to_string()),
-            "paste" => Some("PASTE".to_string()),
-            _ => None,
-        }
-    }
-
-    fn select_all_matcher(w1: &str, w2: &str) -> Option<String> {
-        if w1.to_lowercase() == "select" && w2.to_lowercase() == "all" {
-            Some("SELECT_ALL".to_string())
-        } else {
-            None
-        }
+    fn no_commands() -> PhraseTrie {
+        PhraseTrie::new()
     }
 
-    fn could_be_select(word: &str) -> bool {
-        word.to_lowercase() == "select"
+    fn commands() -> PhraseTrie {
+        let mut phrases = PhraseTrie::new();
+        phrases.insert("enter", "ENTER");
+        phrases.insert("copy", "COPY");
+        phrases.insert("paste", "PASTE");
+        phrases.insert("select all", "SELECT_ALL");
+        phrases
     }
 
     #[test]
     fn test_words_processed_immediately() {
         let mut buffer = WordBuffer::new();
 
-        let items = buffer.process_word(
-            "hello".to_string(),
-            "session1",
-            &no_command,
-            &no_two_word,
-            &never_starts_two_word,
-        );
-        assert_eq!(items, vec![ProcessedItem::Text("hello ".to_string())]);
-
-        let items = buffer.process_word(
-            "world".to_string(),
-            "session1",
-            &no_command,
-            &no_two_word,
-            &never_starts_two_word,
-        );
-        assert_eq!(items, vec![ProcessedItem::Text("world ".to_string())]);
+        let items = buffer.process_word("hello".to_string(), 0, "session1", &no_commands());
+        assert_eq!(items, vec![ProcessedItem::Text("hello ".to_string(), 0)]);
+
+        let items = buffer.process_word("world".to_string(), 0, "session1", &no_commands());
+        assert_eq!(items, vec![ProcessedItem::Text("world ".to_string(), 0)]);
     }
 
     #[test]
@@ -273,25 +286,13 @@ mod tests {
         let mut buffer = WordBuffer::new();
 
         // Process some words in session 1
-        buffer.process_word(
-            "hello".to_string(),
-            "session1",
-            &no_command,
-            &no_two_word,
-            &never_starts_two_word,
-        );
+        buffer.process_word("hello".to_string(), 0, "session1", &no_commands());
 
         // New session should work fine
-        let items = buffer.process_word(
-            "new".to_string(),
-            "session2",
-            &no_command,
-            &no_two_word,
-            &never_starts_two_word,
-        );
+        let items = buffer.process_word("new".to_string(), 0, "session2", &no_commands());
         assert_eq!(
             items,
-            vec![ProcessedItem::Text("new ".to_string())],
+            vec![ProcessedItem::Text("new ".to_string(), 0)],
             "new session should process words"
         );
     }
@@ -300,26 +301,14 @@ mod tests {
     fn test_session_change_clears_pending() {
         let mut buffer = WordBuffer::new();
 
-        // Buffer "select" as pending (could start 2-word command)
-        let items = buffer.process_word(
-            "select".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &select_all_matcher,
-            &could_be_select,
-        );
+        // Buffer "select" as pending (could start a longer phrase)
+        let items = buffer.process_word("select".to_string(), 0, "session1", &commands());
         assert!(items.is_empty(), "select should be buffered as pending");
         assert!(buffer.has_pending());
 
         // New session should clear pending
-        let items = buffer.process_word(
-            "hello".to_string(),
-            "session2",
-            &simple_command_matcher,
-            &select_all_matcher,
-            &could_be_select,
-        );
-        assert_eq!(items, vec![ProcessedItem::Text("hello ".to_string())]);
+        let items = buffer.process_word("hello".to_string(), 0, "session2", &commands());
+        assert_eq!(items, vec![ProcessedItem::Text("hello ".to_string(), 0)]);
         assert!(!buffer.has_pending());
     }
 
@@ -327,23 +316,11 @@ mod tests {
     fn test_single_word_command_matching() {
         let mut buffer = WordBuffer::new();
 
-        let items = buffer.process_word(
-            "enter".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &no_two_word,
-            &never_starts_two_word,
-        );
-        assert_eq!(items, vec![ProcessedItem::Command("ENTER".to_string())]);
-
-        let items = buffer.process_word(
-            "copy".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &no_two_word,
-            &never_starts_two_word,
-        );
-        assert_eq!(items, vec![ProcessedItem::Command("COPY".to_string())]);
+        let items = buffer.process_word("enter".to_string(), 0, "session1", &commands());
+        assert_eq!(items, vec![ProcessedItem::Command("ENTER".to_string(), 0)]);
+
+        let items = buffer.process_word("copy".to_string(), 0, "session1", &commands());
+        assert_eq!(items, vec![ProcessedItem::Command("COPY".to_string(), 0)]);
     }
 
     #[test]
@@ -351,27 +328,15 @@ mod tests {
         let mut buffer = WordBuffer::new();
 
         // "select" should be buffered for look-ahead
-        let items = buffer.process_word(
-            "select".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &select_all_matcher,
-            &could_be_select,
-        );
+        let items = buffer.process_word("select".to_string(), 0, "session1", &commands());
         assert!(items.is_empty(), "select should be buffered");
-        assert_eq!(buffer.pending_word(), Some("select"));
+        assert_eq!(buffer.pending_words().as_deref(), Some("select"));
 
         // "all" should complete the command
-        let items = buffer.process_word(
-            "all".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &select_all_matcher,
-            &could_be_select,
-        );
+        let items = buffer.process_word("all".to_string(), 0, "session1", &commands());
         assert_eq!(
             items,
-            vec![ProcessedItem::Command("SELECT_ALL".to_string())]
+            vec![ProcessedItem::Command("SELECT_ALL".to_string(), 0)]
         );
         assert!(!buffer.has_pending());
     }
@@ -381,30 +346,42 @@ mod tests {
         let mut buffer = WordBuffer::new();
 
         // "select" should be buffered
-        let items = buffer.process_word(
-            "select".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &select_all_matcher,
-            &could_be_select,
-        );
+        let items = buffer.process_word("select".to_string(), 0, "session1", &commands());
         assert!(items.is_empty());
 
-        // "something" doesn't complete 2-word command
-        let items = buffer.process_word(
-            "something".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &select_all_matcher,
-            &could_be_select,
-        );
+        // "something" doesn't continue any phrase
+        let items = buffer.process_word("something".to_string(), 0, "session1", &commands());
         assert_eq!(
             items,
             vec![
-                ProcessedItem::Text("select ".to_string()),
-                ProcessedItem::Text("something ".to_string()),
+                ProcessedItem::Text("select ".to_string(), 0),
+                ProcessedItem::Text("something ".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_items_keep_message_timestamps() {
+        let mut buffer = WordBuffer::new();
+
+        assert!(buffer
+            .process_word("select".to_string(), 1, "session1", &commands())
+            .is_empty());
+        assert_eq!(
+            buffer.process_word("something".to_string(), 2, "session1", &commands()),
+            vec![
+                ProcessedItem::Text("select ".to_string(), 1),
+                ProcessedItem::Text("something ".to_string(), 2),
             ]
         );
+
+        assert!(buffer
+            .process_word("select".to_string(), 3, "session1", &commands())
+            .is_empty());
+        assert_eq!(
+            buffer.process_word("all".to_string(), 4, "session1", &commands()),
+            vec![ProcessedItem::Command("SELECT_ALL".to_string(), 4)]
+        );
     }
 
     #[test]
@@ -412,17 +389,11 @@ mod tests {
         let mut buffer = WordBuffer::new();
 
         // Buffer "select"
-        buffer.process_word(
-            "select".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &select_all_matcher,
-            &could_be_select,
-        );
+        buffer.process_word("select".to_string(), 0, "session1", &commands());
         assert!(buffer.has_pending());
 
         // Immediately flush - should return empty (not timed out yet)
-        let items = buffer.flush_pending(&simple_command_matcher);
+        let items = buffer.flush_pending(&commands());
         assert!(items.is_empty(), "should not flush before timeout");
 
         // Simulate timeout by replacing pending with an old timestamp
@@ -431,8 +402,8 @@ mod tests {
         }
 
         // Now flush should emit the pending word as text
-        let items = buffer.flush_pending(&simple_command_matcher);
-        assert_eq!(items, vec![ProcessedItem::Text("select ".to_string())]);
+        let items = buffer.flush_pending(&commands());
+        assert_eq!(items, vec![ProcessedItem::Text("select ".to_string(), 0)]);
         assert!(!buffer.has_pending());
     }
 
@@ -440,23 +411,12 @@ mod tests {
     fn test_flush_pending_single_word_command() {
         let mut buffer = WordBuffer::new();
 
-        // Create a matcher where "select" is ALSO a single-word command
-        let select_is_command = |word: &str| -> Option<String> {
-            if word.to_lowercase() == "select" {
-                Some("SELECT".to_string())
-            } else {
-                None
-            }
-        };
+        // Phrases where "select" is ALSO a single-word command
+        let mut select_is_command = commands();
+        select_is_command.insert("select", "SELECT");
 
         // Buffer "select"
-        buffer.process_word(
-            "select".to_string(),
-            "session1",
-            &select_is_command,
-            &select_all_matcher,
-            &could_be_select,
-        );
+        buffer.process_word("select".to_string(), 0, "session1", &select_is_command);
 
         // Simulate timeout
         if let Some(ref mut pending) = buffer.pending {
@@ -465,7 +425,7 @@ mod tests {
 
         // Flush should emit as command (since it matches single-word)
         let items = buffer.flush_pending(&select_is_command);
-        assert_eq!(items, vec![ProcessedItem::Command("SELECT".to_string())]);
+        assert_eq!(items, vec![ProcessedItem::Command("SELECT".to_string(), 0)]);
     }
 
     #[test]
@@ -475,34 +435,16 @@ mod tests {
         // Send: "hello", "enter", "world"
         let mut all_items = Vec::new();
 
-        all_items.extend(buffer.process_word(
-            "hello".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &no_two_word,
-            &never_starts_two_word,
-        ));
-        all_items.extend(buffer.process_word(
-            "enter".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &no_two_word,
-            &never_starts_two_word,
-        ));
-        all_items.extend(buffer.process_word(
-            "world".to_string(),
-            "session1",
-            &simple_command_matcher,
-            &no_two_word,
-            &never_starts_two_word,
-        ));
+        all_items.extend(buffer.process_word("hello".to_string(), 0, "session1", &commands()));
+        all_items.extend(buffer.process_word("enter".to_string(), 0, "session1", &commands()));
+        all_items.extend(buffer.process_word("world".to_string(), 0, "session1", &commands()));
 
         assert_eq!(
             all_items,
             vec![
-                ProcessedItem::Text("hello ".to_string()),
-                ProcessedItem::Command("ENTER".to_string()),
-                ProcessedItem::Text("world ".to_string()),
+                ProcessedItem::Text("hello ".to_string(), 0),
+                ProcessedItem::Command("ENTER".to_string(), 0),
+                ProcessedItem::Text("world ".to_string(), 0),
             ]
         );
     }
@@ -512,38 +454,247 @@ mod tests {
         let mut buffer = WordBuffer::new();
 
         // Process several words in session1
-        buffer.process_word(
-            "hello".to_string(),
-            "session1",
-            &no_command,
-            &no_two_word,
-            &never_starts_two_word,
-        );
-        buffer.process_word(
-            "world".to_string(),
-            "session1",
-            &no_command,
-            &no_two_word,
-            &never_starts_two_word,
-        );
+        buffer.process_word("hello".to_string(), 0, "session1", &no_commands());
+        buffer.process_word("world".to_string(), 0, "session1", &no_commands());
 
         // Reset the buffer (simulating reconnection)
         buffer.reset();
 
         // Now process words with SAME session ID
-        let items = buffer.process_word(
-            "reconnected".to_string(),
-            "session1",
-            &no_command,
-            &no_two_word,
-            &never_starts_two_word,
-        );
+        let items = buffer.process_word("reconnected".to_string(), 0, "session1", &no_commands());
 
         // Should work because reset() cleared the state
         assert_eq!(
             items,
-            vec![ProcessedItem::Text("reconnected ".to_string())],
+            vec![ProcessedItem::Text("reconnected ".to_string(), 0)],
             "after reset, words should be processed"
         );
     }
+
+    #[test]
+    fn test_literal_escape_types_command_word() {
+        let mut buffer = WordBuffer::new();
+        let mut process =
+            |word: &str| buffer.process_word(word.to_string(), 0, "session1", &commands());
+
+        assert!(process("Literal").is_empty());
+        assert_eq!(
+            process("enter"),
+            vec![ProcessedItem::Text("enter ".to_string(), 0)]
+        );
+        assert_eq!(
+            process("enter"),
+            vec![ProcessedItem::Command("ENTER".to_string(), 0)]
+        );
+
+        // Escaping a longer phrase
+        assert!(process("literal").is_empty());
+        assert!(process("select").is_empty());
+        assert_eq!(
+            process("all"),
+            vec![
+                ProcessedItem::Text("select ".to_string(), 0),
+                ProcessedItem::Text("all ".to_string(), 0),
+            ]
+        );
+
+        // Before other words, it's an ordinary word
+        assert!(process("literal").is_empty());
+        assert_eq!(
+            process("meaning"),
+            vec![
+                ProcessedItem::Text("literal ".to_string(), 0),
+                ProcessedItem::Text("meaning ".to_string(), 0),
+            ]
+        );
+        assert!(process("literal").is_empty());
+        if let Some(ref mut pending) = buffer.pending {
+            pending.received_at = Instant::now() - Duration::from_millis(200);
+        }
+        assert_eq!(
+            buffer.flush_pending(&commands()),
+            vec![ProcessedItem::Text("literal ".to_string(), 0)]
+        );
+
+        // Unless turned off
+        buffer.set_literal_escape(false);
+        assert_eq!(
+            buffer.process_word("literal".to_string(), 0, "session1", &commands()),
+            vec![ProcessedItem::Text("literal ".to_string(), 0)]
+        );
+        assert_eq!(
+            buffer.process_word("enter".to_string(), 0, "session1", &commands()),
+            vec![ProcessedItem::Command("ENTER".to_string(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_long_phrases() {
+        let mut phrases = commands();
+        phrases.insert("open", "OPEN");
+        phrases.insert("open new browser tab", "NEW_TAB");
+        phrases.insert("go to end", "GO_END");
+
+        let mut buffer = WordBuffer::new();
+        let mut process =
+            |word: &str| buffer.process_word(word.to_string(), 0, "session1", &phrases);
+
+        assert!(process("open").is_empty());
+        assert!(process("new").is_empty());
+        assert!(process("browser").is_empty());
+        assert_eq!(
+            process("tab"),
+            vec![ProcessedItem::Command("NEW_TAB".to_string(), 0)]
+        );
+
+        // A broken-off phrase executes its longest complete prefix
+        assert!(process("open").is_empty());
+        assert!(process("new").is_empty());
+        assert_eq!(
+            process("door"),
+            vec![
+                ProcessedItem::Command("OPEN".to_string(), 0),
+                ProcessedItem::Text("new ".to_string(), 0),
+                ProcessedItem::Text("door ".to_string(), 0),
+            ]
+        );
+
+        // The rest of a mismatch can start another phrase
+        assert!(process("go").is_empty());
+        assert_eq!(
+            process("select"),
+            vec![ProcessedItem::Text("go ".to_string(), 0)]
+        );
+        assert_eq!(
+            process("all"),
+            vec![ProcessedItem::Command("SELECT_ALL".to_string(), 0)]
+        );
+
+        // Timeout resolves words waiting for the rest of a phrase
+        assert!(process("go").is_empty());
+        assert!(process("to").is_empty());
+        assert_eq!(buffer.pending_words().as_deref(), Some("go to"));
+        if let Some(ref mut pending) = buffer.pending {
+            pending.received_at = Instant::now() - Duration::from_millis(200);
+        }
+        assert_eq!(
+            buffer.flush_pending(&phrases),
+            vec![
+                ProcessedItem::Text("go ".to_string(), 0),
+                ProcessedItem::Text("to ".to_string(), 0),
+            ]
+        );
+        assert!(!buffer.has_pending());
+    }
+
+    #[test]
+    fn test_repeat_counts() {
+        let mut phrases = commands();
+        phrases.insert("backspace", "BACKSPACE");
+        phrases.insert("delete word", "DELETE_WORD");
+        phrases.set_countable("BACKSPACE");
+        phrases.set_countable("DELETE_WORD");
+        phrases.set_countable("ENTER");
+
+        let mut buffer = WordBuffer::new();
+        let mut process =
+            |word: &str| buffer.process_word(word.to_string(), 0, "session1", &phrases);
+
+        assert!(process("delete").is_empty());
+        assert!(process("three").is_empty());
+        assert_eq!(
+            process("words"),
+            vec![ProcessedItem::Repeated("DELETE_WORD".to_string(), 3, 0)]
+        );
+
+        assert!(process("backspace").is_empty());
+        assert!(process("five").is_empty());
+        assert_eq!(
+            process("times"),
+            vec![ProcessedItem::Repeated("BACKSPACE".to_string(), 5, 0)]
+        );
+
+        // A number not followed by "times" is typed
+        assert!(process("enter").is_empty());
+        assert!(process("2").is_empty());
+        assert_eq!(
+            process("people"),
+            vec![
+                ProcessedItem::Command("ENTER".to_string(), 0),
+                ProcessedItem::Text("2 ".to_string(), 0),
+                ProcessedItem::Text("people ".to_string(), 0),
+            ]
+        );
+
+        // Commands without a count run once the look-ahead times out
+        assert!(process("backspace").is_empty());
+        if let Some(ref mut pending) = buffer.pending {
+            pending.received_at = Instant::now() - Duration::from_millis(200);
+        }
+        assert_eq!(
+            buffer.flush_pending(&phrases),
+            vec![ProcessedItem::Command("BACKSPACE".to_string(), 0)]
+        );
+
+        // Only countable commands take a count
+        let mut process =
+            |word: &str| buffer.process_word(word.to_string(), 0, "session1", &phrases);
+        assert_eq!(
+            process("copy"),
+            vec![ProcessedItem::Command("COPY".to_string(), 0)]
+        );
+        assert_eq!(
+            process("three"),
+            vec![ProcessedItem::Text("three ".to_string(), 0)]
+        );
+    }
+
+    #[test]
+    fn test_wake_word() {
+        let mut phrases = commands();
+        phrases.set_wake_word("computer");
+        let mut buffer = WordBuffer::new();
+
+        // Command words alone are typed
+        assert_eq!(
+            buffer.process_word("paste".to_string(), 0, "session1", &phrases),
+            vec![ProcessedItem::Text("paste ".to_string(), 0)]
+        );
+
+        assert!(buffer
+            .process_word("computer".to_string(), 0, "session1", &phrases)
+            .is_empty());
+        assert_eq!(
+            buffer.process_word("enter".to_string(), 0, "session1", &phrases),
+            vec![ProcessedItem::Command("ENTER".to_string(), 0)]
+        );
+
+        assert!(buffer
+            .process_word("computer".to_string(), 0, "session1", &phrases)
+            .is_empty());
+        assert!(buffer
+            .process_word("select".to_string(), 0, "session1", &phrases)
+            .is_empty());
+        assert_eq!(
+            buffer.process_word("all".to_string(), 0, "session1", &phrases),
+            vec![ProcessedItem::Command("SELECT_ALL".to_string(), 0)]
+        );
+
+        // A dangling wake word waits longer than other look-ahead
+        assert!(buffer
+            .process_word("computer".to_string(), 0, "session1", &phrases)
+            .is_empty());
+        if let Some(ref mut pending) = buffer.pending {
+            pending.received_at = Instant::now() - Duration::from_millis(200);
+        }
+        assert!(buffer.flush_pending(&phrases).is_empty());
+        assert!(buffer.has_pending());
+        if let Some(ref mut pending) = buffer.pending {
+            pending.received_at = Instant::now() - WAKE_WORD_TIMEOUT;
+        }
+        assert_eq!(
+            buffer.flush_pending(&phrases),
+            vec![ProcessedItem::Text("computer ".to_string(), 0)]
+        );
+    }
 }
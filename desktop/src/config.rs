@@ -19,14 +19,25 @@
 use anyhow::Result;
 use gethostname::gethostname;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use tracing::warn;
+
+use crate::commands::{CommandAction, DEFAULT_FUZZY_THRESHOLD};
+use crate::input::FocusedWindow;
 
 /// Get a sanitized hostname suitable for Bluetooth device name.
-/// Bluetooth names should only contain alphanumeric chars, spaces, and hyphens.
 fn get_sanitized_hostname() -> String {
     let hostname = gethostname().to_string_lossy().to_string();
+    sanitize_device_name(&hostname).unwrap_or_else(|| "Desktop".to_string())
+}
+
+/// Sanitize a name for use as the Bluetooth device name.
+/// Bluetooth names should only contain alphanumeric chars, spaces, and hyphens.
+/// Returns `None` if nothing usable is left.
+fn sanitize_device_name(name: &str) -> Option<String> {
     // Sanitize: keep only alphanumeric, spaces, and hyphens
-    let sanitized: String = hostname
+    let sanitized: String = name
         .chars()
         .map(|c| {
             if c.is_alphanumeric() || c == '-' || c == ' ' {
@@ -36,12 +47,12 @@ fn get_sanitized_hostname() -> String {
             }
         })
         .collect();
-    // Trim leading/trailing hyphens and collapse multiple hyphens
-    let trimmed = sanitized.trim_matches('-');
+    // Trim leading/trailing hyphens and whitespace
+    let trimmed = sanitized.trim_matches(|c: char| c == '-' || c.is_whitespace());
     if trimmed.is_empty() {
-        "Desktop".to_string()
+        None
     } else {
-        trimmed.to_string()
+        Some(trimmed.to_string())
     }
 }
 
@@ -57,31 +68,208 @@ pub struct Config {
 
     /// Input settings.
     pub input: InputConfig,
+
+    /// External programs that voice commands may run.
+    #[serde(default)]
+    pub programs: ProgramsConfig,
+
+    /// Per-application profiles.
+    #[serde(default)]
+    pub profiles: ProfilesConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct BluetoothConfig {
     /// Device name advertised over Bluetooth.
-    /// This is always computed at runtime from the system hostname.
+    /// Computed at runtime from `advertised_name`, or the system hostname if unset.
     #[serde(skip)]
     pub device_name: String,
 
+    /// Custom name to advertise instead of the hostname.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub advertised_name: Option<String>,
+
     /// Auto-accept connections from paired devices.
     pub auto_accept: bool,
+
+    /// When the desktop advertises itself to phones.
+    pub advertising: AdvertisingMode,
+
+    /// Optional tag sent as manufacturer data in the advertisement,
+    /// so phones can tell workstations apart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub manufacturer_tag: Option<String>,
 }
 
 impl Default for BluetoothConfig {
     fn default() -> Self {
         Self {
             device_name: get_sanitized_hostname(),
+            advertised_name: None,
             auto_accept: true,
+            advertising: AdvertisingMode::default(),
+            manufacturer_tag: None,
         }
     }
 }
 
+impl BluetoothConfig {
+    /// Resolve the advertised device name from the custom name or the hostname.
+    fn resolve_device_name(&mut self) {
+        self.device_name = match self.advertised_name.as_deref().map(sanitize_device_name) {
+            Some(Some(name)) => name,
+            Some(None) => {
+                warn!("Invalid advertised_name in config, using hostname");
+                get_sanitized_hostname()
+            }
+            None => get_sanitized_hostname(),
+        };
+    }
+}
+
+/// Advertising policy for the BLE GATT server.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdvertisingMode {
+    /// Always advertise.
+    #[default]
+    Always,
+    /// Advertise only while no phone is paired or a pairing is in progress.
+    WhileUnpaired,
+    /// Stop advertising while a phone is connected.
+    StopWhileConnected,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct InputConfig {}
+#[serde(default)]
+pub struct InputConfig {
+    /// Capitalize the first word of sentences and lines.
+    pub auto_capitalize: bool,
+    /// Dictation and voice command language code ("en", "cs"), unless the
+    /// device has its own or the phone announces one.
+    pub language: String,
+    /// Write spoken numbers, amounts, times and dates with digits.
+    pub normalize_numbers: bool,
+    /// Match misheard command phrases approximately.
+    pub fuzzy_matching: bool,
+    /// Minimum fuzzy match score (0.0-1.0), unless the voice commands file
+    /// sets one for the command.
+    pub fuzzy_threshold: f32,
+    /// Word that must precede command phrases ("computer enter"). Without
+    /// it, commands run wherever their phrases are recognized.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wake_word: Option<String>,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            auto_capitalize: false,
+            language: "en".to_string(),
+            normalize_numbers: false,
+            fuzzy_matching: false,
+            fuzzy_threshold: DEFAULT_FUZZY_THRESHOLD,
+            wake_word: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProgramsConfig {
+    /// Programs each command code may run, e.g. `LOCK_SCREEN = ["loginctl"]`.
+    /// Commands not listed here cannot run programs.
+    pub allow: BTreeMap<String, Vec<String>>,
+}
+
+impl ProgramsConfig {
+    /// Check whether a command may run a program.
+    pub fn is_allowed(&self, command: &str, program: &str) -> bool {
+        self.allow
+            .iter()
+            .find(|(code, _)| code.eq_ignore_ascii_case(command))
+            .is_some_and(|(_, programs)| programs.iter().any(|p| p == program))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfilesConfig {
+    /// Program printing the focused window's class and title on two lines,
+    /// for desktops where it can't be detected (X11 is built in).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub focus_command: Vec<String>,
+    /// Profiles in order of precedence; the first matching the focused
+    /// window applies.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub apps: Vec<AppProfile>,
+}
+
+impl ProfilesConfig {
+    /// The profile for a window, if any.
+    pub fn find(&self, window: &FocusedWindow) -> Option<&AppProfile> {
+        self.apps.iter().find(|profile| profile.matches(window))
+    }
+}
+
+/// Command and formatting overrides for an application.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppProfile {
+    /// Name shown in logs.
+    pub name: String,
+    /// Window classes the profile applies to, e.g. `["kitty", "XTerm"]`.
+    pub class: Vec<String>,
+    /// Text the window title must contain.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Actions replacing those of commands, e.g.
+    /// `COPY = { type = "key", keys = "ctrl+shift+c" }`.
+    pub commands: BTreeMap<String, CommandAction>,
+    /// Commands that don't run in the application.
+    pub disable: Vec<String>,
+    /// Capitalize sentence starts, instead of the `[input]` setting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_capitalize: Option<bool>,
+    /// Write spoken numbers with digits, instead of the `[input]` setting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub normalize_numbers: Option<bool>,
+}
+
+impl AppProfile {
+    /// Whether the profile applies to a window: its class is one of the
+    /// classes and its title contains the title text, ignoring case. A
+    /// profile without either applies to no window.
+    pub fn matches(&self, window: &FocusedWindow) -> bool {
+        if self.class.is_empty() && self.title.is_none() {
+            return false;
+        }
+        let class = self.class.is_empty()
+            || self
+                .class
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(&window.class));
+        let title = self
+            .title
+            .as_ref()
+            .is_none_or(|t| window.title.to_lowercase().contains(&t.to_lowercase()));
+        class && title
+    }
+
+    /// The action replacing a command's, if any.
+    pub fn action(&self, command: &str) -> Option<&CommandAction> {
+        self.commands
+            .iter()
+            .find(|(code, _)| code.eq_ignore_ascii_case(command))
+            .map(|(_, action)| action)
+    }
+
+    /// Whether a command is disabled.
+    pub fn is_disabled(&self, command: &str) -> bool {
+        self.disable.iter().any(|c| c.eq_ignore_ascii_case(command))
+    }
+}
 
 impl Default for Config {
     fn default() -> Self {
@@ -90,7 +278,9 @@ impl Default for Config {
                 .unwrap_or_else(|| PathBuf::from("."))
                 .join("prontafon"),
             bluetooth: BluetoothConfig::default(),
-            input: InputConfig {},
+            input: InputConfig::default(),
+            programs: ProgramsConfig::default(),
+            profiles: ProfilesConfig::default(),
         }
     }
 }
@@ -116,6 +306,8 @@ impl Config {
             config
         };
 
+        config.bluetooth.resolve_device_name();
+
         // Set data directory
         config.data_dir = dirs::data_dir()
             .unwrap_or_else(|| PathBuf::from("."))
@@ -125,3 +317,100 @@ impl Config {
         Ok(config)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Key, Modifier};
+
+    #[test]
+    fn test_sanitize_device_name() {
+        assert_eq!(
+            sanitize_device_name("my.host_name"),
+            Some("my-host-name".to_string())
+        );
+        assert_eq!(sanitize_device_name(" Desk 4 "), Some("Desk 4".to_string()));
+        assert_eq!(sanitize_device_name("..."), None);
+    }
+
+    #[test]
+    fn test_bluetooth_config_parsing() {
+        let config: BluetoothConfig = toml::from_str(
+            r#"
+            advertised_name = "Desk 4"
+            advertising = "stop_while_connected"
+            manufacturer_tag = "desk4"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.advertised_name.as_deref(), Some("Desk 4"));
+        assert_eq!(config.advertising, AdvertisingMode::StopWhileConnected);
+        assert_eq!(config.manufacturer_tag.as_deref(), Some("desk4"));
+        assert!(config.auto_accept);
+
+        // Older config files without the new fields keep the defaults
+        let config: BluetoothConfig = toml::from_str("auto_accept = false").unwrap();
+        assert_eq!(config.advertising, AdvertisingMode::Always);
+        assert!(config.advertised_name.is_none());
+    }
+
+    #[test]
+    fn test_programs_allowlist() {
+        let config: ProgramsConfig = toml::from_str(
+            r#"
+            [allow]
+            LOCK_SCREEN = ["loginctl"]
+            "#,
+        )
+        .unwrap();
+        assert!(config.is_allowed("LOCK_SCREEN", "loginctl"));
+        assert!(config.is_allowed("lock_screen", "loginctl"));
+        assert!(!config.is_allowed("LOCK_SCREEN", "rm"));
+        assert!(!config.is_allowed("RUN_TESTS", "loginctl"));
+    }
+
+    #[test]
+    fn test_app_profiles() {
+        let config: ProfilesConfig = toml::from_str(
+            r#"
+            [[apps]]
+            name = "Vim"
+            class = ["kitty"]
+            title = "VIM"
+            disable = ["enter"]
+
+            [[apps]]
+            name = "Terminal"
+            class = ["kitty", "XTerm"]
+            auto_capitalize = false
+
+            [apps.commands]
+            COPY = { type = "key", keys = "ctrl+shift+c" }
+            "#,
+        )
+        .unwrap();
+        let window = |class: &str, title: &str| FocusedWindow {
+            class: class.to_string(),
+            title: title.to_string(),
+        };
+
+        let vim = config.find(&window("kitty", "notes.txt - vim")).unwrap();
+        assert_eq!(vim.name, "Vim");
+        assert!(vim.is_disabled("ENTER"));
+
+        let terminal = config.find(&window("xterm", "bash")).unwrap();
+        assert_eq!(terminal.name, "Terminal");
+        assert_eq!(terminal.auto_capitalize, Some(false));
+        assert_eq!(
+            terminal.action("copy"),
+            Some(&CommandAction::chord(
+                &[Modifier::Ctrl, Modifier::Shift],
+                Key::Letter('c')
+            ))
+        );
+        assert!(terminal.action("PASTE").is_none());
+
+        assert!(config.find(&window("firefox", "vim tips")).is_none());
+        assert!(!AppProfile::default().matches(&window("", "")));
+    }
+}
@@ -0,0 +1,402 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Connection diagnostics.
+//!
+//! Collects per-session link statistics from the GATT server and the event
+//! processor, and exposes them to the tray and the `status` CLI command.
+//! The snapshot file names the process that saved it and is refreshed
+//! while it runs, so `status` can tell a snapshot left by an earlier run.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// File name of the diagnostics snapshot in the data directory.
+pub const DIAGNOSTICS_FILE: &str = "diagnostics.json";
+
+/// How often the snapshot is saved even if nothing changed.
+const SNAPSHOT_REFRESH: Duration = Duration::from_secs(30);
+
+/// Age after which a snapshot is left over from an earlier run.
+const SNAPSHOT_STALE_AFTER: Duration = Duration::from_secs(120);
+
+/// End-to-end word latency statistics in milliseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    /// Number of samples.
+    pub count: u64,
+    /// Most recent sample.
+    pub last_ms: u64,
+    /// Smallest sample.
+    pub min_ms: u64,
+    /// Largest sample.
+    pub max_ms: u64,
+    /// Sum of all samples (for the average).
+    pub total_ms: u64,
+}
+
+impl LatencyStats {
+    /// Add a latency sample.
+    fn record(&mut self, ms: u64) {
+        if self.count == 0 || ms < self.min_ms {
+            self.min_ms = ms;
+        }
+        self.max_ms = self.max_ms.max(ms);
+        self.last_ms = ms;
+        self.total_ms = self.total_ms.saturating_add(ms);
+        self.count += 1;
+    }
+
+    /// Average latency, if any samples were recorded.
+    pub fn average_ms(&self) -> Option<u64> {
+        (self.count > 0).then(|| self.total_ms / self.count)
+    }
+}
+
+/// Statistics for a single connection session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionStats {
+    /// When the session was established.
+    pub started_at: Option<DateTime<Utc>>,
+    /// Connected device name.
+    pub device_name: Option<String>,
+    /// Negotiated ATT MTU.
+    pub mtu: usize,
+    /// BLE packets received.
+    pub packets_in: u64,
+    /// BLE packets sent.
+    pub packets_out: u64,
+    /// Complete messages received.
+    pub messages_in: u64,
+    /// Complete messages sent.
+    pub messages_out: u64,
+    /// Packets dropped by the reassembler (sequence or length errors).
+    pub reassembly_errors: u64,
+    /// Messages rejected because of a bad checksum.
+    pub checksum_failures: u64,
+    /// Messages that could not be decrypted or parsed.
+    pub decode_errors: u64,
+    /// Words received.
+    pub words: u64,
+    /// Text or command injections that failed.
+    pub injection_errors: u64,
+    /// Latency from the phone's word timestamp to injection.
+    pub word_latency: LatencyStats,
+}
+
+/// Session stats as saved for the `status` command.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Process id of the instance that saved it.
+    pub pid: u32,
+    /// When it was saved.
+    pub saved_at: DateTime<Utc>,
+    #[serde(flatten)]
+    pub stats: SessionStats,
+}
+
+impl Snapshot {
+    /// Whether the snapshot comes from a running instance: its process is
+    /// alive and it was saved recently.
+    pub fn is_current(&self) -> bool {
+        let age = (Utc::now() - self.saved_at).to_std().unwrap_or_default();
+        age < SNAPSHOT_STALE_AFTER && process_running(self.pid)
+    }
+}
+
+impl SessionStats {
+    /// One-line summary for the tray menu.
+    pub fn summary(&self) -> String {
+        let mut summary = format!("MTU {} · {} msgs", self.mtu, self.messages_in);
+        if let Some(avg) = self.word_latency.average_ms() {
+            summary.push_str(&format!(" · {} ms", avg));
+        }
+        let errors = self.reassembly_errors + self.checksum_failures + self.decode_errors;
+        if errors > 0 {
+            summary.push_str(&format!(" · {} errors", errors));
+        }
+        summary
+    }
+
+    /// Multi-line report for the `status` CLI command.
+    pub fn report(&self) -> String {
+        let mut lines = Vec::new();
+        match (&self.device_name, &self.started_at) {
+            (Some(device), Some(started)) => {
+                lines.push(format!("Device:            {}", device));
+                lines.push(format!("Connected since:   {}", started.to_rfc3339()));
+            }
+            _ => lines.push("Device:            (none)".to_string()),
+        }
+        lines.push(format!("MTU:               {}", self.mtu));
+        lines.push(format!(
+            "Packets in/out:    {} / {}",
+            self.packets_in, self.packets_out
+        ));
+        lines.push(format!(
+            "Messages in/out:   {} / {}",
+            self.messages_in, self.messages_out
+        ));
+        lines.push(format!("Words:             {}", self.words));
+        lines.push(format!("Reassembly errors: {}", self.reassembly_errors));
+        lines.push(format!("Checksum failures: {}", self.checksum_failures));
+        lines.push(format!("Decode errors:     {}", self.decode_errors));
+        lines.push(format!("Injection errors:  {}", self.injection_errors));
+        match self.word_latency.average_ms() {
+            Some(avg) => lines.push(format!(
+                "Word latency:      avg {} ms, min {} ms, max {} ms, last {} ms",
+                avg, self.word_latency.min_ms, self.word_latency.max_ms, self.word_latency.last_ms
+            )),
+            None => lines.push("Word latency:      (no samples)".to_string()),
+        }
+        lines.join("\n")
+    }
+}
+
+/// Shared diagnostics collector.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    stats: RwLock<SessionStats>,
+    /// Set when the stats changed since the last snapshot was saved.
+    dirty: AtomicBool,
+    /// When the last snapshot was saved.
+    saved_at: RwLock<Option<Instant>>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Arc<Self> {
+        Arc::new(Self::default())
+    }
+
+    /// Apply a change to the current session stats.
+    fn update(&self, f: impl FnOnce(&mut SessionStats)) {
+        f(&mut self.stats.write());
+        self.dirty.store(true, Ordering::Relaxed);
+    }
+
+    /// Start a new session, clearing the previous session's counters.
+    pub fn start_session(&self, device_name: &str, mtu: usize) {
+        self.update(|s| {
+            *s = SessionStats {
+                started_at: Some(Utc::now()),
+                device_name: Some(device_name.to_string()),
+                mtu,
+                ..Default::default()
+            }
+        });
+    }
+
+    pub fn set_mtu(&self, mtu: usize) {
+        self.update(|s| s.mtu = mtu);
+    }
+
+    pub fn record_packet_in(&self) {
+        self.update(|s| s.packets_in += 1);
+    }
+
+    pub fn record_packets_out(&self, count: usize) {
+        self.update(|s| {
+            s.packets_out += count as u64;
+            s.messages_out += 1;
+        });
+    }
+
+    pub fn record_message_in(&self) {
+        self.update(|s| s.messages_in += 1);
+    }
+
+    pub fn record_reassembly_errors(&self, count: u64) {
+        if count > 0 {
+            self.update(|s| s.reassembly_errors += count);
+        }
+    }
+
+    pub fn record_checksum_failure(&self) {
+        self.update(|s| s.checksum_failures += 1);
+    }
+
+    pub fn record_decode_error(&self) {
+        self.update(|s| s.decode_errors += 1);
+    }
+
+    pub fn record_injection_error(&self) {
+        self.update(|s| s.injection_errors += 1);
+    }
+
+    pub fn record_word(&self) {
+        self.update(|s| s.words += 1);
+    }
+
+    /// Record the latency of a word injected now, sent by the phone at
+    /// `sent_at_ms`.
+    ///
+    /// Latency assumes the phone and desktop clocks are roughly in sync;
+    /// samples from a phone clock ahead of ours are ignored.
+    pub fn record_latency(&self, sent_at_ms: u64) {
+        if let Some(ms) = now_ms().checked_sub(sent_at_ms) {
+            self.update(|s| s.word_latency.record(ms));
+        }
+    }
+
+    /// Get a copy of the current session stats.
+    pub fn snapshot(&self) -> SessionStats {
+        self.stats.read().clone()
+    }
+
+    /// Write a snapshot to `path` if anything changed since the last save,
+    /// or to refresh its timestamp. Returns whether a snapshot was written.
+    pub fn save_if_changed(&self, path: &Path) -> Result<bool> {
+        let refresh = self
+            .saved_at
+            .read()
+            .is_none_or(|at| at.elapsed() >= SNAPSHOT_REFRESH);
+        if !self.dirty.swap(false, Ordering::Relaxed) && !refresh {
+            return Ok(false);
+        }
+        let snapshot = Snapshot {
+            pid: std::process::id(),
+            saved_at: Utc::now(),
+            stats: self.snapshot(),
+        };
+        let content = serde_json::to_string_pretty(&snapshot)?;
+        std::fs::write(path, content).with_context(|| format!("Failed to write {:?}", path))?;
+        *self.saved_at.write() = Some(Instant::now());
+        Ok(true)
+    }
+
+    /// Load a snapshot saved by an instance, running or not.
+    pub fn load_snapshot(path: &Path) -> Result<Snapshot> {
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+        serde_json::from_str(&content).with_context(|| "Failed to parse diagnostics.json")
+    }
+}
+
+/// Whether a process with this id is running.
+fn process_running(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Current time in milliseconds since the UNIX epoch.
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_latency_stats() {
+        let mut latency = LatencyStats::default();
+        assert_eq!(latency.average_ms(), None);
+
+        latency.record(30);
+        latency.record(10);
+        latency.record(50);
+
+        assert_eq!(latency.count, 3);
+        assert_eq!(latency.min_ms, 10);
+        assert_eq!(latency.max_ms, 50);
+        assert_eq!(latency.last_ms, 50);
+        assert_eq!(latency.average_ms(), Some(30));
+    }
+
+    #[test]
+    fn test_start_session_resets_counters() {
+        let diagnostics = Diagnostics::new();
+        diagnostics.record_packet_in();
+        diagnostics.record_checksum_failure();
+
+        diagnostics.start_session("Pixel 7", 185);
+
+        let stats = diagnostics.snapshot();
+        assert_eq!(stats.packets_in, 0);
+        assert_eq!(stats.checksum_failures, 0);
+        assert_eq!(stats.mtu, 185);
+        assert_eq!(stats.device_name.as_deref(), Some("Pixel 7"));
+    }
+
+    #[test]
+    fn test_record_word_latency() {
+        let diagnostics = Diagnostics::new();
+
+        for _ in 0..3 {
+            diagnostics.record_word();
+        }
+        diagnostics.record_latency(now_ms() - 40);
+        // Phone clock ahead of ours: no latency sample
+        diagnostics.record_latency(now_ms() + 60_000);
+
+        let stats = diagnostics.snapshot();
+        assert_eq!(stats.words, 3);
+        assert_eq!(stats.word_latency.count, 1);
+        assert!(stats.word_latency.last_ms >= 40);
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join(DIAGNOSTICS_FILE);
+
+        let diagnostics = Diagnostics::new();
+        diagnostics.start_session("Pixel 7", 247);
+        diagnostics.record_message_in();
+        assert!(diagnostics.save_if_changed(&path)?);
+
+        let loaded = Diagnostics::load_snapshot(&path)?;
+        assert_eq!(loaded.stats, diagnostics.snapshot());
+        assert_eq!(loaded.pid, std::process::id());
+        assert!(loaded.is_current());
+
+        // Nothing changed, so the file is left alone
+        std::fs::remove_file(&path)?;
+        assert!(!diagnostics.save_if_changed(&path)?);
+        assert!(!path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stale_snapshot() {
+        let snapshot = Snapshot {
+            pid: std::process::id(),
+            saved_at: Utc::now(),
+            stats: SessionStats::default(),
+        };
+        assert!(snapshot.is_current());
+
+        // Saved long ago, or by a process that is gone
+        let old = Snapshot {
+            saved_at: Utc::now() - chrono::Duration::hours(1),
+            ..snapshot.clone()
+        };
+        assert!(!old.is_current());
+        let exited = Snapshot {
+            pid: u32::MAX,
+            ..snapshot
+        };
+        assert!(!exited.is_current());
+    }
+}
@@ -14,36 +14,203 @@
 
 //! Event processing and message dispatch.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot::error::TryRecvError;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, warn};
 
-use crate::bluetooth::ConnectionEvent;
+use crate::bluetooth::{ConnectionEvent, DesktopMode, FeedbackPayload, InjectorHealth};
 use crate::commands::{
-    CombinedMatcher, MatchResult, ProcessedItem, TextSegment, VoiceCommand, WordBuffer,
+    normalizer_for, Capitalization, CombinedMatcher, CommandAction, MacroCancelled, MatchResult,
+    Normalizer, PhraseTrie, ProcessedItem, ProgramFailed, ResolvedCommand, SafetyCheck,
+    SafetyGuard, SafetyPolicy, SmartSpacing, Speller, SpokenFormHold, TextSegment, UndoHistory,
+    UtteranceKey, WordBuffer, LOOK_AHEAD_TIMEOUT,
 };
-use crate::input::InputInjector;
+use crate::config::{AppProfile, ProfilesConfig, ProgramsConfig};
+use crate::diagnostics::Diagnostics;
+use crate::input::{Chord, FocusProvider, FocusedWindow, InputInjector, Key};
 use crate::state::AppState;
-use crate::storage::VoiceCommandStore;
+use crate::storage::{builtin_commands, language_code, PhraseConflict, VoiceCommandStore};
+
+/// How often the focused window is checked to pick an application profile.
+const FOCUS_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a command waits for approval on the desktop before it counts
+/// as declined (the dialog closes itself after the same time).
+const DESKTOP_CONFIRM_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Request to confirm an external program, or a command whose safety
+/// policy asks for it, on the desktop.
+pub struct ProgramConfirmRequest {
+    /// The command code.
+    pub command: String,
+    /// What will run, e.g. "exec loginctl lock-session".
+    pub description: String,
+    /// Receives whether the user approved.
+    pub respond: oneshot::Sender<bool>,
+}
+
+/// A command waiting for approval in a desktop dialog. Dictation goes on
+/// meanwhile; dropping it withdraws the request.
+struct DesktopConfirmation {
+    code: String,
+    action: CommandAction,
+    /// Timestamp of the message that requested the command.
+    msg_ts: u64,
+    approved: oneshot::Receiver<bool>,
+    /// When the request counts as declined.
+    deadline: Instant,
+}
+
+/// A macro, snippet or external program running in the background.
+struct BackgroundAction {
+    code: String,
+    /// Whether the action runs external programs (feedback carries an exit code).
+    runs_program: bool,
+    /// Timestamp of the message that started the action.
+    msg_ts: u64,
+    cancel: Arc<AtomicBool>,
+    handle: JoinHandle<Result<()>>,
+}
+
+/// Input held back while a background action runs.
+enum Delivery {
+    Text(String, Option<UndoEntry>),
+    Command(ResolvedCommand, Option<UndoEntry>),
+    /// An action approved on the desktop, ready to start.
+    Approved(String, CommandAction),
+}
+
+/// Typed text for the undo history, recorded once it is injected.
+struct UndoEntry {
+    utterance: UtteranceKey,
+    chars: usize,
+    /// Spacing and capitalization from before the text.
+    before: (SmartSpacing, Capitalization),
+    /// The history was cleared since, if `history_epoch` moved on.
+    epoch: u64,
+}
 
 /// Process events from Bluetooth connections.
 pub struct EventProcessor {
-    injector: Box<dyn InputInjector>,
+    injector: Arc<dyn InputInjector>,
     voice_command_store: Option<Arc<VoiceCommandStore>>,
     state: Option<Arc<AppState>>,
     matcher: Option<CombinedMatcher>,
     word_buffer: WordBuffer,
+    /// Spacing between dictated text and spoken punctuation.
+    spacing: SmartSpacing,
+    /// Sentence position and caps mode for dictated text.
+    capitalization: Capitalization,
+    /// Configured language, used unless the device has one.
+    language: String,
+    /// Language set for the connected trusted device.
+    device_language: Option<String>,
+    /// Recognizer language announced by the connected phone.
+    announced_language: Option<String>,
+    /// Language code of the active phrases and normalizer.
+    active_language: String,
+    /// Whether to write spoken numbers with digits.
+    normalize_numbers: bool,
+    /// Writes spoken numbers, amounts, times and dates with digits.
+    normalizer: Option<Box<dyn Normalizer>>,
+    /// Streamed words held while they may continue a spoken number.
+    spoken_forms: SpokenFormHold,
+    /// Spelling mode state, while words are typed as single characters.
+    spelling: Option<Speller>,
+    /// The utterance being typed (for undo).
+    utterance: UtteranceKey,
+    /// Number of the current batch of streamed words, counting pauses.
+    word_batch: u64,
+    /// When the last streamed word arrived.
+    last_word_at: Option<Instant>,
+    /// Dictated utterances that "scratch that" can delete, with the
+    /// spacing and capitalization state from before each one.
+    history: UndoHistory<(SmartSpacing, Capitalization)>,
+    /// Number of times the history was cleared, so text queued before
+    /// isn't recorded into it.
+    history_epoch: u64,
+    diagnostics: Arc<Diagnostics>,
+    /// Phone timestamps of streamed words not injected yet, by message
+    /// timestamp, for latency diagnostics.
+    word_sent_at: VecDeque<(u64, u64)>,
+    /// Whether the most recent injection failed.
+    injection_failed: bool,
+    /// Timestamp of the message being processed (for feedback correlation).
+    msg_ts: u64,
+    /// Delivery feedback waiting to be sent to the phone.
+    feedback: Vec<FeedbackPayload>,
+    background: Option<BackgroundAction>,
+    /// Text and commands received while a background action runs, with their message timestamps.
+    queued: VecDeque<(u64, Delivery)>,
+    /// Programs each command may run.
+    programs: ProgramsConfig,
+    /// Channel for confirming programs on the desktop.
+    confirm_tx: Option<mpsc::UnboundedSender<ProgramConfirmRequest>>,
+    /// The command waiting for approval on the desktop.
+    confirming: Option<DesktopConfirmation>,
+    /// Pending spoken confirmations and cooldowns of commands.
+    safety: SafetyGuard,
+    /// Capitalize sentence starts, unless the active profile says otherwise.
+    auto_capitalize: bool,
+    /// Per-application profiles.
+    profiles: ProfilesConfig,
+    /// Reports the focused window, to pick a profile.
+    focus: Option<Arc<dyn FocusProvider>>,
+    /// When the focused window was last checked.
+    focus_checked: Option<Instant>,
+    /// The focused window query running on a blocking thread.
+    focus_query: Option<oneshot::Receiver<Result<Option<FocusedWindow>>>>,
+    /// Index of the profile for the focused window.
+    profile: Option<usize>,
 }
 
 impl EventProcessor {
     /// Create a new event processor.
     pub fn new(injector: Box<dyn InputInjector>) -> Self {
         Self {
-            injector,
+            injector: Arc::from(injector),
             voice_command_store: None,
             state: None,
             matcher: None,
             word_buffer: WordBuffer::new(),
+            spacing: SmartSpacing::new(),
+            capitalization: Capitalization::new(),
+            language: String::new(),
+            device_language: None,
+            announced_language: None,
+            active_language: String::new(),
+            normalize_numbers: false,
+            normalizer: None,
+            spoken_forms: SpokenFormHold::new(),
+            spelling: None,
+            utterance: UtteranceKey::Text(0),
+            word_batch: 0,
+            last_word_at: None,
+            history: UndoHistory::new(),
+            history_epoch: 0,
+            diagnostics: Diagnostics::new(),
+            word_sent_at: VecDeque::new(),
+            injection_failed: false,
+            msg_ts: 0,
+            feedback: Vec::new(),
+            background: None,
+            queued: VecDeque::new(),
+            programs: ProgramsConfig::default(),
+            confirm_tx: None,
+            confirming: None,
+            safety: SafetyGuard::new(),
+            auto_capitalize: false,
+            profiles: ProfilesConfig::default(),
+            focus: None,
+            focus_checked: None,
+            focus_query: None,
+            profile: None,
         }
     }
 
@@ -53,34 +220,979 @@ impl EventProcessor {
         voice_command_store: Arc<VoiceCommandStore>,
         state: Arc<AppState>,
     ) -> Self {
-        let matcher = CombinedMatcher::new(voice_command_store.clone());
-        Self {
-            injector,
-            voice_command_store: Some(voice_command_store),
-            state: Some(state),
-            matcher: Some(matcher),
-            word_buffer: WordBuffer::new(),
+        let mut processor = Self::new(injector);
+        processor.matcher = Some(CombinedMatcher::new(voice_command_store.clone()));
+        processor.voice_command_store = Some(voice_command_store);
+        processor.state = Some(state);
+        processor
+    }
+
+    /// Share a diagnostics collector for word latency and injection errors.
+    pub fn set_diagnostics(&mut self, diagnostics: Arc<Diagnostics>) {
+        self.diagnostics = diagnostics;
+    }
+
+    /// Enable or disable writing spoken numbers, amounts, times and dates
+    /// with digits. Takes effect with the next language change.
+    pub fn set_normalize_numbers(&mut self, enabled: bool) {
+        self.normalize_numbers = enabled;
+    }
+
+    /// Turn fuzzy command matching on with a default threshold, or off.
+    pub fn set_fuzzy_matching(&mut self, threshold: Option<f32>) {
+        if let Some(store) = &self.voice_command_store {
+            store.set_fuzzy_matching(threshold);
+        }
+    }
+
+    /// Require command phrases to follow a wake word, or not.
+    pub fn set_wake_word(&mut self, wake_word: Option<&str>) {
+        if let Some(store) = &self.voice_command_store {
+            store.set_wake_word(wake_word);
+        }
+    }
+
+    /// Set the configured language ("en", "cs-CZ"), used when neither the
+    /// trusted device nor the phone gives one.
+    pub fn set_language(&mut self, language: &str) {
+        self.language = language.to_string();
+        self.apply_language();
+    }
+
+    /// Set the language configured for the device about to connect.
+    pub fn set_device_language(&mut self, language: Option<String>) {
+        self.device_language = language;
+    }
+
+    /// Switch command phrases and number normalization to the language of
+    /// the trusted device, the phone's recognizer or the config, in that
+    /// order of precedence.
+    fn apply_language(&mut self) {
+        let tag = self
+            .device_language
+            .as_deref()
+            .or(self.announced_language.as_deref())
+            .unwrap_or(&self.language);
+        let language = language_code(tag);
+        if language.is_empty() || language == self.active_language {
+            return;
+        }
+        // Held words were spoken in the previous language
+        self.release_spoken_forms("Failed to inject text");
+        self.active_language = language;
+        self.capitalization.set_language(&self.active_language);
+
+        if let Some(store) = &self.voice_command_store {
+            if let Err(e) = store.set_locale(&self.active_language) {
+                warn!("Keeping '{}' voice commands: {:#}", store.locale(), e);
+            }
+        }
+        self.update_normalizer();
+    }
+
+    /// Whether to write spoken numbers with digits, per the active profile
+    /// or the config.
+    fn numbers_enabled(&self) -> bool {
+        self.active_profile()
+            .and_then(|p| p.normalize_numbers)
+            .unwrap_or(self.normalize_numbers)
+    }
+
+    /// Set up number normalization for the active language, if enabled.
+    fn update_normalizer(&mut self) {
+        self.normalizer = None;
+        if self.numbers_enabled() {
+            match normalizer_for(&self.active_language) {
+                Some(normalizer) => {
+                    info!("Number normalization: {}", normalizer.language());
+                    self.normalizer = Some(normalizer);
+                }
+                None => warn!(
+                    "No number normalization for language '{}'",
+                    self.active_language
+                ),
+            }
+        }
+    }
+
+    /// Enable or disable automatic capitalization at sentence starts.
+    pub fn set_auto_capitalize(&mut self, auto: bool) {
+        self.auto_capitalize = auto;
+        self.capitalization.set_auto(
+            self.active_profile()
+                .and_then(|p| p.auto_capitalize)
+                .unwrap_or(auto),
+        );
+    }
+
+    /// Set the per-application profiles and how to find the focused window.
+    /// Without a provider, no profile applies.
+    pub fn set_profiles(
+        &mut self,
+        profiles: ProfilesConfig,
+        focus: Option<Box<dyn FocusProvider>>,
+    ) {
+        if let Some(focus) = &focus {
+            info!(
+                "{} application profiles, focused window from {}",
+                profiles.apps.len(),
+                focus.name()
+            );
+        }
+        self.profiles = profiles;
+        self.focus = focus.map(Arc::from);
+        self.focus_checked = None;
+        self.focus_query = None;
+        self.set_profile(None);
+    }
+
+    /// The profile for the focused window, if any.
+    fn active_profile(&self) -> Option<&AppProfile> {
+        self.profile.and_then(|i| self.profiles.apps.get(i))
+    }
+
+    /// Pick the profile for the focused window. The window is queried on
+    /// a blocking thread at most every `FOCUS_CHECK_INTERVAL`, so a slow
+    /// query doesn't hold up typing; its answer applies from the next
+    /// event on.
+    fn update_profile(&mut self) {
+        if let Some(query) = &mut self.focus_query {
+            let result = match query.try_recv() {
+                Ok(result) => result,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Closed) => Err(anyhow!("Focus query failed")),
+            };
+            self.focus_query = None;
+            let profile = match result {
+                Ok(Some(window)) => self
+                    .profiles
+                    .apps
+                    .iter()
+                    .position(|profile| profile.matches(&window)),
+                Ok(None) => None,
+                Err(e) => {
+                    debug!("Failed to get the focused window: {:#}", e);
+                    self.profile
+                }
+            };
+            self.set_profile(profile);
+        }
+
+        let Some(focus) = self.focus.clone() else {
+            return;
+        };
+        if self
+            .focus_checked
+            .is_some_and(|checked| checked.elapsed() < FOCUS_CHECK_INTERVAL)
+        {
+            return;
+        }
+        self.focus_checked = Some(Instant::now());
+        let (respond, query) = oneshot::channel();
+        tokio::task::spawn_blocking(move || {
+            let _ = respond.send(focus.focused_window());
+        });
+        self.focus_query = Some(query);
+    }
+
+    /// Switch to a profile, applying its text formatting.
+    fn set_profile(&mut self, profile: Option<usize>) {
+        if profile == self.profile {
+            return;
+        }
+        let numbers = self.numbers_enabled();
+        self.profile = profile;
+        match self.active_profile() {
+            Some(profile) => info!("Application profile: {}", profile.name),
+            None => info!("No application profile"),
+        }
+        let auto_capitalize = self.auto_capitalize;
+        self.set_auto_capitalize(auto_capitalize);
+        if self.numbers_enabled() != numbers {
+            // Held words may be part of a number the new setting doesn't write
+            self.release_spoken_forms("Failed to inject text");
+            self.update_normalizer();
+        }
+    }
+
+    /// Apply the active profile to a command: its own action for the
+    /// command, if any. Returns `None` if the profile disables the command.
+    fn apply_profile(&self, cmd: &ResolvedCommand) -> Option<ResolvedCommand> {
+        let Some(profile) = self.active_profile() else {
+            return Some(cmd.clone());
+        };
+        if profile.is_disabled(&cmd.code) {
+            return None;
+        }
+        Some(ResolvedCommand {
+            code: cmd.code.clone(),
+            action: profile.action(&cmd.code).unwrap_or(&cmd.action).clone(),
+        })
+    }
+
+    /// Set which programs commands may run.
+    pub fn set_programs(&mut self, programs: ProgramsConfig) {
+        self.programs = programs;
+    }
+
+    /// Set the channel used to confirm programs on the desktop.
+    pub fn set_confirm_sender(&mut self, confirm_tx: mpsc::UnboundedSender<ProgramConfirmRequest>) {
+        self.confirm_tx = Some(confirm_tx);
+    }
+
+    /// Current desktop mode and injector health for the Status characteristic.
+    pub fn desktop_status(&self) -> (DesktopMode, InjectorHealth) {
+        let mode = match &self.state {
+            Some(state) if state.is_recording() => DesktopMode::Recording,
+            Some(state) if state.is_paused() => DesktopMode::Paused,
+            Some(state) if state.is_dictation_only() => DesktopMode::DictationOnly,
+            _ => DesktopMode::Dictating,
+        };
+        let health = if !self.injector.is_available() {
+            InjectorHealth::Unavailable
+        } else if self.injection_failed {
+            InjectorHealth::Failing
+        } else {
+            InjectorHealth::Ok
+        };
+        (mode, health)
+    }
+
+    /// Check if dictation is paused from the tray.
+    fn is_paused(&self) -> bool {
+        self.state.as_ref().is_some_and(|s| s.is_paused())
+    }
+
+    /// Match only the phrases leaving dictation-only mode while it is on,
+    /// whether it was turned on by voice or from the tray.
+    fn sync_dictation_only(&mut self) {
+        let enabled = self.state.as_ref().is_some_and(|s| s.is_dictation_only());
+        if let Some(matcher) = &mut self.matcher {
+            matcher.set_dictation_only(enabled);
+        }
+        self.word_buffer.set_literal_escape(!enabled);
+    }
+
+    /// Match the confirm phrase only while a command waits for it.
+    fn sync_confirming(&mut self) {
+        let confirming = self.safety.is_pending(Instant::now());
+        if let Some(matcher) = &mut self.matcher {
+            matcher.set_confirming(confirming);
+        }
+    }
+
+    /// Match only the phrases leaving spelling mode while it is on.
+    fn sync_spelling(&mut self) {
+        let enabled = self.spelling.is_some();
+        if let Some(matcher) = &mut self.matcher {
+            matcher.set_spelling(enabled);
+        }
+    }
+
+    /// Take the delivery feedback collected since the last call.
+    pub fn take_feedback(&mut self) -> Vec<FeedbackPayload> {
+        std::mem::take(&mut self.feedback)
+    }
+
+    /// Record the outcome of an injection and queue feedback for the phone.
+    /// `command` is the command code for command executions, `None` for text.
+    /// Returns whether the injection succeeded.
+    fn track_injection(
+        &mut self,
+        result: Result<()>,
+        context: &str,
+        command: Option<&str>,
+    ) -> bool {
+        match result {
+            Ok(()) => {
+                self.injection_failed = false;
+                self.record_word_latency();
+                self.feedback.push(match command {
+                    Some(command) => FeedbackPayload::command(self.msg_ts, command),
+                    None => FeedbackPayload::typed(self.msg_ts),
+                });
+                true
+            }
+            Err(e) => {
+                error!("{}: {}", context, e);
+                self.diagnostics.record_injection_error();
+                self.injection_failed = true;
+                self.feedback.push(FeedbackPayload::failed(
+                    self.msg_ts,
+                    command.map(str::to_string),
+                    e.to_string(),
+                ));
+                false
+            }
+        }
+    }
+
+    /// Record the latency of the streamed words injected up to the current
+    /// message.
+    fn record_word_latency(&mut self) {
+        while let Some(&(msg_ts, sent_at)) = self.word_sent_at.front() {
+            if msg_ts > self.msg_ts {
+                break;
+            }
+            self.word_sent_at.pop_front();
+            self.diagnostics.record_latency(sent_at);
+        }
+    }
+
+    /// Resolve a command code, falling back to the built-in commands when
+    /// there is no voice command store.
+    fn resolve_command(&self, code: &str) -> Option<ResolvedCommand> {
+        match &self.voice_command_store {
+            Some(store) => store.resolve(code),
+            None => builtin_commands()
+                .iter()
+                .find(|d| d.command.eq_ignore_ascii_case(code.trim()))
+                .map(|d| d.resolve()),
+        }
+    }
+
+    /// Type text and track the outcome, or queue it while a background action runs.
+    /// `undo` goes into the undo history once the text is typed.
+    /// Returns whether it succeeded (queued text counts as success).
+    fn deliver_text(&mut self, text: &str, context: &str, undo: Option<UndoEntry>) -> bool {
+        if self.background.is_some() {
+            debug!(
+                "Background action running, queueing {} chars of text",
+                text.len()
+            );
+            self.queued
+                .push_back((self.msg_ts, Delivery::Text(text.to_string(), undo)));
+            return true;
+        }
+        let result = self.injector.type_text(text);
+        let ok = self.track_injection(result, context, None);
+        if ok {
+            self.record_undo(undo);
+        }
+        ok
+    }
+
+    /// An undo history entry for text about to be typed in the current
+    /// utterance.
+    fn undo_entry(&self, text: &str, before: (SmartSpacing, Capitalization)) -> UndoEntry {
+        UndoEntry {
+            utterance: self.utterance.clone(),
+            chars: text.chars().count(),
+            before,
+            epoch: self.history_epoch,
+        }
+    }
+
+    /// Add typed text to the undo history, unless the history was cleared
+    /// after the text was queued.
+    fn record_undo(&mut self, undo: Option<UndoEntry>) {
+        if let Some(undo) = undo.filter(|u| u.epoch == self.history_epoch) {
+            self.history
+                .record(&undo.utterance, undo.chars, undo.before);
+        }
+    }
+
+    /// Forget the undo history (e.g., when the cursor may have moved).
+    fn clear_history(&mut self) {
+        self.history.clear();
+        self.history_epoch += 1;
+    }
+
+    /// Type a streamed word. Words that may be part of a spoken number are
+    /// held until the number is complete, so it's normalized as a whole.
+    fn type_word(&mut self, word: &str, context: &str) -> bool {
+        if self.spelling.is_some() {
+            return self.type_spelled(word, context);
+        }
+        let Some(normalizer) = &self.normalizer else {
+            return self.type_dictated(word, context);
+        };
+        match self
+            .spoken_forms
+            .push(word, self.msg_ts, normalizer.as_ref())
+        {
+            Some((_, text)) => self.type_dictated(&text, context),
+            None => true,
+        }
+    }
+
+    /// Type the held streamed words, if any, reported against the message
+    /// of the last held word.
+    fn release_spoken_forms(&mut self, context: &str) {
+        if let Some((msg_ts, text)) = self.spoken_forms.take() {
+            self.with_msg_ts(msg_ts, |p| p.type_dictated(&text, context));
+        }
+    }
+
+    /// Run `f` with feedback reported against the message with timestamp
+    /// `msg_ts`.
+    fn with_msg_ts<R>(&mut self, msg_ts: u64, f: impl FnOnce(&mut Self) -> R) -> R {
+        let current_ts = std::mem::replace(&mut self.msg_ts, msg_ts);
+        let result = f(self);
+        self.msg_ts = current_ts;
+        result
+    }
+
+    /// Type dictated text, normalized, capitalized and spaced against
+    /// spoken punctuation.
+    /// Returns whether it succeeded.
+    fn type_dictated(&mut self, text: &str, context: &str) -> bool {
+        if self.spelling.is_some() {
+            return self.type_spelled(text, context);
+        }
+        let before = (self.spacing.clone(), self.capitalization.clone());
+        let text = match &self.normalizer {
+            Some(normalizer) => normalizer.normalize(text),
+            None => text.to_string(),
+        };
+        let text = self.capitalization.apply(&text);
+        let text = self.spacing.text(&text);
+        if text.is_empty() {
+            return true;
+        }
+        let undo = self.undo_entry(&text, before);
+        self.deliver_text(&text, context, Some(undo))
+    }
+
+    /// Type dictated words spelled, one character per word and without
+    /// spaces between them.
+    /// Returns whether it succeeded.
+    fn type_spelled(&mut self, text: &str, context: &str) -> bool {
+        let Some(speller) = &mut self.spelling else {
+            return self.type_dictated(text, context);
+        };
+        let spelled: String = text
+            .split_whitespace()
+            .filter_map(|word| speller.spell(word))
+            .collect();
+        if spelled.is_empty() {
+            return true;
+        }
+        let before = (self.spacing.clone(), self.capitalization.clone());
+        self.capitalization.track(&spelled);
+        let text = self.spacing.text(&spelled);
+        if text.is_empty() {
+            return true;
+        }
+        let undo = self.undo_entry(&text, before);
+        self.deliver_text(&text, context, Some(undo))
+    }
+
+    /// Delete the last dictated utterance with backspaces and restore the
+    /// spacing and capitalization from before it.
+    /// Returns whether it succeeded.
+    fn undo(&mut self, cmd: &ResolvedCommand, context: &str) -> bool {
+        let Some((chars, (spacing, capitalization))) = self.history.pop() else {
+            debug!("Nothing to undo");
+            self.feedback.push(FeedbackPayload::failed(
+                self.msg_ts,
+                Some(cmd.code.clone()),
+                "Nothing to undo",
+            ));
+            return false;
+        };
+        debug!("Undoing {} chars", chars);
+        self.spacing = spacing;
+        self.capitalization = capitalization;
+        // The language may have changed since
+        self.capitalization.set_language(&self.active_language);
+
+        let backspaces = ResolvedCommand {
+            code: cmd.code.clone(),
+            action: CommandAction::Key {
+                keys: Chord::new(&[], Key::Backspace),
+                repeat: chars as u32,
+            },
+        };
+        self.execute_command(&backspaces, context)
+    }
+
+    /// Run a recognized command, unless its safety policy holds it back
+    /// for a spoken confirmation or a cooldown.
+    /// Returns whether it succeeded.
+    fn run_command(&mut self, cmd: &ResolvedCommand, context: &str) -> bool {
+        let Some(cmd) = self.apply_profile(cmd) else {
+            info!("{} is disabled for this application", cmd.code);
+            self.feedback.push(FeedbackPayload::failed(
+                self.msg_ts,
+                Some(cmd.code.clone()),
+                "Disabled for this application",
+            ));
+            return false;
+        };
+        match cmd.action {
+            CommandAction::Confirm => return self.confirm_command(&cmd, context),
+            CommandAction::Cancel => {
+                if let Some(code) = self.safety.cancel() {
+                    info!("{} no longer waits for confirmation", code);
+                }
+            }
+            _ => {}
+        }
+
+        let policy = self.safety_policy(&cmd.code);
+        let error = match self.safety.check(&cmd, &policy, Instant::now()) {
+            SafetyCheck::Run => return self.perform_command(&cmd, context),
+            SafetyCheck::AwaitConfirmation(window) => {
+                info!("{} waits for confirmation", cmd.code);
+                format!(
+                    "Say \"{}\" within {} s to run it",
+                    self.confirm_phrase(),
+                    window.as_secs()
+                )
+            }
+            SafetyCheck::CoolingDown(remaining) => {
+                info!("{} ran recently, ignoring it", cmd.code);
+                format!(
+                    "Ran recently, try again in {} s",
+                    remaining.as_secs_f32().ceil()
+                )
+            }
+        };
+        self.feedback.push(FeedbackPayload::failed(
+            self.msg_ts,
+            Some(cmd.code.clone()),
+            error,
+        ));
+        false
+    }
+
+    /// Run the command waiting for a spoken confirmation.
+    fn confirm_command(&mut self, cmd: &ResolvedCommand, context: &str) -> bool {
+        let Some(pending) = self.safety.confirm(Instant::now()) else {
+            info!("Nothing waits for confirmation");
+            self.feedback.push(FeedbackPayload::failed(
+                self.msg_ts,
+                Some(cmd.code.clone()),
+                "Nothing to confirm",
+            ));
+            return false;
+        };
+        info!("{} confirmed", pending.code);
+        self.perform_command(&pending, context)
+    }
+
+    /// The safety policy of a command.
+    fn safety_policy(&self, code: &str) -> SafetyPolicy {
+        self.voice_command_store
+            .as_ref()
+            .map(|store| store.safety(code))
+            .unwrap_or_default()
+    }
+
+    /// The phrase confirming a command, for feedback.
+    fn confirm_phrase(&self) -> String {
+        let confirm = builtin_commands()
+            .iter()
+            .find(|d| d.action == CommandAction::Confirm)
+            .map_or("CONFIRM", |d| &d.command);
+        match &self.voice_command_store {
+            Some(store) => store.get_phrase(confirm),
+            None => confirm.to_lowercase(),
+        }
+    }
+
+    /// Run a command: streamed words held for number normalization are
+    /// typed first, punctuation is typed with smart spacing, caps commands
+    /// change capitalization, other commands first release whitespace held
+    /// back by the spacing.
+    /// Returns whether it succeeded.
+    fn perform_command(&mut self, cmd: &ResolvedCommand, context: &str) -> bool {
+        self.release_spoken_forms("Failed to inject text");
+        match &cmd.action {
+            CommandAction::Caps { mode } => {
+                debug!("Caps mode: {:?}", mode);
+                self.capitalization.set_mode(*mode);
+                self.feedback
+                    .push(FeedbackPayload::command(self.msg_ts, &cmd.code));
+                true
+            }
+            CommandAction::Punctuation { text, attach } => {
+                let before = (self.spacing.clone(), self.capitalization.clone());
+                self.capitalization.mark(text);
+                let spaced = self.spacing.punctuation(text, *attach);
+                let undo = self.undo_entry(&spaced, before);
+                let spaced = ResolvedCommand {
+                    code: cmd.code.clone(),
+                    action: CommandAction::Text { text: spaced },
+                };
+                self.execute_recorded(&spaced, context, Some(undo))
+            }
+            CommandAction::Cancel => self.execute_command(cmd, context),
+            CommandAction::Undo => self.undo(cmd, context),
+            CommandAction::DictationOnly { enabled } => {
+                info!(
+                    "Dictation-only mode {}",
+                    if *enabled { "on" } else { "off" }
+                );
+                if let Some(state) = &self.state {
+                    state.set_dictation_only(*enabled);
+                }
+                self.sync_dictation_only();
+                self.feedback
+                    .push(FeedbackPayload::command(self.msg_ts, &cmd.code));
+                true
+            }
+            CommandAction::Spelling { enabled } => {
+                info!("Spelling mode {}", if *enabled { "on" } else { "off" });
+                if !*enabled {
+                    // Separate the spelled text from the words that follow
+                    if self.spelling.take().is_some() {
+                        self.spacing.text(" ");
+                    }
+                } else if self.spelling.is_none() {
+                    // Held words were typed above, so they aren't spelled
+                    self.spelling = Some(Speller::new());
+                }
+                self.sync_spelling();
+                self.feedback
+                    .push(FeedbackPayload::command(self.msg_ts, &cmd.code));
+                true
+            }
+            _ => {
+                // The cursor may move, so typed lengths no longer apply
+                self.clear_history();
+                let held = self.spacing.flush();
+                if !held.is_empty() {
+                    self.deliver_text(&held, context, None);
+                }
+                if let CommandAction::Key { keys, .. } = &cmd.action {
+                    if keys.modifiers.is_empty() && keys.key == Key::Enter {
+                        self.capitalization.line_start();
+                    }
+                }
+                self.execute_command(cmd, context)
+            }
+        }
+    }
+
+    /// Execute a resolved command and track the outcome.
+    /// Macros and programs are started in the background; other commands are
+    /// queued while one runs, except `cancel` which stops it.
+    /// Returns whether it succeeded (queued or started counts as success).
+    fn execute_command(&mut self, cmd: &ResolvedCommand, context: &str) -> bool {
+        self.execute_recorded(cmd, context, None)
+    }
+
+    /// Execute a resolved command typing text, with `undo` going into the
+    /// undo history once the text is typed.
+    fn execute_recorded(
+        &mut self,
+        cmd: &ResolvedCommand,
+        context: &str,
+        undo: Option<UndoEntry>,
+    ) -> bool {
+        if cmd.action == CommandAction::Cancel {
+            self.cancel_background();
+            self.feedback
+                .push(FeedbackPayload::command(self.msg_ts, &cmd.code));
+            return true;
+        }
+        if self.background.is_some() {
+            debug!("Background action running, queueing command {}", cmd.code);
+            self.queued
+                .push_back((self.msg_ts, Delivery::Command(cmd.clone(), undo)));
+            return true;
+        }
+
+        let expanded = cmd.action.expand(&|code| {
+            self.active_profile()
+                .and_then(|p| p.action(code))
+                .cloned()
+                .or_else(|| self.resolve_command(code).map(|c| c.action))
+        });
+        let action = match expanded {
+            Ok(action) => action,
+            Err(e) => return self.track_injection(Err(e), context, Some(&cmd.code)),
+        };
+        let confirm = self.safety_policy(&cmd.code).confirm_on_desktop;
+        if confirm || action.runs_in_background() {
+            if let Err(e) = self.start_background(&cmd.code, action, confirm) {
+                warn!("Not running {}: {}", cmd.code, e);
+                self.feedback.push(FeedbackPayload::failed(
+                    self.msg_ts,
+                    Some(cmd.code.clone()),
+                    e.to_string(),
+                ));
+                return false;
+            }
+            return true;
+        }
+
+        let result =
+            crate::commands::execute(&action, self.injector.as_ref(), &AtomicBool::new(false));
+        let ok = self.track_injection(result, context, Some(&cmd.code));
+        if ok {
+            self.record_undo(undo);
         }
+        ok
+    }
+
+    /// Start a background action. Programs must be allowed for the
+    /// command, and are confirmed on the desktop first if requested.
+    /// Commands confirmed on the desktop by their safety policy (`confirm`)
+    /// also start here, once approved.
+    fn start_background(&mut self, code: &str, action: CommandAction, confirm: bool) -> Result<()> {
+        let programs = action.programs();
+        if let Some(program) = programs.iter().find(|p| !self.programs.is_allowed(code, p)) {
+            return Err(anyhow!("{} is not allowed to run {}", code, program));
+        }
+        if !confirm && !action.needs_confirmation() {
+            self.spawn_background(code, action);
+            return Ok(());
+        }
+
+        let Some(confirm_tx) = &self.confirm_tx else {
+            return Err(anyhow!("Confirmation is not available"));
+        };
+        if let Some(pending) = &self.confirming {
+            return Err(anyhow!(
+                "{} is waiting for confirmation on the desktop",
+                pending.code
+            ));
+        }
+        let (respond, approved) = oneshot::channel();
+        confirm_tx.send(ProgramConfirmRequest {
+            command: code.to_string(),
+            description: action.to_string(),
+            respond,
+        })?;
+        info!("{} waits for confirmation on the desktop", code);
+        self.confirming = Some(DesktopConfirmation {
+            code: code.to_string(),
+            action,
+            msg_ts: self.msg_ts,
+            approved,
+            deadline: Instant::now() + DESKTOP_CONFIRM_TIMEOUT,
+        });
+        Ok(())
+    }
+
+    /// Run a macro, program or snippet on a blocking thread so delays, slow
+    /// programs and clipboard reads don't stall event processing.
+    fn spawn_background(&mut self, code: &str, action: CommandAction) {
+        info!("Starting {} in the background", code);
+        let runs_program = !action.programs().is_empty();
+        let injector = self.injector.clone();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let task_cancel = cancel.clone();
+        let handle = tokio::task::spawn_blocking(move || {
+            crate::commands::execute(&action, injector.as_ref(), &task_cancel)
+        });
+        self.background = Some(BackgroundAction {
+            code: code.to_string(),
+            runs_program,
+            msg_ts: self.msg_ts,
+            cancel,
+            handle,
+        });
+    }
+
+    /// Start the command approved on the desktop, or report it declined
+    /// once the dialog is answered or the request times out.
+    fn check_confirmation(&mut self) {
+        let Some(pending) = &mut self.confirming else {
+            return;
+        };
+        let approved = match pending.approved.try_recv() {
+            Ok(approved) => approved,
+            Err(TryRecvError::Empty) if Instant::now() < pending.deadline => return,
+            Err(TryRecvError::Empty) => {
+                info!("{} was not confirmed on the desktop in time", pending.code);
+                false
+            }
+            Err(TryRecvError::Closed) => false,
+        };
+        let Some(pending) = self.confirming.take() else {
+            return;
+        };
+
+        if !approved {
+            info!("{} declined on the desktop", pending.code);
+            self.feedback.push(FeedbackPayload::failed(
+                pending.msg_ts,
+                Some(pending.code),
+                "Declined on the desktop",
+            ));
+        } else if self.background.is_some() {
+            debug!("Background action running, queueing {}", pending.code);
+            self.queued.push_back((
+                pending.msg_ts,
+                Delivery::Approved(pending.code, pending.action),
+            ));
+        } else {
+            self.with_msg_ts(pending.msg_ts, |p| {
+                p.spawn_background(&pending.code, pending.action)
+            });
+        }
+    }
+
+    /// Ask the running macro or program, if any, to stop, and withdraw the
+    /// request waiting for approval on the desktop.
+    fn cancel_background(&mut self) {
+        if let Some(pending) = self.confirming.take() {
+            info!(
+                "{} no longer waits for confirmation on the desktop",
+                pending.code
+            );
+            self.feedback.push(FeedbackPayload::failed(
+                pending.msg_ts,
+                Some(pending.code),
+                "Cancelled",
+            ));
+        }
+        match &self.background {
+            Some(running) => {
+                info!("Cancelling {}", running.code);
+                running.cancel.store(true, Ordering::Relaxed);
+            }
+            None => debug!("Cancel received with nothing running"),
+        }
+    }
+
+    /// Report a finished background action and deliver the input queued behind it.
+    async fn finish_background(&mut self) {
+        if !self
+            .background
+            .as_ref()
+            .is_some_and(|m| m.handle.is_finished())
+        {
+            return;
+        }
+        let Some(running) = self.background.take() else {
+            return;
+        };
+        let result = running
+            .handle
+            .await
+            .unwrap_or_else(|e| Err(anyhow!("Background task failed: {}", e)));
+
+        // Feedback refers to the message that started the action, then to
+        // each queued message in turn
+        let current_ts = std::mem::replace(&mut self.msg_ts, running.msg_ts);
+        match result {
+            // Cancelled or failing programs are not injection failures
+            Err(e) if e.is::<MacroCancelled>() || running.runs_program => {
+                info!("{} did not complete: {:#}", running.code, e);
+                let exit_code = e.downcast_ref::<ProgramFailed>().and_then(|p| p.exit_code);
+                self.feedback.push(
+                    FeedbackPayload::failed(self.msg_ts, Some(running.code), format!("{:#}", e))
+                        .with_exit_code(exit_code),
+                );
+            }
+            result => {
+                if self.track_injection(result, "Macro failed", Some(&running.code)) {
+                    debug!("{} finished", running.code);
+                    if running.runs_program {
+                        if let Some(feedback) = self.feedback.last_mut() {
+                            feedback.exit_code = Some(0);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Stop draining if a queued command starts another background action
+        while self.background.is_none() {
+            let Some((msg_ts, delivery)) = self.queued.pop_front() else {
+                break;
+            };
+            self.msg_ts = msg_ts;
+            match delivery {
+                Delivery::Text(text, undo) => {
+                    self.deliver_text(&text, "Failed to inject queued text", undo);
+                }
+                Delivery::Command(cmd, undo) => {
+                    self.execute_recorded(&cmd, "Failed to execute queued command", undo);
+                }
+                Delivery::Approved(code, action) => self.spawn_background(&code, action),
+            }
+        }
+        self.msg_ts = current_ts;
     }
 
     /// Process a single event.
     pub async fn process_event(&mut self, event: ConnectionEvent) -> Result<()> {
+        if self.is_paused()
+            && matches!(
+                event,
+                ConnectionEvent::TextReceived { .. }
+                    | ConnectionEvent::WordReceived { .. }
+                    | ConnectionEvent::CommandReceived { .. }
+            )
+        {
+            debug!("Dictation paused, ignoring input");
+            return Ok(());
+        }
+        self.sync_dictation_only();
+        self.sync_confirming();
+        if matches!(
+            event,
+            ConnectionEvent::TextReceived { .. }
+                | ConnectionEvent::WordReceived { .. }
+                | ConnectionEvent::CommandReceived { .. }
+        ) {
+            self.update_profile();
+        }
+
         match event {
-            ConnectionEvent::TextReceived(text) => {
+            ConnectionEvent::TextReceived { text, msg_ts } => {
+                self.msg_ts = msg_ts;
+                self.utterance = UtteranceKey::Text(msg_ts);
                 self.handle_text(&text).await?;
             }
-            ConnectionEvent::WordReceived { word, seq, session } => {
+            ConnectionEvent::WordReceived {
+                word,
+                seq,
+                session,
+                ts,
+                msg_ts,
+            } => {
+                // A pause between words ends the utterance, along with the
+                // words held from before it
+                if self
+                    .last_word_at
+                    .is_none_or(|at| at.elapsed() > LOOK_AHEAD_TIMEOUT)
+                {
+                    for item in self.flush_pending_words() {
+                        self.process_item(item).await?;
+                    }
+                    self.word_batch += 1;
+                }
+                self.last_word_at = Some(Instant::now());
+                self.msg_ts = msg_ts;
+                self.diagnostics.record_word();
+                if let Some(ts) = ts {
+                    self.word_sent_at.push_back((msg_ts, ts));
+                }
+                self.utterance = UtteranceKey::Words(session.clone(), self.word_batch);
                 self.handle_word(&word, seq, &session).await?;
             }
-            ConnectionEvent::CommandReceived(cmd) => {
-                self.handle_command(&cmd).await?;
+            ConnectionEvent::CommandReceived { command, msg_ts } => {
+                self.msg_ts = msg_ts;
+                self.handle_command(&command).await?;
             }
-            ConnectionEvent::Connected { device_name } => {
+            ConnectionEvent::Connected { device_name, .. } => {
                 info!("Device connected: {}", device_name);
                 // Reset word buffer state for the new connection to prevent
                 // stale session/sequence state from blocking words
                 self.word_buffer.reset();
+                self.spacing.reset();
+                self.capitalization.reset();
+                self.spoken_forms.clear();
+                self.clear_history();
+                self.word_sent_at.clear();
+                self.spelling = None;
+                self.sync_spelling();
+                self.safety.reset();
+                self.confirming = None;
                 info!("Word buffer reset for new connection");
+                self.announced_language = None;
+                self.apply_language();
+            }
+            ConnectionEvent::LanguageAnnounced { language } => {
+                self.announced_language = Some(language);
+                self.apply_language();
             }
             ConnectionEvent::Disconnected => {
                 info!("Device disconnected");
@@ -100,27 +1212,42 @@ impl EventProcessor {
         Ok(())
     }
 
+    /// Save text as a phrase for the command being recorded. Returns
+    /// `false` if not recording. A phrase conflicting with another
+    /// command's phrases is rejected and recording continues, so another
+    /// one can be said.
+    fn record_phrase(&self, text: &str) -> bool {
+        let (Some(state), Some(store)) = (&self.state, &self.voice_command_store) else {
+            return false;
+        };
+        let Some(command) = state.get_recording_command() else {
+            return false;
+        };
+
+        info!("Recording phrase '{}' for command '{}'", text, command);
+        match store.add_phrase(&command, text) {
+            Ok(()) => info!(
+                "Successfully saved phrase '{}' for command '{}'",
+                text, command
+            ),
+            Err(e) if e.is::<PhraseConflict>() => {
+                warn!("Rejected phrase for '{}': {}", command, e);
+                state.set_recording_error(e.to_string());
+                return true;
+            }
+            Err(e) => error!("Failed to save phrase: {}", e),
+        }
+        state.stop_recording();
+        true
+    }
+
     /// Handle received text.
     async fn handle_text(&mut self, text: &str) -> Result<()> {
         debug!("Processing text: {} chars", text.len());
 
         // Check if we're in recording mode
-        if let (Some(state), Some(store)) = (&self.state, &self.voice_command_store) {
-            if let Some(command) = state.get_recording_command() {
-                // We're recording - save this text as the phrase for the command
-                info!("Recording phrase '{}' for command '{}'", text, command);
-                if let Err(e) = store.set_phrase(&command, text) {
-                    error!("Failed to save phrase: {}", e);
-                } else {
-                    info!(
-                        "Successfully saved phrase '{}' for command '{}'",
-                        text, command
-                    );
-                }
-                // Stop recording mode
-                state.stop_recording();
-                return Ok(());
-            }
+        if self.record_phrase(text) {
+            return Ok(());
         }
 
         // Check if this text matches a voice command phrase (with context support)
@@ -128,10 +1255,8 @@ impl EventProcessor {
             match matcher.match_with_context(text) {
                 MatchResult::ExactCommand(voice_cmd) => {
                     // Entire text is a command
-                    info!("Text '{}' matched voice command: {:?}", text, voice_cmd);
-                    if let Err(e) = crate::commands::execute(&voice_cmd, self.injector.as_ref()) {
-                        error!("Failed to execute voice command: {}", e);
-                    }
+                    info!("Text '{}' matched voice command: {}", text, voice_cmd.code);
+                    self.run_command(&voice_cmd, "Failed to execute voice command");
                     return Ok(());
                 }
                 MatchResult::MidTextCommand(segments) => {
@@ -144,17 +1269,11 @@ impl EventProcessor {
                         match segment {
                             TextSegment::Text(text_part) => {
                                 debug!("Typing text segment: {} chars", text_part.len());
-                                if let Err(e) = self.injector.type_text(&text_part) {
-                                    error!("Failed to inject text segment: {}", e);
-                                }
+                                self.type_dictated(&text_part, "Failed to inject text segment");
                             }
                             TextSegment::Command(cmd) => {
-                                debug!("Executing command segment: {:?}", cmd);
-                                if let Err(e) =
-                                    crate::commands::execute(&cmd, self.injector.as_ref())
-                                {
-                                    error!("Failed to execute command segment: {}", e);
-                                }
+                                debug!("Executing command segment: {}", cmd.code);
+                                self.run_command(&cmd, "Failed to execute command segment");
                             }
                         }
                     }
@@ -169,9 +1288,7 @@ impl EventProcessor {
 
         // Inject text (no command match)
         debug!("Injecting text into active window: {} chars", text.len());
-        if let Err(e) = self.injector.type_text(text) {
-            error!("Failed to inject text: {}", e);
-        } else {
+        if self.type_dictated(text, "Failed to inject text") {
             debug!("Text injection successful");
         }
 
@@ -183,12 +1300,15 @@ impl EventProcessor {
         debug!("Processing command: {}", cmd);
 
         // Parse and execute command
-        if let Some(voice_cmd) = VoiceCommand::parse(cmd) {
-            if let Err(e) = crate::commands::execute(&voice_cmd, self.injector.as_ref()) {
-                error!("Failed to execute command: {}", e);
-            }
+        if let Some(voice_cmd) = self.resolve_command(cmd) {
+            self.run_command(&voice_cmd, "Failed to execute command");
         } else {
             warn!("Unknown command: {}", cmd);
+            self.feedback.push(FeedbackPayload::failed(
+                self.msg_ts,
+                Some(cmd.to_string()),
+                "Unknown command",
+            ));
         }
 
         Ok(())
@@ -201,30 +1321,12 @@ impl EventProcessor {
             word, seq, session
         );
 
-        // Create closures for the matcher functions
-        let matcher = self.matcher.as_ref();
-
-        let single_word_matcher =
-            |w: &str| -> Option<String> { matcher.and_then(|m| m.match_single_word(w)) };
-
-        let two_word_matcher = |w1: &str, w2: &str| -> Option<String> {
-            matcher.and_then(|m| m.match_two_words(w1, w2))
-        };
-
-        let could_start = |w: &str| -> bool {
-            matcher
-                .map(|m| m.could_start_two_word_command(w))
-                .unwrap_or(false)
-        };
+        let phrases = self.phrases();
 
         // Process through buffer (seq is ignored, kept for logging only)
-        let items = self.word_buffer.process_word(
-            word.to_string(),
-            session,
-            &single_word_matcher,
-            &two_word_matcher,
-            &could_start,
-        );
+        let items = self
+            .word_buffer
+            .process_word(word.to_string(), self.msg_ts, session, &phrases);
 
         // Process each item
         for item in items {
@@ -234,35 +1336,47 @@ impl EventProcessor {
         Ok(())
     }
 
-    /// Process a single item from the word buffer.
+    /// Process a single item from the word buffer. Feedback refers to the
+    /// message the item came from, which may precede the current one if
+    /// the item waited for look-ahead.
     async fn process_item(&mut self, item: ProcessedItem) -> Result<()> {
         match item {
-            ProcessedItem::Text(text) => {
+            ProcessedItem::Text(text, msg_ts) => {
                 // Check recording mode
-                if let (Some(state), Some(store)) = (&self.state, &self.voice_command_store) {
-                    if let Some(command) = state.get_recording_command() {
-                        info!("Recording word '{}' for command '{}'", text.trim(), command);
-                        if let Err(e) = store.set_phrase(&command, text.trim()) {
-                            error!("Failed to save phrase: {}", e);
-                        }
-                        state.stop_recording();
-                        return Ok(());
-                    }
+                if self.record_phrase(text.trim()) {
+                    return Ok(());
                 }
 
-                // Type the text (includes trailing space)
-                if let Err(e) = self.injector.type_text(&text) {
-                    error!("Failed to inject text: {}", e);
-                } else {
+                // Type the text (the trailing space is held back by the spacing)
+                if self.with_msg_ts(msg_ts, |p| p.type_word(&text, "Failed to inject text")) {
                     debug!("Word delivered: '{}' -> typed", text.trim());
                 }
             }
-            ProcessedItem::Command(cmd_code) => {
+            ProcessedItem::Command(cmd_code, msg_ts) => {
                 debug!("Executing command from word buffer: {}", cmd_code);
-                if let Some(cmd) = VoiceCommand::parse(&cmd_code) {
-                    if let Err(e) = crate::commands::execute(&cmd, self.injector.as_ref()) {
-                        error!("Failed to execute command: {}", e);
-                    } else {
+                if let Some(cmd) = self.resolve_command(&cmd_code) {
+                    if self
+                        .with_msg_ts(msg_ts, |p| p.run_command(&cmd, "Failed to execute command"))
+                    {
+                        debug!("Command delivered: {} -> executed", cmd_code);
+                    }
+                }
+            }
+            ProcessedItem::Repeated(cmd_code, count, msg_ts) => {
+                debug!(
+                    "Executing command from word buffer: {} x{}",
+                    cmd_code, count
+                );
+                let repeated = self.resolve_command(&cmd_code).and_then(|cmd| {
+                    Some(ResolvedCommand {
+                        action: cmd.action.repeated(count)?,
+                        code: cmd.code,
+                    })
+                });
+                if let Some(cmd) = repeated {
+                    if self
+                        .with_msg_ts(msg_ts, |p| p.run_command(&cmd, "Failed to execute command"))
+                    {
                         debug!("Command delivered: {} -> executed", cmd_code);
                     }
                 }
@@ -274,21 +1388,491 @@ impl EventProcessor {
     /// Flush any pending words that have timed out.
     /// Call this periodically to ensure words aren't stuck in the look-ahead buffer.
     pub fn flush_pending_words(&mut self) -> Vec<ProcessedItem> {
-        let matcher = self.matcher.as_ref();
-        let single_word_matcher =
-            |w: &str| -> Option<String> { matcher.and_then(|m| m.match_single_word(w)) };
-        self.word_buffer.flush_pending(&single_word_matcher)
+        self.sync_dictation_only();
+        self.sync_confirming();
+        if !self.word_buffer.has_pending() {
+            return Vec::new();
+        }
+        let phrases = self.phrases();
+        self.word_buffer.flush_pending(&phrases)
+    }
+
+    /// The current command phrases, empty without a matcher.
+    fn phrases(&self) -> Arc<PhraseTrie> {
+        self.matcher
+            .as_ref()
+            .map(|m| m.phrases())
+            .unwrap_or_default()
     }
 
     /// Process all pending flushes and return items ready for processing.
     /// This combines flush_pending and flush_stale for convenience.
     pub async fn process_periodic_flush(&mut self) -> Result<()> {
+        self.finish_background().await;
+        self.check_confirmation();
+
         // Flush look-ahead pending words (100ms timeout is built into the buffer)
         let pending_items = self.flush_pending_words();
         for item in pending_items {
             self.process_item(item).await?;
         }
 
+        // Release words held for a spoken number nobody continued
+        if let Some((msg_ts, text)) = self.spoken_forms.take_expired() {
+            self.with_msg_ts(msg_ts, |p| p.type_dictated(&text, "Failed to inject text"));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Modifier;
+    use parking_lot::Mutex;
+
+    /// Injector that records what it types and presses.
+    struct RecordingInjector(Arc<Mutex<Vec<String>>>);
+
+    impl InputInjector for RecordingInjector {
+        fn backend_name(&self) -> &'static str {
+            "Test"
+        }
+
+        fn type_text(&self, text: &str) -> Result<()> {
+            self.0.lock().push(format!("type {}", text));
+            Ok(())
+        }
+
+        fn press_key(&self, key: Key) -> Result<()> {
+            self.0.lock().push(format!("key {:?}", key));
+            Ok(())
+        }
+
+        fn key_combo(&self, modifiers: &[Modifier], key: Key) -> Result<()> {
+            self.0
+                .lock()
+                .push(format!("combo {:?} {:?}", modifiers, key));
+            Ok(())
+        }
+    }
+
+    /// Focus provider reporting a fixed window class.
+    struct FixedFocus(&'static str);
+
+    impl FocusProvider for FixedFocus {
+        fn name(&self) -> &'static str {
+            "Test"
+        }
+
+        fn focused_window(&self) -> Result<Option<FocusedWindow>> {
+            Ok(Some(FocusedWindow {
+                class: self.0.to_string(),
+                title: String::new(),
+            }))
+        }
+    }
+
+    /// Query the focused window now and wait for the answer to apply.
+    async fn check_focus(processor: &mut EventProcessor) {
+        processor.focus_checked = None;
+        processor.update_profile();
+        while processor.focus_query.is_some() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            processor.update_profile();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_app_profiles() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = EventProcessor::new(Box::new(RecordingInjector(calls.clone())));
+        let profiles: ProfilesConfig = toml::from_str(
+            r#"
+            [[apps]]
+            name = "Terminal"
+            class = ["kitty"]
+            disable = ["CUT"]
+            auto_capitalize = false
+
+            [apps.commands]
+            COPY = { type = "key", keys = "ctrl+shift+c" }
+            "#,
+        )
+        .unwrap();
+        let copy = processor.resolve_command("COPY").unwrap();
+        let cut = processor.resolve_command("CUT").unwrap();
+
+        processor.set_profiles(profiles.clone(), Some(Box::new(FixedFocus("kitty"))));
+        check_focus(&mut processor).await;
+        assert!(processor.type_dictated("hello", "test"));
+        assert!(processor.run_command(&copy, "test"));
+        assert!(!processor.run_command(&cut, "test"));
+        assert_eq!(
+            *calls.lock(),
+            vec![
+                "type hello".to_string(),
+                format!(
+                    "combo {:?} {:?}",
+                    [Modifier::Ctrl, Modifier::Shift],
+                    Key::Letter('c')
+                ),
+            ]
+        );
+
+        // Other applications keep the defaults
+        calls.lock().clear();
+        processor.set_profiles(profiles, Some(Box::new(FixedFocus("firefox"))));
+        check_focus(&mut processor).await;
+        assert!(processor.run_command(&cut, "test"));
+        assert_eq!(
+            *calls.lock(),
+            vec![format!(
+                "combo {:?} {:?}",
+                [Modifier::Ctrl],
+                Key::Letter('x')
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_safety_policies() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{
+                "version": 3,
+                "mappings": [],
+                "commands": [
+                    {"command": "CUT", "phrases": ["cut"],
+                     "action": {"type": "key", "keys": "ctrl+x"},
+                     "safety": {"confirm_secs": 5, "cooldown_secs": 60}}
+                ]
+            }"#,
+        )?;
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path())?);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = EventProcessor::with_voice_commands(
+            Box::new(RecordingInjector(calls.clone())),
+            store,
+            AppState::new(),
+        );
+        let cut = processor.resolve_command("CUT").unwrap();
+        let confirm = processor.resolve_command("CONFIRM").unwrap();
+        let cancel = processor.resolve_command("CANCEL").unwrap();
+
+        // Held back until confirmed
+        assert!(!processor.run_command(&cut, "test"));
+        assert!(calls.lock().is_empty());
+        assert_eq!(
+            processor.feedback.last().unwrap().error.as_deref(),
+            Some("Say \"confirm\" within 5 s to run it")
+        );
+        processor.run_command(&cancel, "test");
+        assert!(!processor.run_command(&confirm, "test"));
+
+        processor.run_command(&cut, "test");
+        assert!(processor.run_command(&confirm, "test"));
+        assert_eq!(calls.lock().len(), 1);
+
+        // Then cooling down
+        assert!(!processor.run_command(&cut, "test"));
+        assert!(!processor.run_command(&confirm, "test"));
+        assert_eq!(calls.lock().len(), 1);
+
+        // The confirm phrase is a command only while a command waits for it
+        let text = |text: &str| ConnectionEvent::TextReceived {
+            text: text.to_string(),
+            msg_ts: 0,
+        };
+        processor.safety.reset();
+        calls.lock().clear();
+        processor.process_event(text("confirm")).await?;
+        assert_eq!(*calls.lock(), vec!["type confirm".to_string()]);
+        processor.process_event(text("cut")).await?;
+        processor.process_event(text("confirm")).await?;
+        assert_eq!(calls.lock().len(), 2);
+        Ok(())
+    }
+
+    /// Injector failing to type text while `failing` is set.
+    struct FlakyInjector {
+        calls: Arc<Mutex<Vec<String>>>,
+        failing: Arc<AtomicBool>,
+    }
+
+    impl InputInjector for FlakyInjector {
+        fn backend_name(&self) -> &'static str {
+            "Test"
+        }
+
+        fn type_text(&self, text: &str) -> Result<()> {
+            if self.failing.load(Ordering::Relaxed) {
+                return Err(anyhow!("typing failed"));
+            }
+            self.calls.lock().push(format!("type {}", text));
+            Ok(())
+        }
+
+        fn press_key(&self, key: Key) -> Result<()> {
+            self.calls.lock().push(format!("key {:?}", key));
+            Ok(())
+        }
+
+        fn key_combo(&self, _modifiers: &[Modifier], _key: Key) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Type "hello" in a message while a background action runs, then
+    /// finish the action so the queued text is typed.
+    async fn queue_text(processor: &mut EventProcessor, msg_ts: u64) {
+        processor.background = Some(BackgroundAction {
+            code: "MACRO".to_string(),
+            runs_program: false,
+            msg_ts: 0,
+            cancel: Arc::new(AtomicBool::new(false)),
+            handle: tokio::spawn(async { Ok(()) }),
+        });
+        processor.utterance = UtteranceKey::Text(msg_ts);
+        assert!(processor.type_dictated("hello", "test"));
+        while !processor.background.as_ref().unwrap().handle.is_finished() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        processor.finish_background().await;
+    }
+
+    #[tokio::test]
+    async fn test_undo_queued_text() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let failing = Arc::new(AtomicBool::new(false));
+        let mut processor = EventProcessor::new(Box::new(FlakyInjector {
+            calls: calls.clone(),
+            failing: failing.clone(),
+        }));
+        let scratch = processor.resolve_command("SCRATCH_THAT").unwrap();
+
+        // Text that failed to type after the action isn't undone
+        failing.store(true, Ordering::Relaxed);
+        queue_text(&mut processor, 1).await;
+        failing.store(false, Ordering::Relaxed);
+        assert!(!processor.run_command(&scratch, "test"));
+        assert!(calls.lock().is_empty());
+
+        // Text typed after it is
+        queue_text(&mut processor, 2).await;
+        assert_eq!(*calls.lock(), vec!["type hello".to_string()]);
+        calls.lock().clear();
+        assert!(processor.run_command(&scratch, "test"));
+        assert_eq!(calls.lock().len(), 5);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_snippet_runs_in_background() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = EventProcessor::new(Box::new(RecordingInjector(calls.clone())));
+        let snippet = ResolvedCommand {
+            code: "SIGN_OFF".to_string(),
+            action: CommandAction::Snippet {
+                text: "Best regards\n".to_string(),
+            },
+        };
+
+        // Text dictated meanwhile is typed after the snippet
+        assert!(processor.run_command(&snippet, "test"));
+        assert!(processor.background.is_some());
+        assert!(processor.type_dictated("hello", "test"));
+        while !processor.background.as_ref().unwrap().handle.is_finished() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        processor.finish_background().await;
+        assert_eq!(
+            *calls.lock(),
+            vec!["type Best regards\n".to_string(), "type hello".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undo_after_pause() -> Result<()> {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = EventProcessor::new(Box::new(RecordingInjector(calls.clone())));
+        let scratch = processor.resolve_command("SCRATCH_THAT").unwrap();
+        let backspaces = |calls: &Arc<Mutex<Vec<String>>>| {
+            let backspaces = calls
+                .lock()
+                .iter()
+                .filter(|c| *c == &format!("key {:?}", Key::Backspace))
+                .count();
+            calls.lock().clear();
+            backspaces
+        };
+
+        for (i, word) in ["hello", "world", "again"].into_iter().enumerate() {
+            if word == "again" {
+                processor.last_word_at = Some(Instant::now() - Duration::from_millis(200));
+            }
+            processor
+                .process_event(ConnectionEvent::WordReceived {
+                    word: word.to_string(),
+                    seq: Some(i as u64),
+                    session: "s1".to_string(),
+                    ts: None,
+                    msg_ts: i as u64,
+                })
+                .await?;
+        }
+        calls.lock().clear();
+
+        // " again" came after a pause, "Hello world" without one
+        assert!(processor.run_command(&scratch, "test"));
+        assert_eq!(backspaces(&calls), 6);
+        assert!(processor.run_command(&scratch, "test"));
+        assert_eq!(backspaces(&calls), 11);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_word_latency_after_injection() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path())?);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = EventProcessor::with_voice_commands(
+            Box::new(RecordingInjector(calls.clone())),
+            store,
+            AppState::new(),
+        );
+
+        // "new" may start "new line", so it isn't typed yet
+        let sent_at = chrono::Utc::now().timestamp_millis() as u64;
+        processor
+            .process_event(ConnectionEvent::WordReceived {
+                word: "new".to_string(),
+                seq: Some(0),
+                session: "s1".to_string(),
+                ts: Some(sent_at),
+                msg_ts: 1,
+            })
+            .await?;
+        let stats = processor.diagnostics.snapshot();
+        assert_eq!(stats.words, 1);
+        assert_eq!(stats.word_latency.count, 0);
+
+        tokio::time::sleep(LOOK_AHEAD_TIMEOUT).await;
+        for item in processor.flush_pending_words() {
+            processor.process_item(item).await?;
+        }
+        assert_eq!(*calls.lock(), vec!["type new".to_string()]);
+        assert_eq!(processor.diagnostics.snapshot().word_latency.count, 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_spelling_words() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path())?);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = EventProcessor::with_voice_commands(
+            Box::new(RecordingInjector(calls.clone())),
+            store,
+            AppState::new(),
+        );
+        processor.set_normalize_numbers(true);
+        processor.set_language("en");
+
+        // The held number is typed before spelling starts, the spelled
+        // characters without spaces, and a space after spelling stops
+        let words = "two hundred start spelling alpha bravo 7 stop spelling done";
+        for (i, word) in words.split_whitespace().enumerate() {
+            processor
+                .process_event(ConnectionEvent::WordReceived {
+                    word: word.to_string(),
+                    seq: Some(i as u64),
+                    session: "s1".to_string(),
+                    ts: None,
+                    msg_ts: i as u64,
+                })
+                .await?;
+        }
+        for item in processor.flush_pending_words() {
+            processor.process_item(item).await?;
+        }
+        processor.release_spoken_forms("test");
+
+        let typed: String = calls
+            .lock()
+            .iter()
+            .filter_map(|call| call.strip_prefix("type "))
+            .collect();
+        assert_eq!(typed, "200 ab7 done");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_desktop_confirmation() -> Result<()> {
+        let temp_dir = tempfile::TempDir::new()?;
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{
+                "version": 3,
+                "mappings": [],
+                "commands": [
+                    {"command": "CUT", "phrases": ["cut"],
+                     "action": {"type": "key", "keys": "ctrl+x"},
+                     "safety": {"confirm_on_desktop": true}}
+                ]
+            }"#,
+        )?;
+        let store = Arc::new(VoiceCommandStore::new(temp_dir.path())?);
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let mut processor = EventProcessor::with_voice_commands(
+            Box::new(RecordingInjector(calls.clone())),
+            store,
+            AppState::new(),
+        );
+        let (confirm_tx, mut confirm_rx) = mpsc::unbounded_channel();
+        processor.set_confirm_sender(confirm_tx);
+        let cut = processor.resolve_command("CUT").unwrap();
+        let cancel = processor.resolve_command("CANCEL").unwrap();
+        let last_error = |processor: &EventProcessor| processor.feedback.last()?.error.clone();
+
+        // Dictation goes on while the dialog is open
+        assert!(processor.run_command(&cut, "test"));
+        let request = confirm_rx.try_recv()?;
+        assert!(processor.type_dictated("hello", "test"));
+        assert_eq!(*calls.lock(), vec!["type hello".to_string()]);
+
+        request.respond.send(true).unwrap();
+        processor.process_periodic_flush().await?;
+        while processor.background.is_some() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            processor.process_periodic_flush().await?;
+        }
+        assert_eq!(calls.lock().len(), 2);
+
+        // Cancel withdraws the request
+        processor.run_command(&cut, "test");
+        let request = confirm_rx.try_recv()?;
+        processor.run_command(&cancel, "test");
+        assert!(request.respond.is_closed());
+        assert!(processor
+            .feedback
+            .iter()
+            .any(|f| f.error.as_deref() == Some("Cancelled")));
+
+        // An unanswered request times out as declined
+        processor.run_command(&cut, "test");
+        if let Some(pending) = &mut processor.confirming {
+            pending.deadline = Instant::now();
+        }
+        processor.process_periodic_flush().await?;
+        assert!(processor.confirming.is_none());
+        assert_eq!(
+            last_error(&processor).as_deref(),
+            Some("Declined on the desktop")
+        );
+        assert_eq!(calls.lock().len(), 2);
         Ok(())
     }
 }
@@ -0,0 +1,246 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Focused window detection, for per-application profiles.
+
+use anyhow::{anyhow, Context, Result};
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+use super::injector::DisplayServer;
+
+/// The application window with keyboard focus.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FocusedWindow {
+    /// Application class, e.g. "kitty" (X11 `WM_CLASS`).
+    pub class: String,
+    /// Window title.
+    pub title: String,
+}
+
+/// Source of the focused window for a desktop environment.
+pub trait FocusProvider: Send + Sync {
+    /// Get the provider name (e.g., "X11").
+    fn name(&self) -> &'static str;
+
+    /// The focused window, `None` if no window has focus.
+    fn focused_window(&self) -> Result<Option<FocusedWindow>>;
+}
+
+/// Focused window from the X11 `_NET_ACTIVE_WINDOW` property, read with
+/// `xprop`.
+pub struct X11FocusProvider;
+
+impl FocusProvider for X11FocusProvider {
+    fn name(&self) -> &'static str {
+        "X11 (xprop)"
+    }
+
+    fn focused_window(&self) -> Result<Option<FocusedWindow>> {
+        let root = run("xprop", &["-root", "_NET_ACTIVE_WINDOW"])?;
+        let Some(id) = parse_active_window(&root) else {
+            return Ok(None);
+        };
+        let properties = run(
+            "xprop",
+            &["-id", &id, "WM_CLASS", "_NET_WM_NAME", "WM_NAME"],
+        )?;
+        Ok(Some(parse_window_properties(&properties)))
+    }
+}
+
+/// Focused window printed by a user-supplied program: the class on the
+/// first line and the title on the second, for environments without a
+/// built-in provider (e.g. a script around `swaymsg` or `hyprctl`).
+pub struct CommandFocusProvider {
+    program: String,
+    args: Vec<String>,
+}
+
+impl CommandFocusProvider {
+    /// Create a provider running `command` (program and arguments).
+    pub fn new(command: &[String]) -> Option<Self> {
+        let (program, args) = command.split_first()?;
+        Some(Self {
+            program: program.clone(),
+            args: args.to_vec(),
+        })
+    }
+}
+
+impl FocusProvider for CommandFocusProvider {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn focused_window(&self) -> Result<Option<FocusedWindow>> {
+        let args: Vec<&str> = self.args.iter().map(String::as_str).collect();
+        let output = run(&self.program, &args)?;
+        let mut lines = output.lines().map(str::trim);
+        let class = lines.next().unwrap_or_default();
+        if class.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(FocusedWindow {
+            class: class.to_string(),
+            title: lines.next().unwrap_or_default().to_string(),
+        }))
+    }
+}
+
+/// Create a focused window provider: the configured command if any, or
+/// the built-in one for the display server. Returns `None` if there is no
+/// way to detect the focused window.
+pub fn create_focus_provider(command: &[String]) -> Option<Box<dyn FocusProvider>> {
+    if let Some(provider) = CommandFocusProvider::new(command) {
+        info!("Focused window from: {}", provider.program);
+        return Some(Box::new(provider));
+    }
+    match DisplayServer::detect() {
+        DisplayServer::X11 => Some(Box::new(X11FocusProvider)),
+        _ => {
+            info!(
+                "No focused window detection for this display server; set profiles.focus_command"
+            );
+            None
+        }
+    }
+}
+
+/// How long a focus query program may run before it is killed.
+const FOCUS_QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often a running focus query program is checked for exit.
+const FOCUS_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Run a program and return its standard output, killing it after
+/// [`FOCUS_QUERY_TIMEOUT`].
+fn run(program: &str, args: &[&str]) -> Result<String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to run {}", program))?;
+
+    let deadline = Instant::now() + FOCUS_QUERY_TIMEOUT;
+    while child.try_wait()?.is_none() {
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(anyhow!(
+                "{} timed out after {} s",
+                program,
+                FOCUS_QUERY_TIMEOUT.as_secs()
+            ));
+        }
+        thread::sleep(FOCUS_POLL_INTERVAL);
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow!("{} failed: {}", program, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The window id in `xprop -root _NET_ACTIVE_WINDOW` output
+/// (`_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007`). No window has
+/// focus if it is 0.
+fn parse_active_window(output: &str) -> Option<String> {
+    let id = output.rsplit('#').next()?.trim();
+    let id = id.split([',', ' ']).next()?;
+    let valid = id.starts_with("0x") && u64::from_str_radix(&id[2..], 16).is_ok_and(|n| n != 0);
+    valid.then(|| id.to_string())
+}
+
+/// The class and title in `xprop -id` output. `WM_CLASS` holds the
+/// instance and the class name; `_NET_WM_NAME` is preferred over the
+/// older `WM_NAME` for the title.
+fn parse_window_properties(output: &str) -> FocusedWindow {
+    let mut window = FocusedWindow::default();
+    let mut legacy_title = String::new();
+    for line in output.lines() {
+        let Some((name, value)) = line.split_once(" = ") else {
+            continue;
+        };
+        let values = quoted_values(value);
+        if name.starts_with("WM_CLASS") {
+            window.class = values.last().cloned().unwrap_or_default();
+        } else if name.starts_with("_NET_WM_NAME") {
+            window.title = values.into_iter().next().unwrap_or_default();
+        } else if name.starts_with("WM_NAME") {
+            legacy_title = values.into_iter().next().unwrap_or_default();
+        }
+    }
+    if window.title.is_empty() {
+        window.title = legacy_title;
+    }
+    window
+}
+
+/// The quoted strings in an xprop value (`"kitty", "kitty"`).
+fn quoted_values(value: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '"' {
+            continue;
+        }
+        let mut current = String::new();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => current.extend(chars.next()),
+                '"' => break,
+                c => current.push(c),
+            }
+        }
+        values.push(current);
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_xprop_output() {
+        assert_eq!(
+            parse_active_window("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007\n"),
+            Some("0x3a00007".to_string())
+        );
+        assert_eq!(
+            parse_active_window("_NET_ACTIVE_WINDOW(WINDOW): window id # 0x0"),
+            None
+        );
+        assert_eq!(parse_active_window("_NET_ACTIVE_WINDOW:  not found."), None);
+
+        let window = parse_window_properties(
+            "WM_CLASS(STRING) = \"kitty\", \"Kitty\"\n\
+             _NET_WM_NAME(UTF8_STRING) = \"vim \\\"notes.txt\\\"\"\n\
+             WM_NAME(STRING) = \"vim\"\n",
+        );
+        assert_eq!(window.class, "Kitty");
+        assert_eq!(window.title, "vim \"notes.txt\"");
+
+        let window = parse_window_properties(
+            "WM_CLASS(STRING) = \"xterm\", \"XTerm\"\n\
+             _NET_WM_NAME:  not found.\n\
+             WM_NAME(STRING) = \"bash\"\n",
+        );
+        assert_eq!(window.title, "bash");
+    }
+}
@@ -14,11 +14,12 @@
 
 //! Text injection abstraction and factory.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use std::env;
-use tracing::{info, warn};
+use std::process::Command;
+use tracing::{debug, info, warn};
 
-use super::keys::{Key, Modifier};
+use super::keys::{Chord, Key, Modifier};
 
 #[cfg(feature = "x11")]
 use super::x11::X11Injector;
@@ -30,6 +31,11 @@ pub trait InputInjector: Send + Sync {
     /// Get the backend name (e.g., "X11", "Wayland").
     fn backend_name(&self) -> &'static str;
 
+    /// Whether the backend is usable at all (e.g., required tools installed).
+    fn is_available(&self) -> bool {
+        true
+    }
+
     /// Type text at current cursor position.
     fn type_text(&self, text: &str) -> Result<()>;
 
@@ -38,6 +44,41 @@ pub trait InputInjector: Send + Sync {
 
     /// Press a key combination (modifiers + key).
     fn key_combo(&self, modifiers: &[Modifier], key: Key) -> Result<()>;
+
+    /// Read the clipboard contents as text.
+    fn clipboard_text(&self) -> Result<String> {
+        Err(anyhow!(
+            "Clipboard not supported by the {} backend",
+            self.backend_name()
+        ))
+    }
+
+    /// Press a chord (a single key if it has no modifiers).
+    fn press_chord(&self, chord: &Chord) -> Result<()> {
+        if chord.modifiers.is_empty() {
+            self.press_key(chord.key)
+        } else {
+            self.key_combo(&chord.modifiers, chord.key)
+        }
+    }
+}
+
+/// Read the clipboard with the first clipboard tool that works.
+pub(super) fn read_clipboard(tools: &[(&str, &[&str])]) -> Result<String> {
+    for (program, args) in tools {
+        match Command::new(program).args(*args).output() {
+            Ok(output) if output.status.success() => {
+                return Ok(String::from_utf8_lossy(&output.stdout).into_owned());
+            }
+            Ok(output) => debug!("{} failed: {}", program, output.status),
+            Err(e) => debug!("Failed to run {}: {}", program, e),
+        }
+    }
+    let names: Vec<&str> = tools.iter().map(|(program, _)| *program).collect();
+    Err(anyhow!(
+        "Could not read clipboard (tried {})",
+        names.join(", ")
+    ))
 }
 
 /// Detected display server type.
@@ -12,20 +12,55 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Key and modifier definitions.
+//! Key, modifier and chord definitions.
+//!
+//! Chords are written as `+`-separated names, modifiers first:
+//! `enter`, `ctrl+c`, `ctrl+shift+t`, `super+f4`.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::str::FromStr;
 
 /// Keyboard modifiers.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Modifier {
     Ctrl,
+    Shift,
+    Alt,
+    Super,
 }
 
 impl Modifier {
+    /// Parse a modifier name (case-insensitive).
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "ctrl" | "control" => Some(Modifier::Ctrl),
+            "shift" => Some(Modifier::Shift),
+            "alt" | "option" => Some(Modifier::Alt),
+            "super" | "meta" | "win" | "cmd" => Some(Modifier::Super),
+            _ => None,
+        }
+    }
+
+    /// Canonical chord name.
+    pub fn name(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "ctrl",
+            Modifier::Shift => "shift",
+            Modifier::Alt => "alt",
+            Modifier::Super => "super",
+        }
+    }
+
     /// Get the enigo key for this modifier.
     #[cfg(feature = "x11")]
     pub fn to_enigo(self) -> enigo::Key {
         match self {
             Modifier::Ctrl => enigo::Key::Control,
+            Modifier::Shift => enigo::Key::Shift,
+            Modifier::Alt => enigo::Key::Alt,
+            Modifier::Super => enigo::Key::Meta,
         }
     }
 
@@ -33,42 +68,305 @@ impl Modifier {
     pub fn to_ydotool(self) -> &'static str {
         match self {
             Modifier::Ctrl => "LEFTCTRL",
+            Modifier::Shift => "LEFTSHIFT",
+            Modifier::Alt => "LEFTALT",
+            Modifier::Super => "LEFTMETA",
         }
     }
 }
 
-/// Special keys.
+/// Keys that can be pressed, alone or in a chord.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Key {
-    A,
-    C,
-    V,
-    X,
+    /// Letter key, `'a'..='z'`.
+    Letter(char),
+    /// Digit key on the main row, `0..=9`.
+    Digit(u8),
+    /// Function key, `1..=24`.
+    F(u8),
+    // Editing
     Enter,
+    Tab,
+    Space,
+    Backspace,
+    Delete,
+    Insert,
+    Escape,
+    // Navigation
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    // System
+    PrintScreen,
+    Pause,
+    CapsLock,
+    // Media
+    VolumeUp,
+    VolumeDown,
+    Mute,
+    PlayPause,
+    NextTrack,
+    PrevTrack,
+    Stop,
 }
 
+/// Named keys with their canonical chord name first, then aliases.
+const NAMED_KEYS: &[(Key, &[&str])] = &[
+    (Key::Enter, &["enter", "return"]),
+    (Key::Tab, &["tab"]),
+    (Key::Space, &["space"]),
+    (Key::Backspace, &["backspace"]),
+    (Key::Delete, &["delete", "del"]),
+    (Key::Insert, &["insert", "ins"]),
+    (Key::Escape, &["escape", "esc"]),
+    (Key::Up, &["up"]),
+    (Key::Down, &["down"]),
+    (Key::Left, &["left"]),
+    (Key::Right, &["right"]),
+    (Key::Home, &["home"]),
+    (Key::End, &["end"]),
+    (Key::PageUp, &["pageup", "pgup"]),
+    (Key::PageDown, &["pagedown", "pgdn"]),
+    (Key::PrintScreen, &["printscreen", "print"]),
+    (Key::Pause, &["pause"]),
+    (Key::CapsLock, &["capslock"]),
+    (Key::VolumeUp, &["volumeup"]),
+    (Key::VolumeDown, &["volumedown"]),
+    (Key::Mute, &["mute"]),
+    (Key::PlayPause, &["playpause"]),
+    (Key::NextTrack, &["nexttrack"]),
+    (Key::PrevTrack, &["prevtrack"]),
+    (Key::Stop, &["stop"]),
+];
+
 impl Key {
+    /// Parse a key name (case-insensitive): `a`, `7`, `f5`, `pageup`, ...
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+
+        let mut chars = name.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return match c {
+                'a'..='z' => Some(Key::Letter(c)),
+                '0'..='9' => Some(Key::Digit(c as u8 - b'0')),
+                _ => None,
+            };
+        }
+
+        if let Some(n) = name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+            return (1..=24).contains(&n).then_some(Key::F(n));
+        }
+
+        NAMED_KEYS
+            .iter()
+            .find(|(_, names)| names.contains(&name.as_str()))
+            .map(|(key, _)| *key)
+    }
+
+    /// Canonical chord name.
+    pub fn name(self) -> String {
+        match self {
+            Key::Letter(c) => c.to_string(),
+            Key::Digit(d) => d.to_string(),
+            Key::F(n) => format!("f{}", n),
+            _ => NAMED_KEYS
+                .iter()
+                .find(|(key, _)| *key == self)
+                .map(|(_, names)| names[0].to_string())
+                .unwrap_or_default(),
+        }
+    }
+
     /// Get the enigo key.
     #[cfg(feature = "x11")]
     pub fn to_enigo(self) -> enigo::Key {
         use enigo::Key as EKey;
         match self {
-            Key::A => EKey::Unicode('a'),
-            Key::C => EKey::Unicode('c'),
-            Key::V => EKey::Unicode('v'),
-            Key::X => EKey::Unicode('x'),
+            Key::Letter(c) => EKey::Unicode(c),
+            Key::Digit(d) => EKey::Unicode((b'0' + d) as char),
+            // X11 keysyms XK_F1..XK_F35 are consecutive
+            Key::F(n) => EKey::Other(0xFFBE + u32::from(n) - 1),
             Key::Enter => EKey::Return,
+            Key::Tab => EKey::Tab,
+            Key::Space => EKey::Space,
+            Key::Backspace => EKey::Backspace,
+            Key::Delete => EKey::Delete,
+            Key::Insert => EKey::Insert,
+            Key::Escape => EKey::Escape,
+            Key::Up => EKey::UpArrow,
+            Key::Down => EKey::DownArrow,
+            Key::Left => EKey::LeftArrow,
+            Key::Right => EKey::RightArrow,
+            Key::Home => EKey::Home,
+            Key::End => EKey::End,
+            Key::PageUp => EKey::PageUp,
+            Key::PageDown => EKey::PageDown,
+            Key::PrintScreen => EKey::Print,
+            Key::Pause => EKey::Pause,
+            Key::CapsLock => EKey::CapsLock,
+            Key::VolumeUp => EKey::VolumeUp,
+            Key::VolumeDown => EKey::VolumeDown,
+            Key::Mute => EKey::VolumeMute,
+            Key::PlayPause => EKey::MediaPlayPause,
+            Key::NextTrack => EKey::MediaNextTrack,
+            Key::PrevTrack => EKey::MediaPrevTrack,
+            Key::Stop => EKey::MediaStop,
         }
     }
 
-    /// Get the ydotool key name.
-    pub fn to_ydotool(self) -> &'static str {
-        match self {
-            Key::A => "A",
-            Key::C => "C",
-            Key::V => "V",
-            Key::X => "X",
+    /// Get the ydotool key name (Linux input event code without `KEY_`).
+    pub fn to_ydotool(self) -> String {
+        let name = match self {
+            Key::Letter(c) => return c.to_ascii_uppercase().to_string(),
+            Key::Digit(d) => return d.to_string(),
+            Key::F(n) => return format!("F{}", n),
             Key::Enter => "ENTER",
+            Key::Tab => "TAB",
+            Key::Space => "SPACE",
+            Key::Backspace => "BACKSPACE",
+            Key::Delete => "DELETE",
+            Key::Insert => "INSERT",
+            Key::Escape => "ESC",
+            Key::Up => "UP",
+            Key::Down => "DOWN",
+            Key::Left => "LEFT",
+            Key::Right => "RIGHT",
+            Key::Home => "HOME",
+            Key::End => "END",
+            Key::PageUp => "PAGEUP",
+            Key::PageDown => "PAGEDOWN",
+            Key::PrintScreen => "SYSRQ",
+            Key::Pause => "PAUSE",
+            Key::CapsLock => "CAPSLOCK",
+            Key::VolumeUp => "VOLUMEUP",
+            Key::VolumeDown => "VOLUMEDOWN",
+            Key::Mute => "MUTE",
+            Key::PlayPause => "PLAYPAUSE",
+            Key::NextTrack => "NEXTSONG",
+            Key::PrevTrack => "PREVIOUSSONG",
+            Key::Stop => "STOPCD",
+        };
+        name.to_string()
+    }
+}
+
+/// A key pressed while holding zero or more modifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord {
+    pub modifiers: Vec<Modifier>,
+    pub key: Key,
+}
+
+impl Chord {
+    pub fn new(modifiers: &[Modifier], key: Key) -> Self {
+        Self {
+            modifiers: modifiers.to_vec(),
+            key,
         }
     }
 }
+
+impl FromStr for Chord {
+    type Err = anyhow::Error;
+
+    /// Parse a chord such as `ctrl+shift+t`. Modifiers must come before the key.
+    fn from_str(s: &str) -> Result<Self> {
+        let parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let (key_name, modifier_names) = parts
+            .split_last()
+            .filter(|(key, _)| !key.is_empty())
+            .ok_or_else(|| anyhow!("Empty key chord: '{}'", s))?;
+
+        let mut modifiers = Vec::new();
+        for name in modifier_names {
+            let modifier = Modifier::from_name(name)
+                .ok_or_else(|| anyhow!("Unknown modifier '{}' in chord '{}'", name, s))?;
+            if modifiers.contains(&modifier) {
+                bail!("Duplicate modifier '{}' in chord '{}'", name, s);
+            }
+            modifiers.push(modifier);
+        }
+
+        let key = Key::from_name(key_name)
+            .ok_or_else(|| anyhow!("Unknown key '{}' in chord '{}'", key_name, s))?;
+
+        Ok(Self { modifiers, key })
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for modifier in &self.modifiers {
+            write!(f, "{}+", modifier.name())?;
+        }
+        write!(f, "{}", self.key.name())
+    }
+}
+
+impl Serialize for Chord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Chord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chord() {
+        let chord: Chord = "ctrl+shift+t".parse().unwrap();
+        assert_eq!(
+            chord,
+            Chord::new(&[Modifier::Ctrl, Modifier::Shift], Key::Letter('t'))
+        );
+
+        let chord: Chord = " Super + F4 ".parse().unwrap();
+        assert_eq!(chord, Chord::new(&[Modifier::Super], Key::F(4)));
+
+        let chord: Chord = "esc".parse().unwrap();
+        assert_eq!(chord, Chord::new(&[], Key::Escape));
+    }
+
+    #[test]
+    fn test_parse_chord_errors() {
+        assert!("".parse::<Chord>().is_err());
+        assert!("ctrl+".parse::<Chord>().is_err());
+        assert!("hyper+a".parse::<Chord>().is_err());
+        assert!("ctrl+ctrl+a".parse::<Chord>().is_err());
+        assert!("t+ctrl".parse::<Chord>().is_err());
+        assert!("f25".parse::<Chord>().is_err());
+    }
+
+    #[test]
+    fn test_chord_display_roundtrip() {
+        for spec in ["ctrl+shift+t", "alt+f4", "pageup", "ctrl+7", "playpause"] {
+            let chord: Chord = spec.parse().unwrap();
+            assert_eq!(chord.to_string(), spec);
+        }
+
+        // Aliases display as canonical names
+        let chord: Chord = "control+return".parse().unwrap();
+        assert_eq!(chord.to_string(), "ctrl+enter");
+    }
+
+    #[test]
+    fn test_ydotool_names() {
+        assert_eq!(Key::Letter('t').to_ydotool(), "T");
+        assert_eq!(Key::F(12).to_ydotool(), "F12");
+        assert_eq!(Key::PageDown.to_ydotool(), "PAGEDOWN");
+        assert_eq!(Modifier::Super.to_ydotool(), "LEFTMETA");
+    }
+}
@@ -14,8 +14,10 @@
 
 //! Input injection module.
 //!
-//! Handles simulating keyboard input on X11 and Wayland.
+//! Handles simulating keyboard input on X11 and Wayland, and detecting
+//! the focused window.
 
+mod focus;
 mod injector;
 mod keys;
 mod wayland;
@@ -23,5 +25,6 @@ mod wayland;
 #[cfg(feature = "x11")]
 mod x11;
 
+pub use focus::{create_focus_provider, FocusProvider, FocusedWindow};
 pub use injector::{create_injector, InputInjector};
-pub use keys::{Key, Modifier};
+pub use keys::{Chord, Key, Modifier};
@@ -18,6 +18,7 @@ use anyhow::{anyhow, Result};
 use std::process::Command;
 use tracing::{debug, warn};
 
+use super::injector::read_clipboard;
 use super::keys::{Key, Modifier};
 use super::InputInjector;
 
@@ -82,6 +83,10 @@ impl InputInjector for WaylandInjector {
         "Wayland (ydotool)"
     }
 
+    fn is_available(&self) -> bool {
+        self.ydotool_available
+    }
+
     fn type_text(&self, text: &str) -> Result<()> {
         debug!("Typing text: {} chars", text.len());
 
@@ -89,11 +94,15 @@ impl InputInjector for WaylandInjector {
         self.run_ydotool(&["type", "--clearmodifiers", text])
     }
 
+    fn clipboard_text(&self) -> Result<String> {
+        read_clipboard(&[("wl-paste", &["--no-newline"])])
+    }
+
     fn press_key(&self, key: Key) -> Result<()> {
         debug!("Pressing key: {:?}", key);
 
         let key_name = key.to_ydotool();
-        self.run_ydotool(&["key", key_name])
+        self.run_ydotool(&["key", &key_name])
     }
 
     fn key_combo(&self, modifiers: &[Modifier], key: Key) -> Result<()> {
@@ -105,7 +114,7 @@ impl InputInjector for WaylandInjector {
             combo.push_str(modifier.to_ydotool());
             combo.push('+');
         }
-        combo.push_str(key.to_ydotool());
+        combo.push_str(&key.to_ydotool());
 
         self.run_ydotool(&["key", &combo])
     }
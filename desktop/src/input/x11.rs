@@ -21,6 +21,7 @@ use std::thread;
 use std::time::Duration;
 use tracing::{debug, info, trace};
 
+use super::injector::read_clipboard;
 use super::keys::{Key, Modifier};
 use super::InputInjector;
 
@@ -71,6 +72,13 @@ impl InputInjector for X11Injector {
         Ok(())
     }
 
+    fn clipboard_text(&self) -> Result<String> {
+        read_clipboard(&[
+            ("xclip", &["-selection", "clipboard", "-o"]),
+            ("xsel", &["--clipboard", "--output"]),
+        ])
+    }
+
     fn press_key(&self, key: Key) -> Result<()> {
         debug!("Pressing key: {:?}", key);
 
@@ -18,6 +18,7 @@ pub mod bluetooth;
 pub mod commands;
 pub mod config;
 pub mod crypto;
+pub mod diagnostics;
 pub mod events;
 pub mod input;
 pub mod state;
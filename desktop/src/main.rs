@@ -18,6 +18,7 @@ mod bluetooth;
 mod commands;
 mod config;
 mod crypto;
+mod diagnostics;
 mod events;
 mod icons;
 mod input;
@@ -34,6 +35,7 @@ use tracing::{debug, error, info, warn};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use bluetooth::GattServer;
+use diagnostics::Diagnostics;
 use events::EventProcessor;
 use state::AppState;
 use storage::{TrustedDeviceStore, VoiceCommandStore};
@@ -56,6 +58,11 @@ async fn main() -> Result<()> {
         )
         .init();
 
+    // `prontafon-desktop status` prints diagnostics of the running instance
+    if std::env::args().nth(1).as_deref() == Some("status") {
+        return print_status();
+    }
+
     info!(
         "Starting Prontafon Desktop v{}...",
         env!("CARGO_PKG_VERSION")
@@ -108,15 +115,23 @@ async fn main() -> Result<()> {
 
     // Create application state
     let state = AppState::new();
+    let diagnostics = Diagnostics::new();
+    let diagnostics_path = config.data_dir.join(diagnostics::DIAGNOSTICS_FILE);
 
     // Initialize BLE GATT server
     info!("Initializing BLE GATT server...");
     let (gatt_event_tx, gatt_event_rx) =
         tokio::sync::mpsc::channel::<bluetooth::ConnectionEvent>(32);
-    let gatt_server = Arc::new(Mutex::new(GattServer::new(gatt_event_tx).await?));
+    let gatt_server = Arc::new(Mutex::new(
+        GattServer::new(gatt_event_tx, diagnostics.clone()).await?,
+    ));
     {
         let mut server = gatt_server.lock().await;
         server.set_name(&config.bluetooth.device_name).await?;
+        server.set_advertising(
+            config.bluetooth.advertising,
+            config.bluetooth.manufacturer_tag.as_deref(),
+        );
         server.start().await?;
     }
     info!(
@@ -128,14 +143,34 @@ async fn main() -> Result<()> {
     let (pairing_tx, mut pairing_rx) = tokio::sync::mpsc::channel::<PairingRequest>(8);
 
     // Create event processor
-    let processor = if let Some(store) = voice_command_store.clone() {
+    let mut processor = if let Some(store) = voice_command_store.clone() {
         EventProcessor::with_voice_commands(injector, store, state.clone())
     } else {
         EventProcessor::new(injector)
     };
+    processor.set_diagnostics(diagnostics.clone());
+    processor.set_auto_capitalize(config.input.auto_capitalize);
+    processor.set_programs(config.programs.clone());
+    processor.set_profiles(
+        config.profiles.clone(),
+        input::create_focus_provider(&config.profiles.focus_command),
+    );
+    processor.set_normalize_numbers(config.input.normalize_numbers);
+    processor.set_language(&config.input.language);
+    processor.set_wake_word(config.input.wake_word.as_deref());
+    processor.set_fuzzy_matching(
+        config
+            .input
+            .fuzzy_matching
+            .then_some(config.input.fuzzy_threshold),
+    );
+
+    // Create channel for confirming programs run by voice commands
+    let (confirm_tx, mut confirm_rx) = tokio::sync::mpsc::unbounded_channel();
+    processor.set_confirm_sender(confirm_tx);
 
     // Start system tray
-    let (mut action_rx, tray_handle) = ui::run_tray(state.clone())?;
+    let (mut action_rx, tray_handle) = ui::run_tray(state.clone(), diagnostics.clone())?;
 
     info!("Ready. System tray active.");
 
@@ -143,32 +178,49 @@ async fn main() -> Result<()> {
     let state_gatt = state.clone();
     let mut gatt_event_rx_state = gatt_event_rx;
     let tray_handle_gatt = tray_handle.clone();
+    let gatt_server_task = gatt_server.clone();
+    let trusted_store_gatt = trusted_store.clone();
 
     tokio::spawn(async move {
         let mut processor_gatt = processor;
 
+        // Last mode/health published over the Status characteristic
+        // (the server starts out reporting dictating with a healthy injector)
+        let mut last_desktop_status = (
+            bluetooth::DesktopMode::Dictating,
+            bluetooth::InjectorHealth::Ok,
+        );
+
         // Periodic flush interval for look-ahead and stale word handling
         let mut flush_interval = tokio::time::interval(tokio::time::Duration::from_millis(50));
 
+        // Periodic diagnostics snapshot for the tray and the `status` command
+        let mut diagnostics_interval = tokio::time::interval(tokio::time::Duration::from_secs(2));
+
         loop {
             tokio::select! {
                 Some(event) = gatt_event_rx_state.recv() => {
                     // Update state
                     match &event {
-                        bluetooth::ConnectionEvent::Connected { device_name } => {
+                        bluetooth::ConnectionEvent::Connected { device_id, device_name } => {
                             info!("BLE device connected: {}", device_name);
                             state_gatt.set_connected(device_name.clone());
                             tray_handle_gatt.update(|_| {});
+                            let language = trusted_store_gatt.lock().await.language(device_id);
+                            processor_gatt.set_device_language(language);
+                        }
+                        bluetooth::ConnectionEvent::LanguageAnnounced { language } => {
+                            debug!("BLE recognizer language: {}", language);
                         }
                         bluetooth::ConnectionEvent::Disconnected => {
                             info!("BLE device disconnected");
                             state_gatt.set_disconnected();
                             tray_handle_gatt.update(|_| {});
                         }
-                        bluetooth::ConnectionEvent::TextReceived(text) => {
+                        bluetooth::ConnectionEvent::TextReceived { text, .. } => {
                             debug!("BLE text received: {}", text);
                         }
-                        bluetooth::ConnectionEvent::WordReceived { word, seq, session } => {
+                        bluetooth::ConnectionEvent::WordReceived { word, seq, session, .. } => {
                             debug!("BLE word received: '{}' seq={:?} session={}", word, seq, session);
                             // Word processing is handled by event processor
                         }
@@ -182,7 +234,7 @@ async fn main() -> Result<()> {
                             }).await;
                             info!("✅ Pairing request forwarded to main loop");
                         }
-                        bluetooth::ConnectionEvent::CommandReceived(_) => {
+                        bluetooth::ConnectionEvent::CommandReceived { .. } => {
                             // Will be processed below
                         }
                     }
@@ -191,12 +243,31 @@ async fn main() -> Result<()> {
                     if let Err(e) = processor_gatt.process_event(event).await {
                         error!("Error processing BLE event: {}", e);
                     }
+                    send_feedback(&gatt_server_task, &mut processor_gatt).await;
                 }
                 _ = flush_interval.tick() => {
                     // Periodic flush of pending/stale words
                     if let Err(e) = processor_gatt.process_periodic_flush().await {
                         error!("Error during periodic flush: {}", e);
                     }
+                    send_feedback(&gatt_server_task, &mut processor_gatt).await;
+
+                    // Publish mode/injector changes to the phone
+                    let desktop_status = processor_gatt.desktop_status();
+                    if desktop_status != last_desktop_status {
+                        last_desktop_status = desktop_status;
+                        let (mode, health) = desktop_status;
+                        gatt_server_task.lock().await.set_desktop_status(mode, health).await;
+                        // Dictation-only mode may have been toggled by voice
+                        tray_handle_gatt.update(|_| {});
+                    }
+                }
+                _ = diagnostics_interval.tick() => {
+                    match diagnostics.save_if_changed(&diagnostics_path) {
+                        Ok(true) => tray_handle_gatt.update(|_| {}),
+                        Ok(false) => {}
+                        Err(e) => debug!("Failed to save diagnostics: {}", e),
+                    }
                 }
                 else => {
                     // Channel closed, exit the loop
@@ -254,6 +325,12 @@ async fn main() -> Result<()> {
                                                     error!("Failed to revert command: {}", e);
                                                 }
                                             }
+                                            ui::ManageCommandsEvent::RemovePhrase { command, phrase } => {
+                                                info!("Removing phrase '{}' of command '{}'", phrase, command);
+                                                if let Err(e) = store_cmds.remove_phrase(&command, &phrase) {
+                                                    error!("Failed to remove phrase: {}", e);
+                                                }
+                                            }
                                         }
                                         glib::ControlFlow::Continue
                                     }
@@ -272,12 +349,41 @@ async fn main() -> Result<()> {
                             warn!("Voice command store not available");
                         }
                     }
+                    ui::TrayAction::TogglePause => {
+                        let paused = state.toggle_paused();
+                        info!("Dictation {}", if paused { "paused" } else { "resumed" });
+                        tray_handle.update(|_| {});
+                    }
+                    ui::TrayAction::ToggleDictationOnly => {
+                        let enabled = state.toggle_dictation_only();
+                        info!("Dictation-only mode {}", if enabled { "on" } else { "off" });
+                        tray_handle.update(|_| {});
+                    }
                     ui::TrayAction::Quit => {
                         info!("Quit requested");
                         break;
                     }
                 }
             }
+            Some(request) = confirm_rx.recv() => {
+                let withdrawn = Arc::new(std::sync::atomic::AtomicBool::new(false));
+                let dialog_rx = ui::show_program_confirmation_dialog(&gtk_app, &request.command, &request.description, withdrawn.clone());
+                // Wait in a task; GTK events keep being processed by this loop
+                tokio::spawn(async move {
+                    let mut respond = request.respond;
+                    let approved = tokio::select! {
+                        result = dialog_rx => matches!(result, Ok(ui::ConfirmationResult::Approved)),
+                        _ = respond.closed() => {
+                            // Cancelled by voice or timed out
+                            info!("{} no longer waits for confirmation", request.command);
+                            withdrawn.store(true, std::sync::atomic::Ordering::Relaxed);
+                            return;
+                        }
+                    };
+                    info!("{} {} on the desktop", request.command, if approved { "approved" } else { "declined" });
+                    let _ = respond.send(approved);
+                });
+            }
             Some(request) = pairing_rx.recv() => {
                 let display_name = request.device_name.clone().unwrap_or_else(|| request.device_id.clone());
                 info!("🔔 Received pairing request in main loop for: {}", display_name);
@@ -386,3 +492,34 @@ async fn main() -> Result<()> {
     info!("Prontafon Desktop stopped");
     Ok(())
 }
+
+/// Print connection diagnostics saved by the running instance.
+fn print_status() -> Result<()> {
+    let config = config::Config::load()?;
+    let path = config.data_dir.join(diagnostics::DIAGNOSTICS_FILE);
+    match Diagnostics::load_snapshot(&path) {
+        Ok(snapshot) if snapshot.is_current() => println!("{}", snapshot.stats.report()),
+        Ok(snapshot) => println!(
+            "No current diagnostics, the last ones are from an earlier run (pid {}, saved {}). \
+             Is Prontafon Desktop running?",
+            snapshot.pid,
+            snapshot.saved_at.to_rfc3339()
+        ),
+        Err(_) => println!("No diagnostics available. Is Prontafon Desktop running?"),
+    }
+    Ok(())
+}
+
+/// Send delivery feedback collected by the event processor to the phone.
+async fn send_feedback(gatt_server: &Mutex<GattServer>, processor: &mut EventProcessor) {
+    let feedback = processor.take_feedback();
+    if feedback.is_empty() {
+        return;
+    }
+    let server = gatt_server.lock().await;
+    for item in feedback {
+        if let Err(e) = server.send_feedback(item).await {
+            debug!("Failed to send feedback: {}", e);
+        }
+    }
+}
@@ -44,6 +44,16 @@ pub struct AppState {
 
     /// Command being recorded (if in recording mode).
     pub recording_command: RwLock<Option<String>>,
+
+    /// Why the last phrase recorded was rejected, shown in the recording
+    /// dialog.
+    pub recording_error: RwLock<Option<String>>,
+
+    /// Whether dictation is paused (received input is ignored).
+    pub paused: RwLock<bool>,
+
+    /// Whether dictation-only mode is on (command phrases are typed).
+    pub dictation_only: RwLock<bool>,
 }
 
 impl Default for AppState {
@@ -52,6 +62,9 @@ impl Default for AppState {
             connection_status: RwLock::new(ConnectionStatus::Disconnected),
             connected_device: RwLock::new(None),
             recording_command: RwLock::new(None),
+            recording_error: RwLock::new(None),
+            paused: RwLock::new(false),
+            dictation_only: RwLock::new(false),
         }
     }
 }
@@ -82,6 +95,7 @@ impl AppState {
     /// Start recording mode for a command.
     pub fn start_recording(&self, command: String) {
         *self.recording_command.write() = Some(command);
+        *self.recording_error.write() = None;
     }
 
     /// Stop recording mode and return the command that was being recorded.
@@ -98,4 +112,43 @@ impl AppState {
     pub fn get_recording_command(&self) -> Option<String> {
         self.recording_command.read().clone()
     }
+
+    /// Report why a recorded phrase was rejected; recording continues.
+    pub fn set_recording_error(&self, error: String) {
+        *self.recording_error.write() = Some(error);
+    }
+
+    /// Take the reason the last recorded phrase was rejected, if any.
+    pub fn take_recording_error(&self) -> Option<String> {
+        self.recording_error.write().take()
+    }
+
+    /// Toggle paused dictation and return the new value.
+    pub fn toggle_paused(&self) -> bool {
+        let mut paused = self.paused.write();
+        *paused = !*paused;
+        *paused
+    }
+
+    /// Check if dictation is paused.
+    pub fn is_paused(&self) -> bool {
+        *self.paused.read()
+    }
+
+    /// Turn dictation-only mode on or off.
+    pub fn set_dictation_only(&self, enabled: bool) {
+        *self.dictation_only.write() = enabled;
+    }
+
+    /// Toggle dictation-only mode and return the new value.
+    pub fn toggle_dictation_only(&self) -> bool {
+        let mut dictation_only = self.dictation_only.write();
+        *dictation_only = !*dictation_only;
+        *dictation_only
+    }
+
+    /// Check if dictation-only mode is on.
+    pub fn is_dictation_only(&self) -> bool {
+        *self.dictation_only.read()
+    }
 }
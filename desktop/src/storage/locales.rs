@@ -0,0 +1,188 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Locale packs.
+//!
+//! A locale pack supplies the default phrases of the built-in commands in
+//! one language. English and Czech packs are built in; a pack in
+//! `locales/<language>.json` next to `voice_commands.json` replaces the
+//! built-in pack for that language, or adds a new language.
+
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::OnceLock;
+use tracing::{debug, warn};
+
+use super::voice_commands::normalize_phrases;
+
+/// Directory with user locale packs, in the same directory as the voice
+/// commands file.
+pub const LOCALES_DIR: &str = "locales";
+
+/// Language whose pack supplies the built-in commands' phrases.
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Packs compiled into the binary, by language code.
+const BUILTIN_PACKS: &[(&str, &str)] = &[
+    ("en", include_str!("../../resources/locales/en.json")),
+    ("cs", include_str!("../../resources/locales/cs.json")),
+];
+
+/// Default phrases of the built-in commands in one language.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalePack {
+    /// File format version.
+    pub version: u32,
+    /// Language code, e.g. "cs".
+    pub language: String,
+    /// Display name of the language.
+    #[serde(default)]
+    pub name: String,
+    /// Match phrases ignoring diacritics, for recognizers that drop them
+    /// ("smach" matches "šmach").
+    #[serde(default)]
+    pub ignore_diacritics: bool,
+    /// Word typing the command phrase after it as text; "literal" if
+    /// unset.
+    #[serde(default)]
+    pub literal: Option<String>,
+    /// Word ending a trailing repeat count; "times" if unset.
+    #[serde(default)]
+    pub times: Option<String>,
+    /// Number words of repeat counts from one upward, each listing its
+    /// forms separated by spaces ("dva dvě"); English ones if empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub numbers: Vec<String>,
+    /// Phrases indexed by command code. Commands missing here keep their
+    /// English phrases.
+    pub phrases: HashMap<String, Vec<String>>,
+}
+
+impl LocalePack {
+    /// Parse a pack, normalizing command codes and phrases.
+    fn parse(content: &str, source: &str) -> Result<Self> {
+        let mut pack: Self = serde_json::from_str(content)
+            .with_context(|| format!("Failed to parse locale pack {}", source))?;
+        pack.language = language_code(&pack.language);
+        pack.phrases = pack
+            .phrases
+            .into_iter()
+            .map(|(command, phrases)| {
+                let command = command.trim().to_uppercase();
+                let phrases = normalize_phrases(&command, &phrases);
+                (command, phrases)
+            })
+            .filter(|(_, phrases)| !phrases.is_empty())
+            .collect();
+        Ok(pack)
+    }
+
+    /// The phrases this pack gives a command, if any.
+    pub fn phrases(&self, command: &str) -> Option<&[String]> {
+        self.phrases.get(command).map(Vec::as_slice)
+    }
+}
+
+/// The language code of a locale tag ("cs-CZ", "cs_CZ" → "cs").
+pub fn language_code(tag: &str) -> String {
+    tag.trim()
+        .split(['-', '_'])
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// The built-in English pack.
+pub(super) fn english() -> &'static LocalePack {
+    static ENGLISH: OnceLock<LocalePack> = OnceLock::new();
+    ENGLISH.get_or_init(|| builtin(DEFAULT_LANGUAGE).expect("built-in English locale pack"))
+}
+
+/// The phrases a command has in any pack compiled into the binary.
+pub(super) fn builtin_phrases(command: &str) -> Vec<String> {
+    let mut phrases: Vec<String> = Vec::new();
+    for (language, _) in BUILTIN_PACKS {
+        let pack = builtin(language);
+        for phrase in pack.iter().flat_map(|p| p.phrases(command)).flatten() {
+            if !phrases.contains(phrase) {
+                phrases.push(phrase.clone());
+            }
+        }
+    }
+    phrases
+}
+
+/// A pack compiled into the binary.
+fn builtin(language: &str) -> Option<LocalePack> {
+    let (_, content) = BUILTIN_PACKS.iter().find(|(code, _)| *code == language)?;
+    match LocalePack::parse(content, language) {
+        Ok(pack) => Some(pack),
+        Err(e) => {
+            warn!("Built-in locale pack is broken: {:#}", e);
+            None
+        }
+    }
+}
+
+/// Load the pack for a locale tag: the user's pack if there is one,
+/// otherwise the built-in one.
+pub(super) fn load_locale(config_dir: &Path, tag: &str) -> Result<LocalePack> {
+    let language = language_code(tag);
+    let path = config_dir
+        .join(LOCALES_DIR)
+        .join(format!("{}.json", language));
+
+    if path.exists() {
+        let content =
+            std::fs::read_to_string(&path).with_context(|| format!("Failed to read {:?}", path))?;
+        let mut pack = LocalePack::parse(&content, &path.display().to_string())?;
+        if pack.language != language {
+            warn!(
+                "Locale pack {:?} is for '{}', using it for '{}'",
+                path, pack.language, language
+            );
+            pack.language = language;
+        }
+        debug!("Loaded locale pack from {:?}", path);
+        return Ok(pack);
+    }
+
+    builtin(&language).ok_or_else(|| anyhow!("No locale pack for language '{}'", language))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::builtin_commands;
+
+    #[test]
+    fn test_builtin_packs_cover_all_commands() {
+        for (language, _) in BUILTIN_PACKS {
+            let pack = builtin(language).expect("built-in pack should parse");
+            assert_eq!(pack.language, *language);
+            for command in builtin_commands() {
+                assert!(
+                    pack.phrases(&command.command).is_some(),
+                    "{} pack has no phrases for {}",
+                    language,
+                    command.command
+                );
+            }
+        }
+        assert_eq!(language_code("cs-CZ"), "cs");
+        assert_eq!(language_code(" EN_us "), "en");
+    }
+}
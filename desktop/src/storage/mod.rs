@@ -14,8 +14,11 @@
 
 //! Storage module for voice commands and secure storage.
 
+mod locales;
 mod paired_devices;
+mod snippets;
 mod voice_commands;
 
+pub use locales::language_code;
 pub use paired_devices::TrustedDeviceStore;
-pub use voice_commands::{CommandInfo, VoiceCommandStore};
+pub use voice_commands::{builtin_commands, CommandInfo, PhraseConflict, VoiceCommandStore};
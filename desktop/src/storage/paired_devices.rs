@@ -34,6 +34,10 @@ pub struct TrustedDevice {
     pub first_paired: String,
     /// ISO 8601 timestamp when last connected.
     pub last_connected: String,
+    /// Voice command language for this device ("cs"), overriding the
+    /// language announced by the phone and the configured one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
 }
 
 /// Trusted devices file format.
@@ -88,6 +92,15 @@ impl TrustedDeviceStore {
         self.devices.iter().any(|d| d.device_id == device_id)
     }
 
+    /// The voice command language set for a device, if any.
+    pub fn language(&self, device_id: &str) -> Option<String> {
+        self.devices
+            .iter()
+            .find(|d| d.device_id == device_id)
+            .and_then(|d| d.language.clone())
+            .filter(|l| !l.trim().is_empty())
+    }
+
     /// Add a new trusted device.
     ///
     /// # Arguments
@@ -109,6 +122,7 @@ impl TrustedDeviceStore {
                 device_name,
                 first_paired: now.clone(),
                 last_connected: now,
+                language: None,
             });
             info!("Added new trusted device: {}", device_id);
         }
@@ -224,6 +238,27 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_device_language() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        std::fs::write(
+            temp_dir.path().join("paired_devices.json"),
+            r#"{"version": 1, "devices": [{"device_id": "device-123",
+                "device_name": "My Phone", "first_paired": "2026-01-01T00:00:00Z",
+                "last_connected": "2026-01-01T00:00:00Z", "language": "cs"}]}"#,
+        )?;
+        let mut store = TrustedDeviceStore::new(temp_dir.path())?;
+        assert_eq!(store.language("device-123"), Some("cs".to_string()));
+        assert_eq!(store.language("device-456"), None);
+
+        // Re-pairing keeps the language
+        store.add_trusted("device-123".to_string(), Some("My Phone".to_string()))?;
+        let store = TrustedDeviceStore::new(temp_dir.path())?;
+        assert_eq!(store.language("device-123"), Some("cs".to_string()));
+
+        Ok(())
+    }
+
     #[test]
     fn test_persistence() -> Result<()> {
         let temp_dir = TempDir::new()?;
@@ -237,6 +272,7 @@ mod tests {
         let store = TrustedDeviceStore::new(temp_dir.path())?;
         assert!(store.is_trusted("device-123"));
         assert_eq!(store.devices.len(), 1);
+        assert_eq!(store.language("device-123"), None);
 
         Ok(())
     }
@@ -0,0 +1,89 @@
+// Copyright 2026 Daniel Pelikan
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Text snippet storage.
+//!
+//! Snippets live in `snippets.json` next to `voice_commands.json`. Each one
+//! becomes a command whose action types the snippet text.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use tracing::{debug, warn};
+
+use super::voice_commands::{normalize_phrases, CommandDefinition};
+use crate::commands::{CommandAction, SafetyPolicy};
+
+/// Snippets file name, in the same directory as the voice commands file.
+pub const SNIPPETS_FILE: &str = "snippets.json";
+
+/// A stored block of text bound to spoken phrases.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snippet {
+    /// Snippet name, used as its command code (uppercased).
+    pub name: String,
+    /// Spoken phrases (case-insensitive).
+    pub phrases: Vec<String>,
+    /// Text to type. May contain placeholders such as `{date}`.
+    pub text: String,
+}
+
+/// Snippets file format.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetsFile {
+    /// File format version.
+    pub version: u32,
+    pub snippets: Vec<Snippet>,
+}
+
+/// Load snippets as command definitions indexed by command code.
+pub(super) fn load_snippets(path: &Path) -> Result<HashMap<String, CommandDefinition>> {
+    if !path.exists() {
+        debug!("Snippets file doesn't exist, no snippets loaded");
+        return Ok(HashMap::new());
+    }
+
+    let content =
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {:?}", path))?;
+    let file: SnippetsFile =
+        serde_json::from_str(&content).with_context(|| "Failed to parse snippets.json")?;
+
+    let mut snippets = HashMap::new();
+    for snippet in file.snippets {
+        let command = snippet.name.trim().to_uppercase();
+        if command.is_empty() {
+            warn!("Skipping snippet without a name");
+            continue;
+        }
+
+        let phrases = normalize_phrases(&command, &snippet.phrases);
+        if phrases.is_empty() {
+            warn!("Skipping snippet '{}' without phrases", command);
+            continue;
+        }
+
+        snippets.insert(
+            command.clone(),
+            CommandDefinition {
+                command,
+                phrases,
+                action: CommandAction::Snippet { text: snippet.text },
+                safety: SafetyPolicy::default(),
+            },
+        );
+    }
+
+    Ok(snippets)
+}
@@ -21,42 +21,215 @@ use chrono::{DateTime, Utc};
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 use tracing::{debug, error, info, warn};
-
-/// Normalize a phrase to at most 2 words.
-/// If more than 2 words, take the last 2.
-/// Trims whitespace and converts to lowercase.
+use unicode_normalization::UnicodeNormalization;
+
+use super::locales::{self, LocalePack};
+use super::snippets::{load_snippets, SNIPPETS_FILE};
+use crate::commands::{
+    Attach, CapsMode, CommandAction, FuzzySettings, PhraseTrie, ResolvedCommand, SafetyPolicy,
+};
+use crate::input::{Key, Modifier};
+
+/// Voice commands file format version written by this build. Version 2
+/// stores phrases NFC-normalized; version 3 allows several phrases per
+/// command, which no longer replace the default phrases, and lists removed
+/// default phrases.
+const VOICE_COMMANDS_VERSION: u32 = 3;
+
+/// Normalize a phrase: NFKC-normalized, lowercase words separated by
+/// single spaces.
 fn normalize_phrase(phrase: &str) -> String {
-    let words: Vec<&str> = phrase.split_whitespace().collect();
-    match words.len() {
-        0 => String::new(),
-        1 => words[0].to_lowercase(),
-        2 => format!("{} {}", words[0].to_lowercase(), words[1].to_lowercase()),
-        _ => {
-            // Take last 2 words
-            let last_two = &words[words.len() - 2..];
-            format!(
-                "{} {}",
-                last_two[0].to_lowercase(),
-                last_two[1].to_lowercase()
-            )
+    phrase
+        .nfkc()
+        .collect::<String>()
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Normalize the phrases of a command loaded from a file, dropping empty ones.
+pub(super) fn normalize_phrases(command: &str, phrases: &[String]) -> Vec<String> {
+    phrases
+        .iter()
+        .filter_map(|phrase| {
+            let normalized = normalize_phrase(phrase);
+            if normalized.is_empty() {
+                warn!("Skipping empty phrase for '{}'", command);
+                return None;
+            }
+            Some(normalized)
+        })
+        .collect()
+}
+
+/// A command definition: a command code, its default phrases and its action.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandDefinition {
+    /// The command code (ENTER, COPY, etc.).
+    pub command: String,
+    /// Default spoken phrases (case-insensitive).
+    #[serde(default)]
+    pub phrases: Vec<String>,
+    /// What the command does.
+    pub action: CommandAction,
+    /// Checks before the command runs.
+    #[serde(default, skip_serializing_if = "SafetyPolicy::is_empty")]
+    pub safety: SafetyPolicy,
+}
+
+impl CommandDefinition {
+    /// A built-in command, with its phrases from the English locale pack.
+    fn new(command: &str, action: CommandAction) -> Self {
+        Self {
+            command: command.to_string(),
+            phrases: locales::english()
+                .phrases(command)
+                .map(<[String]>::to_vec)
+                .unwrap_or_default(),
+            action,
+            safety: SafetyPolicy::default(),
+        }
+    }
+
+    /// Get the command code and action for execution.
+    pub fn resolve(&self) -> ResolvedCommand {
+        ResolvedCommand {
+            code: self.command.clone(),
+            action: self.action.clone(),
         }
     }
 }
 
-/// Default phrases for built-in commands (case-insensitive matching).
-pub const DEFAULT_PHRASES: &[(&str, &str)] = &[
-    ("ENTER", "enter"),
-    ("SELECT_ALL", "select all"),
-    ("COPY", "copy"),
-    ("PASTE", "paste"),
-    ("CUT", "cut"),
-    ("CANCEL", "cancel"),
-];
+/// Built-in commands, with English phrases. The active locale pack
+/// supplies their phrases in other languages; definitions in the voice
+/// commands file with the same command code replace them.
+pub fn builtin_commands() -> &'static [CommandDefinition] {
+    static BUILTIN: OnceLock<Vec<CommandDefinition>> = OnceLock::new();
+    BUILTIN.get_or_init(|| {
+        vec![
+            CommandDefinition::new("ENTER", CommandAction::key(Key::Enter)),
+            CommandDefinition::new(
+                "SELECT_ALL",
+                CommandAction::chord(&[Modifier::Ctrl], Key::Letter('a')),
+            ),
+            CommandDefinition::new(
+                "COPY",
+                CommandAction::chord(&[Modifier::Ctrl], Key::Letter('c')),
+            ),
+            CommandDefinition::new(
+                "PASTE",
+                CommandAction::chord(&[Modifier::Ctrl], Key::Letter('v')),
+            ),
+            CommandDefinition::new(
+                "CUT",
+                CommandAction::chord(&[Modifier::Ctrl], Key::Letter('x')),
+            ),
+            CommandDefinition::new("CANCEL", CommandAction::Cancel),
+            CommandDefinition::new("CONFIRM", CommandAction::Confirm),
+            CommandDefinition::new("SCRATCH_THAT", CommandAction::Undo),
+            CommandDefinition::new(
+                "DICTATION_ONLY",
+                CommandAction::DictationOnly { enabled: true },
+            ),
+            CommandDefinition::new(
+                "COMMAND_MODE",
+                CommandAction::DictationOnly { enabled: false },
+            ),
+            CommandDefinition::new("SPELL_ON", CommandAction::Spelling { enabled: true }),
+            CommandDefinition::new("SPELL_OFF", CommandAction::Spelling { enabled: false }),
+            // Navigation and editing (key commands take a repeat count)
+            CommandDefinition::new("BACKSPACE", CommandAction::key(Key::Backspace)),
+            CommandDefinition::new(
+                "DELETE_WORD",
+                CommandAction::chord(&[Modifier::Ctrl], Key::Backspace),
+            ),
+            CommandDefinition::new("TAB", CommandAction::key(Key::Tab)),
+            CommandDefinition::new("ESCAPE", CommandAction::key(Key::Escape)),
+            CommandDefinition::new("GO_LEFT", CommandAction::key(Key::Left)),
+            CommandDefinition::new("GO_RIGHT", CommandAction::key(Key::Right)),
+            CommandDefinition::new("GO_UP", CommandAction::key(Key::Up)),
+            CommandDefinition::new("GO_DOWN", CommandAction::key(Key::Down)),
+            CommandDefinition::new(
+                "WORD_LEFT",
+                CommandAction::chord(&[Modifier::Ctrl], Key::Left),
+            ),
+            CommandDefinition::new(
+                "WORD_RIGHT",
+                CommandAction::chord(&[Modifier::Ctrl], Key::Right),
+            ),
+            CommandDefinition::new("GO_HOME", CommandAction::key(Key::Home)),
+            CommandDefinition::new("GO_END", CommandAction::key(Key::End)),
+            CommandDefinition::new(
+                "SELECT_WORD",
+                CommandAction::chord(&[Modifier::Ctrl, Modifier::Shift], Key::Left),
+            ),
+            CommandDefinition::new(
+                "SELECT_LINE",
+                CommandAction::chord(&[Modifier::Shift], Key::Home),
+            ),
+            // Spoken punctuation
+            CommandDefinition::new("COMMA", CommandAction::punctuation(",", Attach::Left)),
+            CommandDefinition::new("PERIOD", CommandAction::punctuation(".", Attach::Left)),
+            CommandDefinition::new(
+                "QUESTION_MARK",
+                CommandAction::punctuation("?", Attach::Left),
+            ),
+            CommandDefinition::new(
+                "EXCLAMATION_MARK",
+                CommandAction::punctuation("!", Attach::Left),
+            ),
+            CommandDefinition::new("COLON", CommandAction::punctuation(":", Attach::Left)),
+            CommandDefinition::new("SEMICOLON", CommandAction::punctuation(";", Attach::Left)),
+            CommandDefinition::new(
+                "OPEN_QUOTE",
+                CommandAction::punctuation("\"", Attach::Right),
+            ),
+            CommandDefinition::new(
+                "CLOSE_QUOTE",
+                CommandAction::punctuation("\"", Attach::Left),
+            ),
+            CommandDefinition::new("OPEN_PAREN", CommandAction::punctuation("(", Attach::Right)),
+            CommandDefinition::new("CLOSE_PAREN", CommandAction::punctuation(")", Attach::Left)),
+            CommandDefinition::new("HYPHEN", CommandAction::punctuation("-", Attach::Both)),
+            CommandDefinition::new(
+                "NEW_PARAGRAPH",
+                CommandAction::punctuation("\n\n", Attach::Both),
+            ),
+            // Capitalization
+            CommandDefinition::new(
+                "CAPS_ON",
+                CommandAction::Caps {
+                    mode: CapsMode::Title,
+                },
+            ),
+            CommandDefinition::new(
+                "ALL_CAPS",
+                CommandAction::Caps {
+                    mode: CapsMode::Upper,
+                },
+            ),
+            CommandDefinition::new(
+                "CAPS_OFF",
+                CommandAction::Caps {
+                    mode: CapsMode::Normal,
+                },
+            ),
+            CommandDefinition::new(
+                "NO_CAPS",
+                CommandAction::Caps {
+                    mode: CapsMode::LowerNext,
+                },
+            ),
+        ]
+    })
+}
 
 /// A single voice command mapping.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -80,41 +253,208 @@ impl VoiceCommandMapping {
     }
 }
 
+/// The custom phrases of a command.
+#[derive(Debug, Clone, Default)]
+struct CustomPhrases {
+    /// Aliases, in the order they were added.
+    aliases: Vec<VoiceCommandMapping>,
+    /// Default phrases that no longer apply.
+    removed_defaults: Vec<String>,
+}
+
+impl CustomPhrases {
+    fn has_alias(&self, phrase: &str) -> bool {
+        self.aliases.iter().any(|m| m.phrase == phrase)
+    }
+
+    /// Whether a default phrase still applies.
+    fn keeps_default(&self, phrase: &str) -> bool {
+        !self.removed_defaults.iter().any(|p| p == phrase)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.aliases.is_empty() && self.removed_defaults.is_empty()
+    }
+}
+
+/// The default phrases of a definition that still apply.
+fn kept_defaults(definition: &CommandDefinition, custom: Option<&CustomPhrases>) -> Vec<String> {
+    definition
+        .phrases
+        .iter()
+        .filter(|phrase| custom.is_none_or(|c| c.keeps_default(phrase)))
+        .cloned()
+        .collect()
+}
+
+/// How a phrase overlaps with another command's phrase.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictKind {
+    /// Both commands have the same phrase.
+    Same,
+    /// The phrase is the beginning of the other one, so look-ahead has to
+    /// guess whether more words follow.
+    StartOf,
+    /// The phrase begins with the other one.
+    StartsWith,
+}
+
+impl ConflictKind {
+    /// How words `a` overlap with words `b`, if they do.
+    fn of(a: &[String], b: &[String]) -> Option<Self> {
+        if a == b {
+            Some(Self::Same)
+        } else if b.starts_with(a) {
+            Some(Self::StartOf)
+        } else if a.starts_with(b) {
+            Some(Self::StartsWith)
+        } else {
+            None
+        }
+    }
+}
+
+/// A phrase overlapping with a phrase of another command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PhraseConflict {
+    /// The phrase being checked.
+    pub phrase: String,
+    pub kind: ConflictKind,
+    /// The other command's phrase.
+    pub other_phrase: String,
+    /// The other command's code.
+    pub command: String,
+}
+
+impl fmt::Display for PhraseConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            ConflictKind::Same => {
+                write!(
+                    f,
+                    "\"{}\" is already a phrase of {}",
+                    self.phrase, self.command
+                )
+            }
+            ConflictKind::StartOf => write!(
+                f,
+                "\"{}\" is the beginning of \"{}\" ({})",
+                self.phrase, self.other_phrase, self.command
+            ),
+            ConflictKind::StartsWith => write!(
+                f,
+                "\"{}\" begins with \"{}\" ({})",
+                self.phrase, self.other_phrase, self.command
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PhraseConflict {}
+
 /// Voice commands file format.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VoiceCommandsFile {
     /// File format version.
     pub version: u32,
-    /// Custom command mappings (only overrides are stored).
+    /// Custom phrases, any number per command, used alongside the default
+    /// phrases.
     pub mappings: Vec<VoiceCommandMapping>,
+    /// Removed default phrases by command code.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub removed_defaults: BTreeMap<String, Vec<String>>,
+    /// User-defined commands (new commands or replacements for built-ins).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub commands: Vec<CommandDefinition>,
+    /// Fuzzy match thresholds (0.0-1.0) by command code, replacing the
+    /// default one; 1.0 means exact matches only.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fuzzy: BTreeMap<String, f32>,
 }
 
 impl Default for VoiceCommandsFile {
     fn default() -> Self {
         Self {
-            version: 1,
+            version: VOICE_COMMANDS_VERSION,
             mappings: Vec::new(),
+            removed_defaults: BTreeMap::new(),
+            commands: Vec::new(),
+            fuzzy: BTreeMap::new(),
         }
     }
 }
 
+/// Mappings and definitions loaded from the voice commands file.
+#[derive(Debug, Default)]
+struct LoadedCommands {
+    mappings: HashMap<String, CustomPhrases>,
+    definitions: HashMap<String, CommandDefinition>,
+    fuzzy_thresholds: HashMap<String, f32>,
+    /// The file is in an older format and should be rewritten.
+    outdated: bool,
+}
+
 /// Represents a command with its current phrase configuration.
 #[derive(Debug, Clone)]
 pub struct CommandInfo {
     /// The command code (ENTER, COPY, etc.).
     pub command: String,
-    /// The current phrase (custom or default).
-    pub phrase: String,
-    /// Whether this is a custom phrase (true) or default (false).
+    /// Custom phrases.
+    pub aliases: Vec<String>,
+    /// Default phrases, unless they were removed.
+    pub default_phrases: Vec<String>,
+    /// Whether the phrases were customized.
     pub is_custom: bool,
+    /// What the command does.
+    pub action: CommandAction,
+}
+
+/// Which commands a phrase trie holds.
+#[derive(Debug, Clone, PartialEq)]
+enum PhraseFilter {
+    All,
+    /// Only commands with this action.
+    Only(CommandAction),
+    /// All commands but the ones with this action.
+    Except(CommandAction),
 }
 
+impl PhraseFilter {
+    fn keeps(&self, action: &CommandAction) -> bool {
+        match self {
+            Self::All => true,
+            Self::Only(only) => action == only,
+            Self::Except(except) => action != except,
+        }
+    }
+}
+
+/// Cached phrase tries, by the commands they hold.
+type PhraseTries = Arc<RwLock<Vec<(PhraseFilter, Arc<PhraseTrie>)>>>;
+
 /// Voice command store with file watching.
 pub struct VoiceCommandStore {
     /// Path to the voice_commands.json file.
     config_path: PathBuf,
-    /// Current mappings indexed by command code.
-    mappings: Arc<RwLock<HashMap<String, VoiceCommandMapping>>>,
+    /// Custom phrases indexed by command code.
+    mappings: Arc<RwLock<HashMap<String, CustomPhrases>>>,
+    /// User-defined commands indexed by command code.
+    definitions: Arc<RwLock<HashMap<String, CommandDefinition>>>,
+    /// Path to the snippets.json file.
+    snippets_path: PathBuf,
+    /// Snippets (as commands) indexed by command code.
+    snippets: Arc<RwLock<HashMap<String, CommandDefinition>>>,
+    /// Fuzzy match thresholds by command code.
+    fuzzy_thresholds: Arc<RwLock<HashMap<String, f32>>>,
+    /// Default fuzzy match threshold, `None` while fuzzy matching is off.
+    fuzzy_threshold: Arc<RwLock<Option<f32>>>,
+    /// Word that phrases must follow, if any.
+    wake_word: Arc<RwLock<Option<String>>>,
+    /// Locale pack supplying the built-in commands' phrases.
+    locale: Arc<RwLock<LocalePack>>,
+    /// Phrase tries built since the phrases last changed, by the action
+    /// they are limited to (see `phrase_trie_for`).
+    phrase_tries: PhraseTries,
     /// File watcher (kept alive).
     _watcher: Option<RecommendedWatcher>,
 }
@@ -128,6 +468,14 @@ impl VoiceCommandStore {
         let mut store = Self {
             config_path,
             mappings: Arc::new(RwLock::new(HashMap::new())),
+            definitions: Arc::new(RwLock::new(HashMap::new())),
+            snippets_path: config_dir.join(SNIPPETS_FILE),
+            snippets: Arc::new(RwLock::new(HashMap::new())),
+            fuzzy_thresholds: Arc::new(RwLock::new(HashMap::new())),
+            fuzzy_threshold: Arc::new(RwLock::new(None)),
+            wake_word: Arc::new(RwLock::new(None)),
+            locale: Arc::new(RwLock::new(locales::english().clone())),
+            phrase_tries: PhraseTries::default(),
             _watcher: None,
         };
 
@@ -140,7 +488,13 @@ impl VoiceCommandStore {
     /// Create a new store with file watching enabled.
     pub fn new_with_watcher(config_dir: &Path) -> Result<Self> {
         let config_path = config_dir.join("voice_commands.json");
+        let snippets_path = config_dir.join(SNIPPETS_FILE);
         let mappings = Arc::new(RwLock::new(HashMap::new()));
+        let definitions = Arc::new(RwLock::new(HashMap::new()));
+        let snippets = Arc::new(RwLock::new(HashMap::new()));
+        let fuzzy_thresholds = Arc::new(RwLock::new(HashMap::new()));
+        let locale = Arc::new(RwLock::new(locales::english().clone()));
+        let phrase_tries = PhraseTries::default();
 
         // Ensure config directory exists
         std::fs::create_dir_all(config_dir)?;
@@ -148,20 +502,60 @@ impl VoiceCommandStore {
         // Set up file watcher
         let config_path_watch = config_path.clone();
         let mappings_watch = mappings.clone();
+        let definitions_watch = definitions.clone();
+        let snippets_path_watch = snippets_path.clone();
+        let snippets_watch = snippets.clone();
+        let fuzzy_thresholds_watch = fuzzy_thresholds.clone();
+        let locale_watch = locale.clone();
+        let phrase_tries_watch = phrase_tries.clone();
 
         let mut watcher = RecommendedWatcher::new(
             move |res: Result<Event, notify::Error>| {
                 match res {
                     Ok(event) => {
-                        if event.kind.is_modify() || event.kind.is_create() {
+                        if !(event.kind.is_modify() || event.kind.is_create()) {
+                            return;
+                        }
+                        // Reload in the watcher thread
+                        let changed = |path: &Path| {
+                            event
+                                .paths
+                                .iter()
+                                .any(|p| p.file_name() == path.file_name())
+                        };
+                        if changed(&config_path_watch) {
                             debug!("Voice commands file changed, reloading...");
-                            // Reload in the watcher thread
-                            if let Ok(new_mappings) = Self::load_from_file(&config_path_watch) {
-                                let mut guard = mappings_watch.write();
-                                *guard = new_mappings;
-                                info!("Voice commands reloaded: {} custom mappings", guard.len());
+                            if let Ok(loaded) = Self::load_from_file(&config_path_watch) {
+                                info!(
+                                    "Voice commands reloaded: {} custom mappings, {} user commands",
+                                    loaded.mappings.len(),
+                                    loaded.definitions.len()
+                                );
+                                *mappings_watch.write() = loaded.mappings;
+                                *definitions_watch.write() = loaded.definitions;
+                                *fuzzy_thresholds_watch.write() = loaded.fuzzy_thresholds;
+                            }
+                        }
+                        if changed(&snippets_path_watch) {
+                            debug!("Snippets file changed, reloading...");
+                            match load_snippets(&snippets_path_watch) {
+                                Ok(loaded) => {
+                                    info!("Snippets reloaded: {} snippets", loaded.len());
+                                    *snippets_watch.write() = loaded;
+                                }
+                                Err(e) => warn!("Failed to reload snippets: {:#}", e),
                             }
                         }
+                        if changed(&config_path_watch) || changed(&snippets_path_watch) {
+                            phrase_tries_watch.write().clear();
+                            let locale = locale_watch.read();
+                            let definitions = merge_definitions(
+                                &definitions_watch.read(),
+                                &snippets_watch.read(),
+                                &locale,
+                            );
+                            warn_phrase_conflicts(&mappings_watch.read(), &definitions, &locale);
+                        }
                     }
                     Err(e) => {
                         error!("File watcher error: {}", e);
@@ -178,6 +572,14 @@ impl VoiceCommandStore {
         let mut store = Self {
             config_path,
             mappings,
+            definitions,
+            snippets_path,
+            snippets,
+            fuzzy_thresholds,
+            fuzzy_threshold: Arc::new(RwLock::new(None)),
+            wake_word: Arc::new(RwLock::new(None)),
+            locale,
+            phrase_tries,
             _watcher: Some(watcher),
         };
 
@@ -189,18 +591,48 @@ impl VoiceCommandStore {
 
     /// Load mappings from file.
     pub fn load(&mut self) -> Result<()> {
-        let new_mappings = Self::load_from_file(&self.config_path)?;
-        let mut guard = self.mappings.write();
-        *guard = new_mappings;
-        info!("Loaded {} custom voice command mappings", guard.len());
+        let loaded = Self::load_from_file(&self.config_path)?;
+        info!(
+            "Loaded {} custom voice command mappings, {} user commands",
+            loaded.mappings.len(),
+            loaded.definitions.len()
+        );
+        *self.mappings.write() = loaded.mappings;
+        *self.definitions.write() = loaded.definitions;
+        *self.fuzzy_thresholds.write() = loaded.fuzzy_thresholds;
+        if loaded.outdated {
+            info!(
+                "Migrating {:?} to version {}",
+                self.config_path, VOICE_COMMANDS_VERSION
+            );
+            // The old format still loads, so keep going if it can't be rewritten
+            if let Err(e) = self.save() {
+                warn!("Failed to migrate {:?}: {:#}", self.config_path, e);
+            }
+        }
+
+        // A broken snippets file shouldn't disable voice commands
+        match load_snippets(&self.snippets_path) {
+            Ok(snippets) => {
+                info!("Loaded {} snippets", snippets.len());
+                *self.snippets.write() = snippets;
+            }
+            Err(e) => warn!("Failed to load snippets: {:#}", e),
+        }
+        self.phrases_changed();
+        warn_phrase_conflicts(
+            &self.mappings.read(),
+            &self.all_definitions(),
+            &self.locale.read(),
+        );
         Ok(())
     }
 
-    /// Load mappings from a specific file path.
-    fn load_from_file(path: &Path) -> Result<HashMap<String, VoiceCommandMapping>> {
+    /// Load mappings and definitions from a specific file path.
+    fn load_from_file(path: &Path) -> Result<LoadedCommands> {
         if !path.exists() {
             debug!("Voice commands file doesn't exist, using defaults");
-            return Ok(HashMap::new());
+            return Ok(LoadedCommands::default());
         }
 
         let content =
@@ -210,13 +642,13 @@ impl VoiceCommandStore {
             .with_context(|| "Failed to parse voice_commands.json")?;
 
         // Index by command code, validating/normalizing phrases
-        let mut mappings = HashMap::new();
+        let mut mappings: HashMap<String, CustomPhrases> = HashMap::new();
         for mut mapping in file.mappings {
             let command_upper = mapping.command.to_uppercase();
 
-            // Validate and normalize phrase to at most 2 words
-            let phrase_trimmed = mapping.phrase.trim();
-            if phrase_trimmed.is_empty() {
+            // Validate and normalize phrase
+            let normalized = normalize_phrase(&mapping.phrase);
+            if normalized.is_empty() {
                 warn!(
                     "Skipping command '{}' with empty phrase in config file",
                     command_upper
@@ -224,30 +656,106 @@ impl VoiceCommandStore {
                 continue;
             }
 
-            let word_count = phrase_trimmed.split_whitespace().count();
-            let normalized = normalize_phrase(phrase_trimmed);
+            mapping.phrase = normalized;
+            mapping.command = command_upper.clone();
+            let custom = mappings.entry(command_upper).or_default();
+            if !custom.has_alias(&mapping.phrase) {
+                custom.aliases.push(mapping);
+            }
+        }
+
+        for (command, phrases) in file.removed_defaults {
+            let command = command.trim().to_uppercase();
+            let custom = mappings.entry(command.clone()).or_default();
+            for phrase in normalize_phrases(&command, &phrases) {
+                if custom.keeps_default(&phrase) {
+                    custom.removed_defaults.push(phrase);
+                }
+            }
+        }
 
-            if word_count > 2 {
-                warn!(
-                    "Config file has >2 word phrase for '{}', using last 2: '{}' (from '{}')",
-                    command_upper, normalized, phrase_trimmed
-                );
+        // Index user-defined commands, normalizing their phrases the same way
+        let mut definitions: HashMap<String, CommandDefinition> = HashMap::new();
+        for mut definition in file.commands {
+            definition.command = definition.command.trim().to_uppercase();
+            if definition.command.is_empty() {
+                warn!("Skipping command definition without a command code");
+                continue;
             }
 
-            mapping.phrase = normalized;
-            mappings.insert(command_upper, mapping);
+            definition.phrases = normalize_phrases(&definition.command, &definition.phrases);
+
+            definitions.insert(definition.command.clone(), definition);
         }
 
-        Ok(mappings)
+        // Before version 3 a custom phrase replaced the default phrases:
+        // remove the phrases of the command in every language
+        if file.version < 3 {
+            for (command, custom) in mappings.iter_mut() {
+                let mut defaults = locales::builtin_phrases(command);
+                if let Some(definition) = definitions.get(command) {
+                    defaults.extend(definition.phrases.iter().cloned());
+                }
+                for phrase in defaults {
+                    if custom.keeps_default(&phrase) {
+                        custom.removed_defaults.push(phrase);
+                    }
+                }
+            }
+        }
+
+        // Index fuzzy thresholds, keeping them within 0.0-1.0
+        let fuzzy_thresholds = file
+            .fuzzy
+            .into_iter()
+            .map(|(command, threshold)| {
+                let command = command.trim().to_uppercase();
+                if !(0.0..=1.0).contains(&threshold) {
+                    warn!(
+                        "Fuzzy threshold {} for '{}' is outside 0.0-1.0, clamping",
+                        threshold, command
+                    );
+                }
+                (command, threshold.clamp(0.0, 1.0))
+            })
+            .collect();
+
+        Ok(LoadedCommands {
+            mappings,
+            definitions,
+            fuzzy_thresholds,
+            outdated: file.version < VOICE_COMMANDS_VERSION,
+        })
     }
 
     /// Save current mappings to file.
     pub fn save(&self) -> Result<()> {
         let guard = self.mappings.read();
+        let mut custom: Vec<(&String, &CustomPhrases)> = guard.iter().collect();
+        custom.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut commands: Vec<CommandDefinition> =
+            self.definitions.read().values().cloned().collect();
+        commands.sort_by(|a, b| a.command.cmp(&b.command));
 
         let file = VoiceCommandsFile {
-            version: 1,
-            mappings: guard.values().cloned().collect(),
+            version: VOICE_COMMANDS_VERSION,
+            mappings: custom
+                .iter()
+                .flat_map(|(_, c)| c.aliases.iter().cloned())
+                .collect(),
+            removed_defaults: custom
+                .iter()
+                .filter(|(_, c)| !c.removed_defaults.is_empty())
+                .map(|(command, c)| (command.to_string(), c.removed_defaults.clone()))
+                .collect(),
+            commands,
+            fuzzy: self
+                .fuzzy_thresholds
+                .read()
+                .iter()
+                .map(|(command, threshold)| (command.clone(), *threshold))
+                .collect(),
         };
 
         // Ensure parent directory exists
@@ -263,60 +771,234 @@ impl VoiceCommandStore {
     }
 
     /// Get the phrase for a command (custom or default).
-    #[allow(dead_code)]
     pub fn get_phrase(&self, command: &str) -> String {
-        let command_upper = command.to_uppercase();
-        let guard = self.mappings.read();
+        self.phrases(command)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| command.to_lowercase())
+    }
+
+    /// All active phrases of a command: its aliases, then its default
+    /// phrases unless they were removed.
+    pub fn phrases(&self, command: &str) -> Vec<String> {
+        let command_upper = command.trim().to_uppercase();
+        let custom = self.mappings.read().get(&command_upper).cloned();
 
-        if let Some(mapping) = guard.get(&command_upper) {
-            return mapping.phrase.clone();
+        let mut phrases: Vec<String> = custom
+            .iter()
+            .flat_map(|c| c.aliases.iter().map(|m| m.phrase.clone()))
+            .collect();
+        if let Some(definition) = self.definition(&command_upper) {
+            for phrase in kept_defaults(&definition, custom.as_ref()) {
+                if !phrases.contains(&phrase) {
+                    phrases.push(phrase);
+                }
+            }
         }
+        phrases
+    }
 
-        // Return default phrase
-        DEFAULT_PHRASES
+    /// Switch to the locale pack for a language ("cs", "cs-CZ"). The
+    /// current pack stays active if there is none for the language.
+    pub fn set_locale(&self, language: &str) -> Result<()> {
+        let config_dir = self.config_path.parent().unwrap_or(Path::new("."));
+        let pack = locales::load_locale(config_dir, language)?;
+        let missing = builtin_commands()
             .iter()
-            .find(|(cmd, _)| *cmd == command_upper)
-            .map(|(_, phrase)| phrase.to_string())
-            .unwrap_or_else(|| command.to_lowercase())
+            .filter(|d| pack.phrases(&d.command).is_none())
+            .count();
+        if missing > 0 {
+            warn!(
+                "Locale pack '{}' has no phrases for {} built-in commands, using English ones",
+                pack.language, missing
+            );
+        }
+        info!("Voice command locale: {}", pack.language);
+        *self.locale.write() = pack;
+        self.phrases_changed();
+        warn_phrase_conflicts(
+            &self.mappings.read(),
+            &self.all_definitions(),
+            &self.locale.read(),
+        );
+        Ok(())
     }
 
-    /// Set a custom phrase for a command.
-    ///
-    /// Custom phrases support 1 or 2 words for reliable matching.
-    /// If more than 2 words are provided, the last 2 words are used.
-    pub fn set_phrase(&self, command: &str, phrase: &str) -> Result<()> {
-        let command_upper = command.to_uppercase();
+    /// Language code of the active locale pack.
+    pub fn locale(&self) -> String {
+        self.locale.read().language.clone()
+    }
 
-        // Normalize to at most 2 words
-        let final_phrase = normalize_phrase(phrase);
+    /// A built-in command with its phrases from the active locale pack.
+    fn localized(&self, builtin: &CommandDefinition) -> CommandDefinition {
+        localize(builtin, &self.locale.read())
+    }
 
-        // Validate: must not be empty
-        if final_phrase.is_empty() {
-            anyhow::bail!("Custom phrase cannot be empty");
+    /// Get the definition for a command code (user-defined, built-in or
+    /// snippet, in that order of precedence).
+    fn definition(&self, command: &str) -> Option<CommandDefinition> {
+        let command_upper = command.trim().to_uppercase();
+        if let Some(definition) = self.definitions.read().get(&command_upper) {
+            return Some(definition.clone());
         }
+        builtin_commands()
+            .iter()
+            .find(|d| d.command == command_upper)
+            .map(|d| self.localized(d))
+            .or_else(|| self.snippets.read().get(&command_upper).cloned())
+    }
 
-        // Log warning if more than 2 words were provided
-        let word_count = phrase.split_whitespace().count();
-        if word_count > 2 {
-            warn!(
-                "More than 2 words detected for command '{}', using last 2: '{}' (from '{}')",
+    /// All command definitions: built-ins (in order, localized or replaced
+    /// by user definitions), then the remaining user definitions, then snippets.
+    fn all_definitions(&self) -> Vec<CommandDefinition> {
+        merge_definitions(
+            &self.definitions.read(),
+            &self.snippets.read(),
+            &self.locale.read(),
+        )
+    }
+
+    /// Resolve a command code to its action.
+    pub fn resolve(&self, command: &str) -> Option<ResolvedCommand> {
+        self.definition(command).map(|d| d.resolve())
+    }
+
+    /// The safety policy of a command.
+    pub fn safety(&self, command: &str) -> SafetyPolicy {
+        self.definition(command)
+            .map(|d| d.safety)
+            .unwrap_or_default()
+    }
+
+    /// Set the only phrase of a command, replacing its aliases and default
+    /// phrases.
+    #[cfg(test)]
+    pub fn set_phrase(&self, command: &str, phrase: &str) -> Result<()> {
+        let command_upper = command.to_uppercase();
+        let mapping = Self::new_mapping(&command_upper, phrase)?;
+        self.check_conflicts(&command_upper, &mapping.phrase)?;
+        let removed_defaults = self
+            .definition(&command_upper)
+            .map(|d| d.phrases)
+            .unwrap_or_default();
+
+        {
+            let mut guard = self.mappings.write();
+            guard.insert(
                 command_upper,
-                final_phrase,
-                phrase.trim()
+                CustomPhrases {
+                    aliases: vec![mapping],
+                    removed_defaults,
+                },
             );
         }
 
-        let mapping = VoiceCommandMapping::new(final_phrase, command_upper.clone());
+        self.phrases_changed();
+        self.save()
+    }
+
+    /// Add a phrase (alias) for a command, alongside its other phrases.
+    pub fn add_phrase(&self, command: &str, phrase: &str) -> Result<()> {
+        let command_upper = command.to_uppercase();
+        let mapping = Self::new_mapping(&command_upper, phrase)?;
+        self.check_conflicts(&command_upper, &mapping.phrase)?;
 
         {
             let mut guard = self.mappings.write();
-            guard.insert(command_upper, mapping);
+            let custom = guard.entry(command_upper).or_default();
+            if custom.has_alias(&mapping.phrase) {
+                return Ok(());
+            }
+            custom.aliases.push(mapping);
         }
 
+        self.phrases_changed();
         self.save()
     }
 
-    /// Remove custom phrase (revert to default).
+    /// Remove a phrase of a command: an alias, or one of its default
+    /// phrases.
+    pub fn remove_phrase(&self, command: &str, phrase: &str) -> Result<()> {
+        let command_upper = command.to_uppercase();
+        let phrase = normalize_phrase(phrase);
+        let is_default = self
+            .definition(&command_upper)
+            .is_some_and(|d| d.phrases.contains(&phrase));
+
+        {
+            let mut guard = self.mappings.write();
+            let mut custom = guard.remove(&command_upper).unwrap_or_default();
+            let aliases = custom.aliases.len();
+            custom.aliases.retain(|m| m.phrase != phrase);
+            let removed = if custom.aliases.len() < aliases {
+                true
+            } else if is_default && custom.keeps_default(&phrase) {
+                custom.removed_defaults.push(phrase.clone());
+                true
+            } else {
+                false
+            };
+            if !custom.is_empty() {
+                guard.insert(command_upper.clone(), custom);
+            }
+            if !removed {
+                anyhow::bail!("'{}' is not a phrase of {}", phrase, command_upper);
+            }
+        }
+
+        self.phrases_changed();
+        self.save()
+    }
+
+    /// Conflicts of a new phrase for a command with the active phrases of
+    /// the other commands: the same phrase, or one phrase beginning with
+    /// the other, which makes look-ahead ambiguous.
+    pub fn find_conflicts(&self, command: &str, phrase: &str) -> Vec<PhraseConflict> {
+        let command_upper = command.trim().to_uppercase();
+        let phrase = normalize_phrase(phrase);
+        let trie = new_phrase_trie(&self.locale.read());
+        let words = trie.words(&phrase);
+        if words.is_empty() {
+            return Vec::new();
+        }
+
+        let definitions = self.all_definitions();
+        active_phrases(&self.mappings.read(), &definitions)
+            .into_iter()
+            .filter(|(other_command, _)| *other_command != command_upper)
+            .filter_map(|(other_command, other_phrase)| {
+                let kind = ConflictKind::of(&words, &trie.words(&other_phrase))?;
+                Some(PhraseConflict {
+                    phrase: phrase.clone(),
+                    kind,
+                    other_phrase,
+                    command: other_command,
+                })
+            })
+            .collect()
+    }
+
+    /// Fail with the first conflict of a new phrase, if any.
+    fn check_conflicts(&self, command: &str, phrase: &str) -> Result<()> {
+        match self.find_conflicts(command, phrase).into_iter().next() {
+            Some(conflict) => Err(conflict.into()),
+            None => Ok(()),
+        }
+    }
+
+    /// A validated, normalized custom phrase for a command.
+    fn new_mapping(command: &str, phrase: &str) -> Result<VoiceCommandMapping> {
+        let final_phrase = normalize_phrase(phrase);
+
+        // Validate: must not be empty
+        if final_phrase.is_empty() {
+            anyhow::bail!("Custom phrase cannot be empty");
+        }
+
+        Ok(VoiceCommandMapping::new(final_phrase, command))
+    }
+
+    /// Remove custom phrases and restore the default ones.
     pub fn revert_to_default(&self, command: &str) -> Result<()> {
         let command_upper = command.to_uppercase();
 
@@ -325,70 +1007,138 @@ impl VoiceCommandStore {
             guard.remove(&command_upper);
         }
 
+        self.phrases_changed();
         self.save()
     }
 
-    /// Get info for all commands (built-in + custom).
+    /// Get info for all commands (built-in + user-defined).
     pub fn get_all_commands(&self) -> Vec<CommandInfo> {
+        let definitions = self.all_definitions();
         let guard = self.mappings.read();
 
-        DEFAULT_PHRASES
-            .iter()
-            .map(|(cmd, default_phrase)| {
-                let custom = guard.get(*cmd);
+        definitions
+            .into_iter()
+            .map(|definition| {
+                let custom = guard.get(&definition.command);
                 CommandInfo {
-                    command: cmd.to_string(),
-                    phrase: custom
-                        .map(|m| m.phrase.clone())
-                        .unwrap_or_else(|| default_phrase.to_string()),
+                    aliases: custom
+                        .map(|c| c.aliases.iter().map(|m| m.phrase.clone()).collect())
+                        .unwrap_or_default(),
+                    default_phrases: kept_defaults(&definition, custom),
                     is_custom: custom.is_some(),
+                    command: definition.command,
+                    action: definition.action,
                 }
             })
             .collect()
     }
 
-    /// Match a spoken phrase to a command code.
-    /// Returns the command code if found.
-    pub fn match_phrase(&self, spoken: &str) -> Option<String> {
-        let spoken_lower = spoken.trim().to_lowercase();
-        let guard = self.mappings.read();
+    /// All active phrases: custom phrases first, then the default phrases
+    /// of commands that keep them.
+    pub fn phrase_trie(&self) -> Arc<PhraseTrie> {
+        self.cached_phrase_trie(PhraseFilter::All)
+    }
 
-        // Check custom mappings first
-        for (cmd, mapping) in guard.iter() {
-            // Trim the stored phrase as well to handle phrases recorded with trailing whitespace
-            if mapping.phrase.trim().to_lowercase() == spoken_lower {
-                return Some(cmd.clone());
-            }
-        }
+    /// The phrases of commands with the given action, such as the ones
+    /// leaving dictation-only mode, the only ones recognized while it is on.
+    pub fn phrase_trie_for(&self, action: &CommandAction) -> Arc<PhraseTrie> {
+        self.cached_phrase_trie(PhraseFilter::Only(action.clone()))
+    }
 
-        // Check default phrases (only for commands without custom mappings)
-        for (cmd, default_phrase) in DEFAULT_PHRASES {
-            if !guard.contains_key(*cmd) && *default_phrase == spoken_lower {
-                return Some(cmd.to_string());
-            }
+    /// The phrases of commands without the given action, such as all but
+    /// "confirm" while nothing waits for confirmation.
+    pub fn phrase_trie_without(&self, action: &CommandAction) -> Arc<PhraseTrie> {
+        self.cached_phrase_trie(PhraseFilter::Except(action.clone()))
+    }
+
+    /// A phrase trie, built only if the phrases changed since it was last
+    /// needed. Words are matched as they stream in, so tries are reused.
+    fn cached_phrase_trie(&self, filter: PhraseFilter) -> Arc<PhraseTrie> {
+        // Held while building, so a trie isn't cached after a change
+        // that happened during the build
+        let mut tries = self.phrase_tries.write();
+        if let Some((_, trie)) = tries.iter().find(|(cached, _)| *cached == filter) {
+            return trie.clone();
         }
+        let trie = Arc::new(self.build_phrase_trie(&filter));
+        tries.push((filter, trie.clone()));
+        trie
+    }
 
-        None
+    /// Drop the cached phrase tries after phrases, thresholds or the wake
+    /// word changed.
+    fn phrases_changed(&self) {
+        self.phrase_tries.write().clear();
     }
 
-    /// Check if a word could be the first word of a custom 2-word command.
-    /// Used for look-ahead buffering in command matching.
-    pub fn could_start_two_word_command(&self, word: &str) -> bool {
-        let word_lower = word.to_lowercase();
+    fn build_phrase_trie(&self, filter: &PhraseFilter) -> PhraseTrie {
+        let definitions: Vec<CommandDefinition> = self
+            .all_definitions()
+            .into_iter()
+            .filter(|d| filter.keeps(&d.action))
+            .collect();
         let guard = self.mappings.read();
 
-        // Check custom mappings for 2-word phrases
-        for mapping in guard.values() {
-            let phrase = mapping.phrase.trim().to_lowercase();
-            if phrase.contains(' ') {
-                let first_word = phrase.split_whitespace().next().unwrap_or("");
-                if first_word == word_lower {
-                    return true;
-                }
+        let mut trie = new_phrase_trie(&self.locale.read());
+        let mut custom: Vec<(&String, &CustomPhrases)> = guard
+            .iter()
+            .filter(|(command, _)| {
+                *filter == PhraseFilter::All || definitions.iter().any(|d| d.command == **command)
+            })
+            .collect();
+        custom.sort_by(|a, b| a.0.cmp(b.0));
+        for (command, phrases) in custom {
+            for mapping in &phrases.aliases {
+                trie.insert(&mapping.phrase, command);
             }
         }
+        for definition in &definitions {
+            for phrase in kept_defaults(definition, guard.get(&definition.command)) {
+                trie.insert(&phrase, &definition.command);
+            }
+            if definition.action.repeated(1).is_some() {
+                trie.set_countable(&definition.command);
+            }
+        }
+        if let Some(threshold) = *self.fuzzy_threshold.read() {
+            trie.set_fuzzy(FuzzySettings {
+                threshold,
+                thresholds: self.fuzzy_thresholds.read().clone(),
+            });
+        }
+        if let Some(wake_word) = self.wake_word.read().as_deref() {
+            trie.set_wake_word(wake_word);
+        }
+        trie
+    }
+
+    /// Require phrases to follow a wake word ("computer enter"), or not.
+    pub fn set_wake_word(&self, wake_word: Option<&str>) {
+        *self.wake_word.write() = wake_word
+            .map(str::trim)
+            .filter(|w| !w.is_empty())
+            .map(str::to_string);
+        self.phrases_changed();
+    }
+
+    /// Turn fuzzy matching on with a default threshold (0.0-1.0), or off.
+    pub fn set_fuzzy_matching(&self, threshold: Option<f32>) {
+        *self.fuzzy_threshold.write() = threshold.map(|t| t.clamp(0.0, 1.0));
+        self.phrases_changed();
+    }
+
+    /// Match a spoken phrase to a command code.
+    /// Returns the command code if found.
+    #[cfg(test)]
+    pub fn match_phrase(&self, spoken: &str) -> Option<String> {
+        let words: Vec<&str> = spoken.split_whitespace().collect();
+        self.phrase_trie().get(&words).map(str::to_string)
+    }
 
-        false
+    /// Check if more words could extend these words into a command phrase.
+    #[cfg(test)]
+    pub fn could_continue_phrase(&self, words: &[&str]) -> bool {
+        self.phrase_trie().could_continue(words)
     }
 
     /// Get the config file path.
@@ -397,9 +1147,150 @@ impl VoiceCommandStore {
     }
 }
 
+/// A built-in command with its phrases from a locale pack.
+fn localize(builtin: &CommandDefinition, locale: &LocalePack) -> CommandDefinition {
+    let mut definition = builtin.clone();
+    if let Some(phrases) = locale.phrases(&builtin.command) {
+        definition.phrases = phrases.to_vec();
+    }
+    definition
+}
+
+/// All command definitions: built-ins (in order, localized or replaced
+/// by user definitions), then the remaining user definitions, then snippets.
+fn merge_definitions(
+    definitions: &HashMap<String, CommandDefinition>,
+    snippets: &HashMap<String, CommandDefinition>,
+    locale: &LocalePack,
+) -> Vec<CommandDefinition> {
+    let mut all: Vec<CommandDefinition> = builtin_commands()
+        .iter()
+        .map(|builtin| match definitions.get(&builtin.command) {
+            Some(definition) => definition.clone(),
+            None => localize(builtin, locale),
+        })
+        .collect();
+
+    let mut user_only: Vec<CommandDefinition> = definitions
+        .values()
+        .filter(|d| !builtin_commands().iter().any(|b| b.command == d.command))
+        .cloned()
+        .collect();
+    user_only.sort_by(|a, b| a.command.cmp(&b.command));
+    all.extend(user_only);
+
+    let mut snippets: Vec<CommandDefinition> = snippets
+        .values()
+        .filter(|s| !all.iter().any(|d| d.command == s.command))
+        .cloned()
+        .collect();
+    snippets.sort_by(|a, b| a.command.cmp(&b.command));
+    all.extend(snippets);
+
+    all
+}
+
+/// An empty phrase trie comparing words the way a locale pack does.
+fn new_phrase_trie(locale: &LocalePack) -> PhraseTrie {
+    let mut trie = if locale.ignore_diacritics {
+        PhraseTrie::ignoring_diacritics()
+    } else {
+        PhraseTrie::new()
+    };
+    if let Some(literal) = &locale.literal {
+        trie.set_literal_word(literal);
+    }
+    trie.set_count_words(locale.times.as_deref(), &locale.numbers);
+    trie
+}
+
+/// The active phrases of each command, as (command, phrase) pairs:
+/// aliases, then the default phrases unless they were removed.
+fn active_phrases(
+    mappings: &HashMap<String, CustomPhrases>,
+    definitions: &[CommandDefinition],
+) -> Vec<(String, String)> {
+    let mut phrases = Vec::new();
+    for definition in definitions {
+        let custom = mappings.get(&definition.command);
+        let aliases = custom.into_iter().flat_map(|c| &c.aliases);
+        for mapping in aliases {
+            phrases.push((definition.command.clone(), mapping.phrase.clone()));
+        }
+        for phrase in kept_defaults(definition, custom) {
+            phrases.push((definition.command.clone(), phrase));
+        }
+    }
+    phrases
+}
+
+/// Conflicts between the active phrases of different commands, each
+/// reported once with the command of the first phrase.
+fn phrase_conflicts(
+    mappings: &HashMap<String, CustomPhrases>,
+    definitions: &[CommandDefinition],
+    locale: &LocalePack,
+) -> Vec<(String, PhraseConflict)> {
+    let trie = new_phrase_trie(locale);
+    let phrases: Vec<(String, String, Vec<String>)> = active_phrases(mappings, definitions)
+        .into_iter()
+        .map(|(command, phrase)| {
+            let words = trie.words(&phrase);
+            (command, phrase, words)
+        })
+        .collect();
+
+    let mut conflicts = Vec::new();
+    for (i, (command, phrase, words)) in phrases.iter().enumerate() {
+        for (other_command, other_phrase, other_words) in &phrases[i + 1..] {
+            if command == other_command {
+                continue;
+            }
+            if let Some(kind) = ConflictKind::of(words, other_words) {
+                let conflict = PhraseConflict {
+                    phrase: phrase.clone(),
+                    kind,
+                    other_phrase: other_phrase.clone(),
+                    command: other_command.clone(),
+                };
+                conflicts.push((command.clone(), conflict));
+            }
+        }
+    }
+    conflicts
+}
+
+/// Log conflicting phrases, which match only one of their commands.
+fn warn_phrase_conflicts(
+    mappings: &HashMap<String, CustomPhrases>,
+    definitions: &[CommandDefinition],
+    locale: &LocalePack,
+) {
+    for (command, conflict) in phrase_conflicts(mappings, definitions, locale) {
+        if differs_by_diacritics(&conflict) {
+            warn!(
+                "Conflicting phrase of {}: \"{}\" and \"{}\" ({}) differ only by diacritics, \
+                 which the '{}' locale ignores",
+                command, conflict.phrase, conflict.other_phrase, conflict.command, locale.language
+            );
+        } else {
+            warn!("Conflicting phrase of {}: {}", command, conflict);
+        }
+    }
+}
+
+/// Whether a conflict is between phrases that only became the same when
+/// their diacritics were ignored ("šmach" and "smach").
+fn differs_by_diacritics(conflict: &PhraseConflict) -> bool {
+    let exact = PhraseTrie::new();
+    conflict.kind == ConflictKind::Same
+        && exact.words(&conflict.phrase) != exact.words(&conflict.other_phrase)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::commands::DEFAULT_FUZZY_THRESHOLD;
     use tempfile::TempDir;
 
     #[test]
@@ -424,10 +1315,15 @@ mod tests {
 
     #[test]
     fn test_normalize_phrase_more_than_two_words() {
-        // Takes last 2 words
-        assert_eq!(normalize_phrase("one two three"), "two three");
-        assert_eq!(normalize_phrase("a b c d"), "c d");
-        assert_eq!(normalize_phrase("please do enter"), "do enter");
+        assert_eq!(normalize_phrase("one two three"), "one two three");
+        assert_eq!(normalize_phrase("A b  c d"), "a b c d");
+        assert_eq!(normalize_phrase("please do enter"), "please do enter");
+    }
+
+    #[test]
+    fn test_normalize_phrase_unicode() {
+        assert_eq!(normalize_phrase("S\u{30C}MACH"), "šmach");
+        assert_eq!(normalize_phrase("Nový\u{a0}Řádek"), "nový řádek");
     }
 
     #[test]
@@ -453,69 +1349,204 @@ mod tests {
     }
 
     #[test]
-    fn test_set_phrase_more_than_two_words_takes_last_two() -> Result<()> {
+    fn test_set_phrase_more_than_two_words() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let store = VoiceCommandStore::new(temp_dir.path())?;
 
-        store.set_phrase("ENTER", "please do enter")?;
-        // Should take last 2 words
-        assert_eq!(store.get_phrase("ENTER"), "do enter");
+        store.set_phrase("ENTER", "please do  enter")?;
+        assert_eq!(store.get_phrase("ENTER"), "please do enter");
+        assert_eq!(
+            store.match_phrase("Please do enter"),
+            Some("ENTER".to_string())
+        );
+        assert_eq!(store.match_phrase("do enter"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_aliases() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        store.add_phrase("enter", "Submit")?;
+        store.add_phrase("ENTER", "send it")?;
+        store.add_phrase("ENTER", "submit")?;
+        for phrase in ["submit", "send it", "enter", "new line"] {
+            assert_eq!(store.match_phrase(phrase), Some("ENTER".to_string()));
+        }
+        assert_eq!(
+            store.phrases("ENTER"),
+            vec!["submit", "send it", "enter", "new line"]
+        );
+
+        // Default phrases are removed one by one
+        store.remove_phrase("ENTER", "send it")?;
+        store.remove_phrase("ENTER", "new line")?;
+        assert!(store.remove_phrase("ENTER", "hello").is_err());
+        assert!(store.remove_phrase("ENTER", "new line").is_err());
+        assert_eq!(store.phrases("ENTER"), vec!["submit", "enter"]);
+        assert_eq!(store.match_phrase("new line"), None);
+        assert_eq!(store.match_phrase("enter"), Some("ENTER".to_string()));
+        store.remove_phrase("ENTER", "enter")?;
+        assert_eq!(store.match_phrase("enter"), None);
+
+        // All aliases survive a reload and are listed
+        store.add_phrase("ENTER", "okay")?;
+        let reloaded = VoiceCommandStore::new(temp_dir.path())?;
+        let enter = reloaded
+            .get_all_commands()
+            .into_iter()
+            .find(|c| c.command == "ENTER")
+            .unwrap();
+        assert_eq!(enter.aliases, vec!["submit", "okay"]);
+        assert!(enter.default_phrases.is_empty());
+        assert!(enter.is_custom);
+
+        reloaded.revert_to_default("ENTER")?;
+        assert_eq!(reloaded.phrases("ENTER"), vec!["enter", "new line"]);
 
         Ok(())
     }
 
     #[test]
-    fn test_could_start_two_word_command_no_matches() -> Result<()> {
+    fn test_phrase_trie_cache() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        let trie = store.phrase_trie();
+        assert!(Arc::ptr_eq(&trie, &store.phrase_trie()));
+        let exit = store.phrase_trie_for(&CommandAction::Spelling { enabled: false });
+        assert!(!Arc::ptr_eq(&trie, &exit));
+
+        // Rebuilt after a change
+        store.add_phrase("ENTER", "submit")?;
+        let trie = store.phrase_trie();
+        assert_eq!(trie.get(&["submit"]), Some("ENTER"));
+        store.set_wake_word(Some("computer"));
+        assert!(!Arc::ptr_eq(&trie, &store.phrase_trie()));
+        assert_eq!(
+            store.phrase_trie().get(&["computer", "submit"]),
+            Some("ENTER")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_phrase_conflicts() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        // Another command's default phrase
+        let error = store.add_phrase("COPY", "New  Line").unwrap_err();
+        let conflict = error.downcast_ref::<PhraseConflict>().unwrap();
+        assert_eq!(conflict.kind, ConflictKind::Same);
+        assert_eq!(conflict.command, "ENTER");
+        assert_eq!(
+            error.to_string(),
+            "\"new line\" is already a phrase of ENTER"
+        );
+
+        // Prefixes make look-ahead ambiguous, in both directions
+        let conflicts = store.find_conflicts("COPY", "select");
+        assert_eq!(conflicts[0].kind, ConflictKind::StartOf);
+        assert_eq!(
+            conflicts[0].to_string(),
+            "\"select\" is the beginning of \"select all\" (SELECT_ALL)"
+        );
+        let conflicts = store.find_conflicts("COPY", "paste now");
+        assert_eq!(conflicts[0].kind, ConflictKind::StartsWith);
+        assert_eq!(conflicts[0].other_phrase, "paste");
+        assert!(store.set_phrase("COPY", "paste now").is_err());
+
+        // Other commands' aliases, but not the command's own phrases
+        store.add_phrase("ENTER", "submit")?;
+        assert!(store.add_phrase("COPY", "submit").is_err());
+        assert!(store.find_conflicts("ENTER", "enter now").is_empty());
+        assert_eq!(store.phrases("COPY"), vec!["copy"]);
+
+        // Removed default phrases are free again
+        store.remove_phrase("ENTER", "new line")?;
+        store.add_phrase("COPY", "new line")?;
+        assert_eq!(store.match_phrase("new line"), Some("COPY".to_string()));
+
+        // Phrases differing only by accents conflict where they are ignored
+        store.set_locale("cs")?;
+        let conflicts = store.find_conflicts("COPY", "vlozit");
+        assert_eq!(conflicts[0].other_phrase, "vložit");
+        assert!(differs_by_diacritics(&conflicts[0]));
+        let conflicts = store.find_conflicts("COPY", "vložit");
+        assert!(!differs_by_diacritics(&conflicts[0]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_could_continue_phrase_no_matches() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let store = VoiceCommandStore::new(temp_dir.path())?;
 
         // No custom commands yet
-        assert!(!store.could_start_two_word_command("do"));
-        assert!(!store.could_start_two_word_command("hello"));
+        assert!(!store.could_continue_phrase(&["do"]));
+        assert!(!store.could_continue_phrase(&["hello"]));
 
         Ok(())
     }
 
     #[test]
-    fn test_could_start_two_word_command_single_word_phrase() -> Result<()> {
+    fn test_could_continue_phrase_single_word_phrase() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let store = VoiceCommandStore::new(temp_dir.path())?;
 
         // Single word phrase - should not match
+        store.set_phrase("SCRATCH_THAT", "oops")?;
+        assert!(!store.could_continue_phrase(&["oops"]));
+
+        // Unless it's a key command, which a repeat count may follow
         store.set_phrase("ENTER", "submit")?;
-        assert!(!store.could_start_two_word_command("submit"));
+        assert!(store.could_continue_phrase(&["submit"]));
+        assert!(store.could_continue_phrase(&["submit", "three"]));
+        assert!(!store.could_continue_phrase(&["submit", "three", "times"]));
+        assert!(!store.could_continue_phrase(&["submit", "please"]));
 
         Ok(())
     }
 
     #[test]
-    fn test_could_start_two_word_command_two_word_phrase() -> Result<()> {
+    fn test_could_continue_phrase_two_word_phrase() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let store = VoiceCommandStore::new(temp_dir.path())?;
 
         // Two word phrase - first word should match
         store.set_phrase("ENTER", "do enter")?;
-        assert!(store.could_start_two_word_command("do"));
-        assert!(store.could_start_two_word_command("DO")); // case insensitive
-        assert!(!store.could_start_two_word_command("enter")); // second word
-        assert!(!store.could_start_two_word_command("other"));
+        assert!(store.could_continue_phrase(&["do"]));
+        assert!(store.could_continue_phrase(&["DO"])); // case insensitive
+        assert!(!store.could_continue_phrase(&["enter"])); // second word
+        assert!(!store.could_continue_phrase(&["other"]));
 
         Ok(())
     }
 
     #[test]
-    fn test_could_start_two_word_command_multiple_phrases() -> Result<()> {
+    fn test_could_continue_phrase_multiple_phrases() -> Result<()> {
         let temp_dir = TempDir::new()?;
         let store = VoiceCommandStore::new(temp_dir.path())?;
 
         store.set_phrase("ENTER", "do enter")?;
         store.set_phrase("COPY", "grab it")?;
-        store.set_phrase("PASTE", "put")?; // single word
+        store.set_phrase("CANCEL", "put")?; // single word
+
+        assert!(store.could_continue_phrase(&["do"]));
+        assert!(store.could_continue_phrase(&["grab"]));
+        assert!(!store.could_continue_phrase(&["put"])); // single word phrase
+        assert!(!store.could_continue_phrase(&["other"]));
 
-        assert!(store.could_start_two_word_command("do"));
-        assert!(store.could_start_two_word_command("grab"));
-        assert!(!store.could_start_two_word_command("put")); // single word phrase
-        assert!(!store.could_start_two_word_command("other"));
+        // Longer phrases continue after every word but the last
+        store.set_phrase("SCRATCH_THAT", "open new browser tab")?;
+        assert!(store.could_continue_phrase(&["open", "new"]));
+        assert!(store.could_continue_phrase(&["open", "new", "browser"]));
+        assert!(!store.could_continue_phrase(&["open", "new", "browser", "tab"]));
 
         Ok(())
     }
@@ -534,4 +1565,221 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_user_defined_commands_from_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{
+                "version": 1,
+                "mappings": [],
+                "commands": [
+                    {"command": "undo", "phrases": ["Undo That"],
+                     "action": {"type": "key", "keys": "ctrl+shift+z"}},
+                    {"command": "CANCEL", "phrases": ["never mind"],
+                     "action": {"type": "text", "text": "cancelled"}}
+                ]
+            }"#,
+        )?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        // New command is matched and resolved from data
+        assert_eq!(store.match_phrase("undo that"), Some("UNDO".to_string()));
+        let undo = store.resolve("UNDO").expect("UNDO should resolve");
+        assert_eq!(
+            undo.action,
+            CommandAction::chord(&[Modifier::Ctrl, Modifier::Shift], Key::Letter('z'))
+        );
+
+        // A definition with a built-in's code replaces it
+        assert_eq!(store.match_phrase("cancel"), None);
+        assert_eq!(store.match_phrase("never mind"), Some("CANCEL".to_string()));
+
+        // Built-ins keep working, including their 2-word phrases
+        assert_eq!(store.match_phrase("new line"), Some("ENTER".to_string()));
+        assert!(store.could_continue_phrase(&["undo"]));
+
+        let codes: Vec<String> = store
+            .get_all_commands()
+            .into_iter()
+            .map(|c| c.command)
+            .collect();
+        assert_eq!(codes.last().map(String::as_str), Some("UNDO"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_snippets_from_file() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        std::fs::write(
+            temp_dir.path().join(SNIPPETS_FILE),
+            r#"{
+                "version": 1,
+                "snippets": [
+                    {"name": "sign_off", "phrases": ["sign off"],
+                     "text": "Best regards,\nDaniel"},
+                    {"name": "copy", "phrases": ["my copy"], "text": "shadowed"}
+                ]
+            }"#,
+        )?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        assert_eq!(store.match_phrase("sign off"), Some("SIGN_OFF".to_string()));
+        assert!(store.could_continue_phrase(&["sign"]));
+        assert_eq!(
+            store.resolve("SIGN_OFF").map(|c| c.action),
+            Some(CommandAction::Snippet {
+                text: "Best regards,\nDaniel".to_string()
+            })
+        );
+
+        // Built-ins take precedence over snippets with the same name
+        assert_eq!(store.match_phrase("my copy"), None);
+        assert_eq!(
+            store.resolve("COPY").map(|c| c.action),
+            Some(CommandAction::chord(&[Modifier::Ctrl], Key::Letter('c')))
+        );
+
+        // Snippets are listed after the commands
+        let codes: Vec<String> = store
+            .get_all_commands()
+            .into_iter()
+            .map(|c| c.command)
+            .collect();
+        assert_eq!(codes.last().map(String::as_str), Some("SIGN_OFF"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_locale_packs() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+        store.set_phrase("COPY", "grab it")?;
+
+        store.set_locale("cs-CZ")?;
+        assert_eq!(store.locale(), "cs");
+        assert_eq!(store.match_phrase("nový řádek"), Some("ENTER".to_string()));
+        assert_eq!(store.match_phrase("new line"), None);
+        assert!(store.could_continue_phrase(&["vybrat"]));
+        // Custom phrases don't depend on the locale
+        assert_eq!(store.match_phrase("grab it"), Some("COPY".to_string()));
+        // Escape and count words come from the pack
+        let phrases = store.phrase_trie();
+        assert!(phrases.is_literal("doslova"));
+        assert!(!phrases.is_literal("literal"));
+        assert_eq!(
+            phrases.counted_match(&["smazat", "znak", "pět", "krát"]),
+            Some(("BACKSPACE", 5, 4))
+        );
+        assert_eq!(
+            phrases.counted_match(&["smazat", "znak", "dve", "krat"]),
+            Some(("BACKSPACE", 2, 4))
+        );
+
+        // Unknown languages keep the current pack
+        assert!(store.set_locale("xx").is_err());
+        assert_eq!(store.locale(), "cs");
+
+        // A user pack replaces the built-in one, missing commands stay English
+        let locales_dir = temp_dir.path().join(locales::LOCALES_DIR);
+        std::fs::create_dir_all(&locales_dir)?;
+        std::fs::write(
+            locales_dir.join("cs.json"),
+            r#"{"version": 1, "language": "cs", "phrases": {"enter": ["šmach"]}}"#,
+        )?;
+        store.set_locale("cs")?;
+        assert_eq!(store.match_phrase("šmach"), Some("ENTER".to_string()));
+        assert_eq!(store.match_phrase("paste"), Some("PASTE".to_string()));
+
+        store.set_locale("en")?;
+        assert_eq!(store.match_phrase("new line"), Some("ENTER".to_string()));
+        assert!(store.phrase_trie().is_literal("literal"));
+        assert_eq!(store.match_phrase("šmach"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fuzzy_matching() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        std::fs::write(
+            temp_dir.path().join("voice_commands.json"),
+            r#"{"version": 1, "mappings": [], "fuzzy": {"paste": 1.0, "cut": 0.6}}"#,
+        )?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        assert_eq!(store.match_phrase("enter."), Some("ENTER".to_string()));
+        assert_eq!(store.match_phrase("cancell"), None);
+
+        store.set_fuzzy_matching(Some(DEFAULT_FUZZY_THRESHOLD));
+        assert_eq!(store.match_phrase("cancell"), Some("CANCEL".to_string()));
+        assert_eq!(
+            store.match_phrase("Backspase"),
+            Some("BACKSPACE".to_string())
+        );
+        assert_eq!(store.match_phrase("council"), None);
+        assert_eq!(store.match_phrase("come"), None);
+        // Per-command thresholds: exact only, or fuzzy for a short phrase
+        assert_eq!(store.match_phrase("past"), None);
+        assert_eq!(store.match_phrase("cat"), Some("CUT".to_string()));
+        assert_eq!(store.match_phrase("hello"), None);
+
+        // Thresholds survive a save
+        store.set_phrase("ENTER", "submit")?;
+        let reloaded = VoiceCommandStore::new(temp_dir.path())?;
+        reloaded.set_fuzzy_matching(Some(DEFAULT_FUZZY_THRESHOLD));
+        assert_eq!(reloaded.match_phrase("past"), None);
+
+        store.set_fuzzy_matching(None);
+        assert_eq!(store.match_phrase("cancell"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_unnormalized_phrases() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let path = temp_dir.path().join("voice_commands.json");
+        std::fs::write(
+            &path,
+            r#"{"version": 1, "mappings": [
+                {"phrase": "S\u030cmach", "command": "ENTER", "created_at": "2024-01-01T00:00:00Z"}
+            ]}"#,
+        )?;
+        let store = VoiceCommandStore::new(temp_dir.path())?;
+
+        // The file is rewritten with the phrase in its composed form
+        let file: VoiceCommandsFile = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        assert_eq!(file.version, VOICE_COMMANDS_VERSION);
+        assert_eq!(file.mappings[0].phrase, "šmach");
+
+        assert_eq!(store.match_phrase("šmach"), Some("ENTER".to_string()));
+        assert_eq!(
+            store.match_phrase("s\u{30C}mach"),
+            Some("ENTER".to_string())
+        );
+        assert_eq!(store.match_phrase("smach"), None);
+
+        // The custom phrase replaced the default ones, in every language,
+        // in version 1
+        assert_eq!(
+            file.removed_defaults["ENTER"],
+            vec!["enter", "new line", "nový řádek", "odřádkovat"]
+        );
+        assert_eq!(store.match_phrase("new line"), None);
+
+        // Czech ignores diacritics
+        store.set_locale("cs")?;
+        assert_eq!(store.match_phrase("smach"), Some("ENTER".to_string()));
+        assert_eq!(store.match_phrase("novy radek"), None);
+        assert_eq!(
+            store.match_phrase("vybrat vse"),
+            Some("SELECT_ALL".to_string())
+        );
+
+        Ok(())
+    }
 }
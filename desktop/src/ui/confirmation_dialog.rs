@@ -1,11 +1,13 @@
 // Copyright 2026 Daniel Pelikan
 // SPDX-License-Identifier: Apache-2.0
 
-//! Confirmation dialog for pairing approval.
+//! Confirmation dialogs for pairing and program approval.
 
 use gtk4::prelude::*;
 use gtk4::{Application, ApplicationWindow, Box as GtkBox, Button, Label, Orientation};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::oneshot;
 use tracing::info;
 
@@ -28,12 +30,57 @@ pub fn show_confirmation_dialog(
         "🪟 Creating confirmation dialog for device: {}",
         device_name
     );
+    show_dialog(
+        app,
+        "Prontafon - Pairing Request",
+        "Connection Request",
+        &format!("\"{}\"", device_name),
+        "wants to connect to this computer.",
+        "Only approve if you initiated this connection.",
+        None,
+    )
+}
+
+/// Show a confirmation dialog before a voice command runs a program, or
+/// runs at all if its safety policy asks for confirmation on the desktop.
+///
+/// Returns Approved if user clicks Yes, Rejected if user clicks No or closes the dialog.
+/// The dialog closes itself, rejecting, once `withdrawn` is set (e.g. the
+/// command was cancelled by voice).
+pub fn show_program_confirmation_dialog(
+    app: &Application,
+    command: &str,
+    description: &str,
+    withdrawn: Arc<AtomicBool>,
+) -> oneshot::Receiver<ConfirmationResult> {
+    info!("🪟 Creating program confirmation dialog for: {}", command);
+    show_dialog(
+        app,
+        "Prontafon - Confirm Command",
+        &format!("Run {}?", command),
+        description,
+        "was requested by voice command.",
+        "Only approve if you just said this command.",
+        Some(withdrawn),
+    )
+}
+
+/// Build and present a Yes/No dialog.
+fn show_dialog(
+    app: &Application,
+    window_title: &str,
+    heading: &str,
+    subject: &str,
+    question: &str,
+    note: &str,
+    withdrawn: Option<Arc<AtomicBool>>,
+) -> oneshot::Receiver<ConfirmationResult> {
     let (tx, rx) = oneshot::channel();
     let tx = Arc::new(std::sync::Mutex::new(Some(tx)));
 
     let window = ApplicationWindow::builder()
         .application(app)
-        .title("Prontafon - Pairing Request")
+        .title(window_title)
         .default_width(350)
         .default_height(180)
         .modal(true)
@@ -49,21 +96,22 @@ pub fn show_confirmation_dialog(
     main_box.set_margin_end(24);
 
     // Title
-    let title = Label::new(Some("Connection Request"));
+    let title = Label::new(Some(heading));
     title.add_css_class("title-2");
     main_box.append(&title);
 
-    // Device info - make device name prominent
-    let device_label = Label::new(Some(&format!("\"{}\"", device_name)));
-    device_label.add_css_class("title-3");
-    main_box.append(&device_label);
+    // Subject (device or program) - make it prominent
+    let subject_label = Label::new(Some(subject));
+    subject_label.add_css_class("title-3");
+    subject_label.set_wrap(true);
+    main_box.append(&subject_label);
 
     // Question
-    let question = Label::new(Some("wants to connect to this computer."));
+    let question = Label::new(Some(question));
     main_box.append(&question);
 
     // Security note
-    let note = Label::new(Some("Only approve if you initiated this connection."));
+    let note = Label::new(Some(note));
     note.add_css_class("dim-label");
     note.set_wrap(true);
     main_box.append(&note);
@@ -101,7 +149,7 @@ pub fn show_confirmation_dialog(
     let window_approve = window.clone();
     let tx_approve = tx.clone();
     approve_button.connect_clicked(move |_| {
-        info!("✅ User clicked 'Yes' button - approving pairing");
+        info!("✅ User clicked 'Yes' button");
         if let Some(tx) = tx_approve.lock().expect("lock failure").take() {
             let _ = tx.send(ConfirmationResult::Approved);
         }
@@ -132,6 +180,27 @@ pub fn show_confirmation_dialog(
         // If tx was already taken, the dialog was handled - do nothing
     });
 
+    // Close when the request is withdrawn
+    if let Some(withdrawn) = withdrawn {
+        let window_withdrawn = window.clone();
+        let tx_withdrawn = tx.clone();
+        glib::timeout_add_local(Duration::from_millis(250), move || {
+            let mut tx = tx_withdrawn.lock().expect("lock failure");
+            if tx.is_none() {
+                return glib::ControlFlow::Break;
+            }
+            if !withdrawn.load(Ordering::Relaxed) {
+                return glib::ControlFlow::Continue;
+            }
+            info!("Request withdrawn, closing dialog");
+            if let Some(tx) = tx.take() {
+                let _ = tx.send(ConfirmationResult::Rejected);
+            }
+            window_withdrawn.close();
+            glib::ControlFlow::Break
+        });
+    }
+
     info!("📺 Presenting dialog window to user...");
     window.present();
     approve_button.grab_focus(); // Focus on Yes button for quick approval
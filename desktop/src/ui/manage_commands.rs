@@ -5,8 +5,8 @@
 
 use gtk4::prelude::*;
 use gtk4::{
-    Application, ApplicationWindow, Box as GtkBox, Button, Label, ListBox, ListBoxRow, Orientation,
-    ScrolledWindow, SelectionMode,
+    Application, ApplicationWindow, Box as GtkBox, Button, FlowBox, Label, ListBox, ListBoxRow,
+    Orientation, ScrolledWindow, SelectionMode,
 };
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -25,8 +25,10 @@ pub enum ManageCommandsEvent {
     StartRecording(String),
     /// Cancel recording.
     CancelRecording,
-    /// Revert command to default phrase.
+    /// Remove a command's custom phrases and restore its default ones.
     RevertToDefault(String),
+    /// Remove one phrase (custom or default) of a command.
+    RemovePhrase { command: String, phrase: String },
 }
 
 /// Create and show the Manage Commands window (non-modal).
@@ -57,7 +59,7 @@ pub fn show_manage_commands_window(
     main_box.append(&header);
 
     let subtitle = Label::new(Some(
-        "Click \"Record\" to set a custom phrase for a command.",
+        "Click \"Record\" to add a phrase for a command, or ✕ to remove one.",
     ));
     subtitle.add_css_class("dim-label");
     main_box.append(&subtitle);
@@ -157,33 +159,59 @@ fn create_command_row(
     hbox.set_margin_start(12);
     hbox.set_margin_end(12);
 
-    // Command name
+    // Command name with a summary of what it does
+    let name_box = GtkBox::new(Orientation::Vertical, 2);
     let cmd_label = Label::new(Some(&cmd_info.command));
     cmd_label.add_css_class("heading");
     cmd_label.set_width_chars(12);
     cmd_label.set_xalign(0.0);
-    hbox.append(&cmd_label);
-
-    // Current phrase
-    let phrase_text = if cmd_info.is_custom {
-        format!("\"{}\" ✱", cmd_info.phrase)
-    } else {
-        format!("\"{}\" (default)", cmd_info.phrase)
-    };
-    let phrase_label = Label::new(Some(&phrase_text));
-    phrase_label.set_hexpand(true);
-    phrase_label.set_xalign(0.0);
-    if cmd_info.is_custom {
-        phrase_label.add_css_class("accent");
-    } else {
-        phrase_label.add_css_class("dim-label");
-    }
-    hbox.append(&phrase_label);
+    name_box.append(&cmd_label);
+
+    let action_text = cmd_info.action.to_string();
+    let action_label = Label::new(Some(&action_text));
+    action_label.add_css_class("dim-label");
+    action_label.add_css_class("caption");
+    action_label.set_xalign(0.0);
+    action_label.set_max_width_chars(40);
+    action_label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+    action_label.set_tooltip_text(Some(&action_text));
+    name_box.append(&action_label);
+    hbox.append(&name_box);
 
     // Check if this command is being recorded
     let is_recording_this = recording_command.as_ref() == Some(&cmd_info.command);
     let is_recording_other = recording_command.is_some() && !is_recording_this;
 
+    // All phrases, each with a remove button: custom ones marked, then the defaults
+    let phrases_box = FlowBox::new();
+    phrases_box.set_selection_mode(SelectionMode::None);
+    phrases_box.set_hexpand(true);
+    phrases_box.set_valign(gtk4::Align::Center);
+    let phrases = cmd_info.aliases.iter().map(|phrase| (phrase, true)).chain(
+        cmd_info
+            .default_phrases
+            .iter()
+            .map(|phrase| (phrase, false)),
+    );
+    for (phrase, custom) in phrases {
+        phrases_box.insert(
+            &create_phrase_chip(
+                &cmd_info.command,
+                phrase,
+                custom,
+                !is_recording_this,
+                event_tx,
+            ),
+            -1,
+        );
+    }
+    if cmd_info.aliases.is_empty() && cmd_info.default_phrases.is_empty() {
+        let empty_label = Label::new(Some("(no phrase)"));
+        empty_label.add_css_class("dim-label");
+        phrases_box.insert(&empty_label, -1);
+    }
+    hbox.append(&phrases_box);
+
     // Record/Cancel button
     let record_button = if is_recording_this {
         let btn = Button::with_label("Cancel");
@@ -234,6 +262,48 @@ fn create_command_row(
     row
 }
 
+/// Create a phrase with a button removing it.
+fn create_phrase_chip(
+    command: &str,
+    phrase: &str,
+    custom: bool,
+    removable: bool,
+    event_tx: &mpsc::UnboundedSender<ManageCommandsEvent>,
+) -> GtkBox {
+    let chip = GtkBox::new(Orientation::Horizontal, 2);
+
+    let text = if custom {
+        format!("\"{}\" ✱", phrase)
+    } else {
+        format!("\"{}\"", phrase)
+    };
+    let label = Label::new(Some(&text));
+    label.add_css_class(if custom { "accent" } else { "dim-label" });
+    chip.append(&label);
+
+    let remove_button = Button::from_icon_name("window-close-symbolic");
+    remove_button.add_css_class("flat");
+    remove_button.add_css_class("circular");
+    remove_button.set_tooltip_text(Some("Remove this phrase"));
+    remove_button.set_sensitive(removable);
+
+    let command = command.to_string();
+    let phrase = phrase.to_string();
+    let tx_remove = event_tx.clone();
+    remove_button.connect_clicked(move |_| {
+        info!("Remove button clicked for '{}' of {}", phrase, command);
+        if let Err(e) = tx_remove.send(ManageCommandsEvent::RemovePhrase {
+            command: command.clone(),
+            phrase: phrase.clone(),
+        }) {
+            error!("Failed to send RemovePhrase event: {}", e);
+        }
+    });
+    chip.append(&remove_button);
+
+    chip
+}
+
 /// Recording timeout duration in seconds.
 const RECORDING_TIMEOUT_SECS: u64 = 30;
 
@@ -275,6 +345,14 @@ pub fn show_recording_dialog(app: &Application, command: &str, state: Arc<AppSta
     countdown_label.add_css_class("title-4");
     main_box.append(&countdown_label);
 
+    // Rejected phrase (conflicts with another command)
+    let error_label = Label::new(None);
+    error_label.add_css_class("error");
+    error_label.set_wrap(true);
+    error_label.set_max_width_chars(40);
+    error_label.set_visible(false);
+    main_box.append(&error_label);
+
     // Cancel button
     let button_box = GtkBox::new(Orientation::Horizontal, 0);
     button_box.set_halign(gtk4::Align::Center);
@@ -307,6 +385,7 @@ pub fn show_recording_dialog(app: &Application, command: &str, state: Arc<AppSta
     let dialog_check = dialog.clone();
     let state_check = state.clone();
     let countdown_label_ref = countdown_label.clone();
+    let error_label_ref = error_label.clone();
     let start_time_ref = start_time.clone();
 
     glib::timeout_add_local(Duration::from_millis(100), move || {
@@ -316,6 +395,13 @@ pub fn show_recording_dialog(app: &Application, command: &str, state: Arc<AppSta
             return glib::ControlFlow::Break;
         }
 
+        // Phrase rejected: show why and give time for another one
+        if let Some(error) = state_check.take_recording_error() {
+            error_label_ref.set_text(&format!("{} — say another phrase.", error));
+            error_label_ref.set_visible(true);
+            *start_time_ref.borrow_mut() = Instant::now();
+        }
+
         // Check timeout
         let elapsed = start_time_ref.borrow().elapsed().as_secs();
         let remaining = RECORDING_TIMEOUT_SECS.saturating_sub(elapsed);
@@ -18,7 +18,9 @@ mod confirmation_dialog;
 mod manage_commands;
 mod tray;
 
-pub use confirmation_dialog::{show_confirmation_dialog, ConfirmationResult};
+pub use confirmation_dialog::{
+    show_confirmation_dialog, show_program_confirmation_dialog, ConfirmationResult,
+};
 pub use manage_commands::{
     show_manage_commands_window, show_recording_dialog, ManageCommandsEvent,
 };
@@ -15,16 +15,23 @@
 //! System tray implementation using ksni.
 
 use anyhow::Result;
-use ksni::{self, menu::StandardItem, Handle, MenuItem, Tray, TrayService};
+use ksni::{
+    self,
+    menu::{CheckmarkItem, StandardItem},
+    Handle, MenuItem, Tray, TrayService,
+};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tracing::{error, info};
 
+use crate::diagnostics::Diagnostics;
 use crate::state::{AppState, ConnectionStatus};
 
 /// Actions that can be triggered from the tray menu.
 #[derive(Debug, Clone)]
 pub enum TrayAction {
+    TogglePause,
+    ToggleDictationOnly,
     ManageCommands,
     Quit,
 }
@@ -32,12 +39,21 @@ pub enum TrayAction {
 /// System tray icon and menu.
 pub struct ProntafonTray {
     state: Arc<AppState>,
+    diagnostics: Arc<Diagnostics>,
     action_tx: mpsc::UnboundedSender<TrayAction>,
 }
 
 impl ProntafonTray {
-    pub fn new(state: Arc<AppState>, action_tx: mpsc::UnboundedSender<TrayAction>) -> Self {
-        Self { state, action_tx }
+    pub fn new(
+        state: Arc<AppState>,
+        diagnostics: Arc<Diagnostics>,
+        action_tx: mpsc::UnboundedSender<TrayAction>,
+    ) -> Self {
+        Self {
+            state,
+            diagnostics,
+            action_tx,
+        }
     }
 }
 
@@ -94,8 +110,41 @@ impl Tray for ProntafonTray {
             ..Default::default()
         }));
 
+        // Link diagnostics
+        if status == ConnectionStatus::Connected {
+            items.push(MenuItem::Standard(StandardItem {
+                label: self.diagnostics.snapshot().summary(),
+                enabled: false,
+                ..Default::default()
+            }));
+        }
+
         items.push(MenuItem::Separator);
 
+        // Pause/resume dictation
+        let pause_label = if self.state.is_paused() {
+            "Resume Dictation"
+        } else {
+            "Pause Dictation"
+        };
+        items.push(MenuItem::Standard(StandardItem {
+            label: pause_label.to_string(),
+            activate: Box::new(|tray: &mut Self| {
+                let _ = tray.action_tx.send(TrayAction::TogglePause);
+            }),
+            ..Default::default()
+        }));
+
+        // Dictation-only mode (command phrases are typed)
+        items.push(MenuItem::Checkmark(CheckmarkItem {
+            label: "Dictation Only".to_string(),
+            checked: self.state.is_dictation_only(),
+            activate: Box::new(|tray: &mut Self| {
+                let _ = tray.action_tx.send(TrayAction::ToggleDictationOnly);
+            }),
+            ..Default::default()
+        }));
+
         // Manage Commands
         items.push(MenuItem::Standard(StandardItem {
             label: "Manage Commands...".to_string(),
@@ -131,10 +180,11 @@ impl Tray for ProntafonTray {
 /// Run the system tray service.
 pub fn run_tray(
     state: Arc<AppState>,
+    diagnostics: Arc<Diagnostics>,
 ) -> Result<(mpsc::UnboundedReceiver<TrayAction>, Handle<ProntafonTray>)> {
     let (action_tx, action_rx) = mpsc::unbounded_channel();
 
-    let tray = ProntafonTray::new(state, action_tx);
+    let tray = ProntafonTray::new(state, diagnostics, action_tx);
     let service = TrayService::new(tray);
     let handle = service.handle();
 